@@ -64,6 +64,16 @@ fn main() {
     combine_with_exported_module!(&mut lib, "rhai_sci_moving", moving_functions);
     combine_with_exported_module!(&mut lib, "rhai_sci_validate", validation_functions);
     combine_with_exported_module!(&mut lib, "rhai_sci_trig", trig_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_arithmetic", arithmetic_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_logical", logical_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_histograms", histogram_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_distributions", distribution_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_hypothesis", hypothesis_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_polynomials", polynomial_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_optimization", optimization_functions);
+    combine_with_exported_module!(&mut lib, "rhai_sci_complex", complex_functions);
+    #[cfg(feature = "nalgebra")]
+    combine_with_exported_module!(&mut lib, "rhai_sci_sparse", sparse_functions);
     engine.register_global_module(rhai::Shared::new(lib));
 
     // Extract metadata
@@ -196,6 +206,14 @@ fn main() {
                         .replace(" ", "_")
                         .replace(":", "_")
                         .replace("->", "_")
+                        .replace("$", "_")
+                        .replace("!=", "op_not_equals")
+                        .replace("==", "op_equals")
+                        .replace("**", "op_power")
+                        .replace("+", "op_plus")
+                        .replace("-", "op_minus")
+                        .replace("*", "op_times")
+                        .replace("/", "op_divide")
                         .replace(",", "_").replace("____", "_").replace("___", "_").replace("__", "_").to_lowercase(),
                     clean_code.replace("\"", "\\\"")
                 )
@@ -221,6 +239,15 @@ mod functions {
     include!("src/validate.rs");
     include!("src/patterns.rs");
     include!("src/trig.rs");
+    include!("src/arithmetic.rs");
+    include!("src/logical.rs");
+    include!("src/histograms.rs");
+    include!("src/distributions.rs");
+    include!("src/hypothesis.rs");
+    include!("src/polynomials.rs");
+    include!("src/optimization.rs");
+    include!("src/complex.rs");
+    include!("src/sparse.rs");
 }
 
 #[cfg(feature = "metadata")]
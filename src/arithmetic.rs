@@ -0,0 +1,287 @@
+use rhai::plugin::*;
+
+#[export_module]
+pub mod arithmetic_functions {
+    use crate::if_broadcastable_do_elementwise;
+    use rhai::{Array, Dynamic, EvalAltResult, FLOAT, INT};
+
+    /// Applies an arithmetic operation element-by-element, broadcasting the inputs against each
+    /// other. If both elements are INT and `int_op` returns a value, the result is an INT.
+    /// Otherwise, the elements are converted to FLOAT and `float_op` is used.
+    fn elementwise(
+        x: Dynamic,
+        y: Dynamic,
+        int_op: fn(INT, INT) -> Option<INT>,
+        float_op: fn(FLOAT, FLOAT) -> FLOAT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_broadcastable_do_elementwise(x, y, |a, b| {
+            if a.is_int() && b.is_int() {
+                if let Some(v) = int_op(a.as_int().unwrap(), b.as_int().unwrap()) {
+                    return Ok(Dynamic::from_int(v));
+                }
+            }
+            let as_float = |d: &Dynamic| {
+                if d.is_float() {
+                    d.as_float().unwrap()
+                } else {
+                    d.as_int().unwrap() as FLOAT
+                }
+            };
+            Ok(Dynamic::from_float(float_op(as_float(a), as_float(b))))
+        })
+    }
+
+    /// Adds two numeric arrays (or an array and a scalar) element-by-element. Arrays with
+    /// different shapes are broadcast against each other: dimensions are aligned from the end,
+    /// and any dimension of size 1 is expanded to match the other input. The `+` operator only
+    /// adds arrays and scalars, since Rhai already uses it to concatenate two arrays.
+    /// ```typescript
+    /// let x = plus([[1, 2], [3, 4]], [10, 20]);
+    /// assert_eq(x, [[11, 22], [13, 24]]);
+    /// ```
+    /// ```typescript
+    /// let x = plus([1, 2, 3], 0.5);
+    /// assert_eq(x, [1.5, 2.5, 3.5]);
+    /// ```
+    #[rhai_fn(name = "plus", return_raw)]
+    pub fn plus(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        elementwise(x, y, INT::checked_add, |a, b| a + b)
+    }
+
+    /// Subtracts two numeric arrays (or an array and a scalar) element-by-element, broadcasting
+    /// them against each other as needed.
+    /// ```typescript
+    /// let x = minus([[1, 2], [3, 4]], [[1], [2]]);
+    /// assert_eq(x, [[0, 1], [1, 2]]);
+    /// ```
+    #[rhai_fn(name = "minus", return_raw)]
+    pub fn minus(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        elementwise(x, y, INT::checked_sub, |a, b| a - b)
+    }
+
+    /// Multiplies two numeric arrays (or an array and a scalar) element-by-element, broadcasting
+    /// them against each other as needed. Use `mtimes` for matrix multiplication.
+    /// ```typescript
+    /// let x = times([[1, 2], [3, 4]], [[1, 2], [3, 4]]);
+    /// assert_eq(x, [[1, 4], [9, 16]]);
+    /// ```
+    #[rhai_fn(name = "times", return_raw)]
+    pub fn times(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        elementwise(x, y, INT::checked_mul, |a, b| a * b)
+    }
+
+    /// Divides two numeric arrays (or an array and a scalar) element-by-element, broadcasting them
+    /// against each other as needed. The result is always FLOAT. Use `mrdivide` to divide by a
+    /// matrix.
+    /// ```typescript
+    /// let x = rdivide([1, 2, 3], 2);
+    /// assert_eq(x, [0.5, 1.0, 1.5]);
+    /// ```
+    #[rhai_fn(name = "rdivide", return_raw)]
+    pub fn rdivide(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        elementwise(x, y, |_, _| None, |a, b| a / b)
+    }
+
+    /// Raises the elements of a numeric array (or scalar) to a power element-by-element,
+    /// broadcasting the inputs against each other as needed.
+    /// ```typescript
+    /// let x = power([1, 2, 3], 2);
+    /// assert_eq(x, [1, 4, 9]);
+    /// ```
+    /// ```typescript
+    /// let x = power(2, [-1, 0.5]);
+    /// assert_eq(x, [0.5, sqrt(2.0)]);
+    /// ```
+    #[rhai_fn(name = "power", return_raw)]
+    pub fn power(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        elementwise(
+            x,
+            y,
+            |a, b| u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+            |a, b| a.powf(b),
+        )
+    }
+
+    /// Adds a scalar to every element of a numeric array. Adding two arrays with `+` concatenates
+    /// them as usual in Rhai; use `plus` to add them element-by-element.
+    /// ```typescript
+    /// let x = [1, 2, 3] + 1;
+    /// assert_eq(x, [2, 3, 4]);
+    /// ```
+    /// ```typescript
+    /// let x = [1, 2] + [3];
+    /// assert_eq(x, [1, 2, 3]);
+    /// ```
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add_array_int(x: Array, y: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        plus(Dynamic::from_array(x), Dynamic::from_int(y))
+    }
+
+    /// Adds a FLOAT to every element of a numeric array.
+    /// ```typescript
+    /// let x = [1, 2, 3] + 0.5;
+    /// assert_eq(x, [1.5, 2.5, 3.5]);
+    /// ```
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add_array_float(x: Array, y: FLOAT) -> Result<Dynamic, Box<EvalAltResult>> {
+        plus(Dynamic::from_array(x), Dynamic::from_float(y))
+    }
+
+    /// Adds an INT to every element of a numeric array.
+    /// ```typescript
+    /// let x = 1 + [1, 2, 3];
+    /// assert_eq(x, [2, 3, 4]);
+    /// ```
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add_int_array(x: INT, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        plus(Dynamic::from_int(x), Dynamic::from_array(y))
+    }
+
+    /// Adds a FLOAT to every element of a numeric array.
+    /// ```typescript
+    /// let x = 1.5 + [1, 2, 3];
+    /// assert_eq(x, [2.5, 3.5, 4.5]);
+    /// ```
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add_float_array(x: FLOAT, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        plus(Dynamic::from_float(x), Dynamic::from_array(y))
+    }
+
+    /// Subtracts two numeric arrays element-by-element with the same broadcasting rules as
+    /// `minus`.
+    /// ```typescript
+    /// let x = [[5, 6], [7, 8]] - [1, 2];
+    /// assert_eq(x, [[4, 4], [6, 6]]);
+    /// ```
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract_arrays(x: Array, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        minus(Dynamic::from_array(x), Dynamic::from_array(y))
+    }
+
+    /// Subtracts a scalar from every element of a numeric array.
+    /// ```typescript
+    /// let x = [1, 2, 3] - 1;
+    /// assert_eq(x, [0, 1, 2]);
+    /// ```
+    /// ```typescript
+    /// let x = [] - 1;
+    /// assert_eq(x, []);
+    /// ```
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract_array_scalar(x: Array, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        minus(Dynamic::from_array(x), y)
+    }
+
+    /// Subtracts every element of a numeric array from a scalar.
+    /// ```typescript
+    /// let x = 1 - [1, 2, 3];
+    /// assert_eq(x, [0, -1, -2]);
+    /// ```
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract_scalar_array(x: Dynamic, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        minus(x, Dynamic::from_array(y))
+    }
+
+    /// Negates every element of a numeric array.
+    /// ```typescript
+    /// let x = -[[1, -2], [3.5, 2.0]];
+    /// assert_eq(x, [[-1, 2], [-3.5, -2.0]]);
+    /// ```
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn negate_array(x: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        times(Dynamic::from_array(x), Dynamic::from_int(-1))
+    }
+
+    /// Multiplies two numeric arrays element-by-element with the same broadcasting rules as
+    /// `times`. Use `mtimes` for matrix multiplication.
+    /// ```typescript
+    /// let x = [[1, 2], [3, 4]] * [[1, 2], [3, 4]];
+    /// assert_eq(x, [[1, 4], [9, 16]]);
+    /// ```
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn multiply_arrays(x: Array, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        times(Dynamic::from_array(x), Dynamic::from_array(y))
+    }
+
+    /// Multiplies every element of a numeric array by a scalar.
+    /// ```typescript
+    /// let x = [1, 2, 3] * 2;
+    /// assert_eq(x, [2, 4, 6]);
+    /// ```
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn multiply_array_scalar(x: Array, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        times(Dynamic::from_array(x), y)
+    }
+
+    /// Multiplies every element of a numeric array by a scalar.
+    /// ```typescript
+    /// let x = 0.5 * [1, 2, 3];
+    /// assert_eq(x, [0.5, 1.0, 1.5]);
+    /// ```
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn multiply_scalar_array(x: Dynamic, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        times(x, Dynamic::from_array(y))
+    }
+
+    /// Divides two numeric arrays element-by-element with the same broadcasting rules as
+    /// `rdivide`. The result is always FLOAT.
+    /// ```typescript
+    /// let x = [[2, 4], [6, 8]] / [2, 4];
+    /// assert_eq(x, [[1.0, 1.0], [3.0, 2.0]]);
+    /// ```
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn divide_arrays(x: Array, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        rdivide(Dynamic::from_array(x), Dynamic::from_array(y))
+    }
+
+    /// Divides every element of a numeric array by a scalar. The result is always FLOAT.
+    /// ```typescript
+    /// let x = [1, 2, 3] / 2;
+    /// assert_eq(x, [0.5, 1.0, 1.5]);
+    /// ```
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn divide_array_scalar(x: Array, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        rdivide(Dynamic::from_array(x), y)
+    }
+
+    /// Divides a scalar by every element of a numeric array. The result is always FLOAT.
+    /// ```typescript
+    /// let x = 1 / [1, 2, 4];
+    /// assert_eq(x, [1.0, 0.5, 0.25]);
+    /// ```
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn divide_scalar_array(x: Dynamic, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        rdivide(x, Dynamic::from_array(y))
+    }
+
+    /// Raises the elements of a numeric array to the powers in another array element-by-element,
+    /// with the same broadcasting rules as `power`.
+    /// ```typescript
+    /// let x = [2, 3, 4] ** [3, 2, 1];
+    /// assert_eq(x, [8, 9, 4]);
+    /// ```
+    #[rhai_fn(name = "**", return_raw)]
+    pub fn power_arrays(x: Array, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        power(Dynamic::from_array(x), Dynamic::from_array(y))
+    }
+
+    /// Raises every element of a numeric array to a scalar power.
+    /// ```typescript
+    /// let x = [[1, 2], [3, 4]] ** 2;
+    /// assert_eq(x, [[1, 4], [9, 16]]);
+    /// ```
+    #[rhai_fn(name = "**", return_raw)]
+    pub fn power_array_scalar(x: Array, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        power(Dynamic::from_array(x), y)
+    }
+
+    /// Raises a scalar to the power of every element of a numeric array.
+    /// ```typescript
+    /// let x = 2 ** [0, 1, 2];
+    /// assert_eq(x, [1, 2, 4]);
+    /// ```
+    #[rhai_fn(name = "**", return_raw)]
+    pub fn power_scalar_array(x: Dynamic, y: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        power(x, Dynamic::from_array(y))
+    }
+}
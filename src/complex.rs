@@ -0,0 +1,475 @@
+use rhai::plugin::*;
+
+/// A complex number with FLOAT real and imaginary parts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    /// The real part
+    pub re: rhai::FLOAT,
+    /// The imaginary part
+    pub im: rhai::FLOAT,
+}
+
+impl Complex {
+    /// Creates a complex number from its real and imaginary parts.
+    pub fn new(re: rhai::FLOAT, im: rhai::FLOAT) -> Self {
+        Complex { re, im }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebralib::Complex<rhai::FLOAT>> for Complex {
+    fn from(z: nalgebralib::Complex<rhai::FLOAT>) -> Self {
+        Complex::new(z.re, z.im)
+    }
+}
+
+impl std::fmt::Display for Complex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.im.is_sign_negative() {
+            write!(f, "{:?}-{:?}i", self.re, -self.im)
+        } else {
+            write!(f, "{:?}+{:?}i", self.re, self.im)
+        }
+    }
+}
+
+#[export_module]
+pub mod complex_functions {
+    use rhai::{Dynamic, EvalAltResult, Position, FLOAT, INT};
+
+    /// A complex number with real part `re` and imaginary part `im`. Create one with `complex` or
+    /// `polar`.
+    pub type Complex = super::Complex;
+
+    /// Converts an INT, FLOAT, or Complex value into a Complex number.
+    fn to_complex(x: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        if let Some(z) = x.clone().try_cast::<Complex>() {
+            Ok(z)
+        } else {
+            crate::if_int_convert_to_float_and_do(x, |re| Ok(Complex::new(re, 0.0))).map_err(|_| {
+                EvalAltResult::ErrorArithmetic(
+                    "The input must be INT, FLOAT, or Complex".to_string(),
+                    Position::NONE,
+                )
+                .into()
+            })
+        }
+    }
+
+    /// Creates a complex number from its real and imaginary parts.
+    /// ```typescript
+    /// let z = complex(1, -2.5);
+    /// assert_eq(z.re, 1.0);
+    /// assert_eq(z.im, -2.5);
+    /// ```
+    #[rhai_fn(name = "complex", return_raw)]
+    pub fn complex(re: Dynamic, im: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        crate::if_int_convert_to_float_and_do(re, |re| {
+            crate::if_int_convert_to_float_and_do(im.clone(), |im| Ok(Complex::new(re, im)))
+        })
+    }
+
+    /// Creates a complex number from its magnitude `r` and angle `theta` (in radians).
+    /// ```typescript
+    /// let z = polar(2, pi/2);
+    /// assert_approx_eq(z.re, 0.0);
+    /// assert_approx_eq(z.im, 2.0);
+    /// ```
+    #[rhai_fn(name = "polar", return_raw)]
+    pub fn polar(r: Dynamic, theta: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        crate::if_int_convert_to_float_and_do(r, |r| {
+            crate::if_int_convert_to_float_and_do(theta.clone(), |theta| {
+                Ok(Complex::new(r * theta.cos(), r * theta.sin()))
+            })
+        })
+    }
+
+    /// Returns the real part of a complex number.
+    /// ```typescript
+    /// let z = complex(3, 4);
+    /// assert_eq(z.re, 3.0);
+    /// ```
+    #[rhai_fn(get = "re", pure)]
+    pub fn get_re(z: &mut Complex) -> FLOAT {
+        z.re
+    }
+
+    /// Sets the real part of a complex number.
+    /// ```typescript
+    /// let z = complex(3, 4);
+    /// z.re = 1.0;
+    /// assert(z == complex(1, 4));
+    /// ```
+    #[rhai_fn(set = "re")]
+    pub fn set_re(z: &mut Complex, re: FLOAT) {
+        z.re = re;
+    }
+
+    /// Returns the imaginary part of a complex number.
+    /// ```typescript
+    /// let z = complex(3, 4);
+    /// assert_eq(z.im, 4.0);
+    /// ```
+    #[rhai_fn(get = "im", pure)]
+    pub fn get_im(z: &mut Complex) -> FLOAT {
+        z.im
+    }
+
+    /// Sets the imaginary part of a complex number.
+    /// ```typescript
+    /// let z = complex(3, 4);
+    /// z.im = -1.0;
+    /// assert(z == complex(3, -1));
+    /// ```
+    #[rhai_fn(set = "im")]
+    pub fn set_im(z: &mut Complex, im: FLOAT) {
+        z.im = im;
+    }
+
+    /// Returns the real part of a complex number.
+    /// ```typescript
+    /// assert_eq(real(complex(3, 4)), 3.0);
+    /// ```
+    #[rhai_fn(name = "real", pure)]
+    pub fn real(z: &mut Complex) -> FLOAT {
+        z.re
+    }
+
+    /// Returns the imaginary part of a complex number.
+    /// ```typescript
+    /// assert_eq(imag(complex(3, 4)), 4.0);
+    /// ```
+    #[rhai_fn(name = "imag", pure)]
+    pub fn imag(z: &mut Complex) -> FLOAT {
+        z.im
+    }
+
+    /// Returns the magnitude of a complex number.
+    /// ```typescript
+    /// assert_eq(abs(complex(3, -4)), 5.0);
+    /// ```
+    #[rhai_fn(name = "abs", pure)]
+    pub fn abs(z: &mut Complex) -> FLOAT {
+        z.re.hypot(z.im)
+    }
+
+    /// Returns the angle of a complex number in radians, between `-pi` and `pi`.
+    /// ```typescript
+    /// assert_approx_eq(arg(complex(0, 1)), pi/2);
+    /// ```
+    /// ```typescript
+    /// assert_approx_eq(arg(complex(-1, 0)), pi);
+    /// ```
+    #[rhai_fn(name = "arg", name = "angle", pure)]
+    pub fn arg(z: &mut Complex) -> FLOAT {
+        z.im.atan2(z.re)
+    }
+
+    /// Returns the complex conjugate of a complex number.
+    /// ```typescript
+    /// assert(conj(complex(1, 2)) == complex(1, -2));
+    /// ```
+    #[rhai_fn(name = "conj", pure)]
+    pub fn conj(z: &mut Complex) -> Complex {
+        Complex::new(z.re, -z.im)
+    }
+
+    /// Returns the exponential of a complex number.
+    /// ```typescript
+    /// let z = exp(complex(0, pi));
+    /// assert_approx_eq(z.re, -1.0);
+    /// assert_approx_eq(z.im, 0.0);
+    /// ```
+    #[rhai_fn(name = "exp", pure)]
+    pub fn exp(z: &mut Complex) -> Complex {
+        let r = z.re.exp();
+        Complex::new(r * z.im.cos(), r * z.im.sin())
+    }
+
+    /// Returns the principal natural logarithm of a complex number.
+    /// ```typescript
+    /// let z = ln(complex(-1, 0));
+    /// assert_approx_eq(z.re, 0.0);
+    /// assert_approx_eq(z.im, pi);
+    /// ```
+    /// ```typescript
+    /// let z = complex(1, 2);
+    /// let w = exp(ln(z));
+    /// assert_approx_eq(w.re, 1.0);
+    /// assert_approx_eq(w.im, 2.0);
+    /// ```
+    #[rhai_fn(name = "ln", pure)]
+    pub fn ln(z: &mut Complex) -> Complex {
+        Complex::new(abs(z).ln(), arg(z))
+    }
+
+    /// Returns the principal base-10 logarithm of a complex number, consistent with `log` for
+    /// FLOAT values.
+    /// ```typescript
+    /// let z = log(complex(100, 0));
+    /// assert_approx_eq(z.re, log(100.0));
+    /// assert_approx_eq(z.im, 0.0);
+    /// ```
+    /// ```typescript
+    /// let z = log(complex(-10, 0));
+    /// assert_approx_eq(z.re, 1.0);
+    /// assert_approx_eq(z.im, pi / ln(10.0));
+    /// ```
+    #[rhai_fn(name = "log", pure)]
+    pub fn log(z: &mut Complex) -> Complex {
+        let w = ln(z);
+        let ln_10 = FLOAT::ln(10.0);
+        Complex::new(w.re / ln_10, w.im / ln_10)
+    }
+
+    /// Returns the principal square root of a complex number.
+    /// ```typescript
+    /// let z = sqrt(complex(-4, 0));
+    /// assert(z == complex(0, 2));
+    /// ```
+    /// ```typescript
+    /// let z = sqrt(complex(3, 4));
+    /// assert_approx_eq(z.re, 2.0);
+    /// assert_approx_eq(z.im, 1.0);
+    /// ```
+    #[rhai_fn(name = "sqrt", pure)]
+    pub fn sqrt(z: &mut Complex) -> Complex {
+        let r = abs(z);
+        let re = ((r + z.re) / 2.0).sqrt();
+        let im = ((r - z.re) / 2.0).sqrt();
+        Complex::new(re, if z.im.is_sign_negative() { -im } else { im })
+    }
+
+    /// Returns the square root of a FLOAT. Negative inputs give a Complex result rather than NaN.
+    /// ```typescript
+    /// assert_eq(sqrt(9.0), 3.0);
+    /// ```
+    /// ```typescript
+    /// assert(sqrt(-9.0) == complex(0, 3));
+    /// ```
+    #[rhai_fn(name = "sqrt")]
+    pub fn sqrt_float(x: FLOAT) -> Dynamic {
+        if x < 0.0 {
+            Dynamic::from(Complex::new(0.0, (-x).sqrt()))
+        } else {
+            Dynamic::from_float(x.sqrt())
+        }
+    }
+
+    /// Returns the square root of an INT as a FLOAT. Negative inputs give a Complex result.
+    /// ```typescript
+    /// assert_eq(sqrt(16), 4.0);
+    /// ```
+    /// ```typescript
+    /// assert(sqrt(-16) == complex(0, 4));
+    /// ```
+    #[rhai_fn(name = "sqrt")]
+    pub fn sqrt_int(x: INT) -> Dynamic {
+        sqrt_float(x as FLOAT)
+    }
+
+    /// Adds a complex number and an INT, FLOAT, or Complex value.
+    /// ```typescript
+    /// assert(complex(1, 2) + complex(3, -1) == complex(4, 1));
+    /// ```
+    /// ```typescript
+    /// assert(complex(1, 2) + 2 == complex(3, 2));
+    /// ```
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add(z: Complex, w: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        let w = to_complex(w)?;
+        Ok(Complex::new(z.re + w.re, z.im + w.im))
+    }
+
+    /// Adds an INT or FLOAT value and a complex number.
+    /// ```typescript
+    /// assert(0.5 + complex(1, 2) == complex(1.5, 2));
+    /// ```
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add_reversed(x: Dynamic, z: Complex) -> Result<Complex, Box<EvalAltResult>> {
+        add(z, x)
+    }
+
+    /// Subtracts an INT, FLOAT, or Complex value from a complex number.
+    /// ```typescript
+    /// assert(complex(1, 2) - complex(3, -1) == complex(-2, 3));
+    /// ```
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract(z: Complex, w: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        let w = to_complex(w)?;
+        Ok(Complex::new(z.re - w.re, z.im - w.im))
+    }
+
+    /// Subtracts a complex number from an INT or FLOAT value.
+    /// ```typescript
+    /// assert(1 - complex(1, 2) == complex(0, -2));
+    /// ```
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract_reversed(x: Dynamic, z: Complex) -> Result<Complex, Box<EvalAltResult>> {
+        subtract(to_complex(x)?, Dynamic::from(z))
+    }
+
+    /// Negates a complex number.
+    /// ```typescript
+    /// assert(-complex(1, -2) == complex(-1, 2));
+    /// ```
+    #[rhai_fn(name = "-")]
+    pub fn negate(z: Complex) -> Complex {
+        Complex::new(-z.re, -z.im)
+    }
+
+    /// Multiplies a complex number by an INT, FLOAT, or Complex value.
+    /// ```typescript
+    /// assert(complex(1, 2) * complex(3, -1) == complex(5, 5));
+    /// ```
+    /// ```typescript
+    /// assert(complex(1, 2) * 2 == complex(2, 4));
+    /// ```
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn multiply(z: Complex, w: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        let w = to_complex(w)?;
+        Ok(Complex::new(
+            z.re * w.re - z.im * w.im,
+            z.re * w.im + z.im * w.re,
+        ))
+    }
+
+    /// Multiplies an INT or FLOAT value by a complex number.
+    /// ```typescript
+    /// assert(2 * complex(1, 2) == complex(2, 4));
+    /// ```
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn multiply_reversed(x: Dynamic, z: Complex) -> Result<Complex, Box<EvalAltResult>> {
+        multiply(z, x)
+    }
+
+    /// Divides a complex number by an INT, FLOAT, or Complex value.
+    /// ```typescript
+    /// assert(complex(5, 5) / complex(3, -1) == complex(1, 2));
+    /// ```
+    /// ```typescript
+    /// assert(complex(2, 4) / 2 == complex(1, 2));
+    /// ```
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn divide(z: Complex, w: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        let w = to_complex(w)?;
+        let denominator = w.re * w.re + w.im * w.im;
+        Ok(Complex::new(
+            (z.re * w.re + z.im * w.im) / denominator,
+            (z.im * w.re - z.re * w.im) / denominator,
+        ))
+    }
+
+    /// Divides an INT or FLOAT value by a complex number.
+    /// ```typescript
+    /// assert(1 / complex(0, 1) == complex(0, -1));
+    /// ```
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn divide_reversed(x: Dynamic, z: Complex) -> Result<Complex, Box<EvalAltResult>> {
+        divide(to_complex(x)?, Dynamic::from(z))
+    }
+
+    /// Raises a complex number to an INT, FLOAT, or Complex power. Integer powers are computed
+    /// by repeated multiplication, and all others with the principal logarithm.
+    /// ```typescript
+    /// assert(complex(0, 1) ** 2 == complex(-1, 0));
+    /// ```
+    /// ```typescript
+    /// let z = complex(-4, 0) ** 0.5;
+    /// assert_approx_eq(z.re, 0.0);
+    /// assert_approx_eq(z.im, 2.0);
+    /// ```
+    #[rhai_fn(name = "**", return_raw)]
+    pub fn power(z: Complex, w: Dynamic) -> Result<Complex, Box<EvalAltResult>> {
+        if w.is_int() {
+            let k = w.as_int().unwrap();
+            let mut base = if k < 0 {
+                divide(Complex::new(1.0, 0.0), Dynamic::from(z))?
+            } else {
+                z
+            };
+            let mut exponent = k.unsigned_abs();
+            let mut result = Complex::new(1.0, 0.0);
+            while exponent > 0 {
+                if exponent % 2 == 1 {
+                    result = multiply(result, Dynamic::from(base))?;
+                }
+                exponent /= 2;
+                if exponent > 0 {
+                    base = multiply(base, Dynamic::from(base))?;
+                }
+            }
+            Ok(result)
+        } else {
+            let w = to_complex(w)?;
+            if z.re == 0.0 && z.im == 0.0 {
+                Ok(if w.re == 0.0 && w.im == 0.0 {
+                    Complex::new(1.0, 0.0)
+                } else {
+                    Complex::new(0.0, 0.0)
+                })
+            } else {
+                Ok(exp(&mut multiply(w, Dynamic::from(ln(&mut z.clone())))?))
+            }
+        }
+    }
+
+    /// Raises an INT or FLOAT value to a complex power.
+    /// ```typescript
+    /// let z = e ** complex(0, pi);
+    /// assert_approx_eq(z.re, -1.0);
+    /// assert_approx_eq(z.im, 0.0);
+    /// ```
+    #[rhai_fn(name = "**", return_raw)]
+    pub fn power_reversed(x: Dynamic, z: Complex) -> Result<Complex, Box<EvalAltResult>> {
+        power(to_complex(x)?, Dynamic::from(z))
+    }
+
+    /// Tests whether a complex number is equal to an INT, FLOAT, or Complex value.
+    /// ```typescript
+    /// assert(complex(2, 0) == 2);
+    /// ```
+    #[rhai_fn(name = "==")]
+    pub fn equals(z: Complex, w: Dynamic) -> bool {
+        to_complex(w).map(|w| z == w).unwrap_or(false)
+    }
+
+    /// Tests whether a complex number is not equal to an INT, FLOAT, or Complex value.
+    /// ```typescript
+    /// assert(complex(2, 1) != 2);
+    /// ```
+    #[rhai_fn(name = "!=")]
+    pub fn not_equals(z: Complex, w: Dynamic) -> bool {
+        !equals(z, w)
+    }
+
+    /// Tests whether an INT or FLOAT value is equal to a complex number.
+    /// ```typescript
+    /// assert(2 == complex(2, 0));
+    /// ```
+    /// ```typescript
+    /// assert(!(2.5 == complex(2.5, 1)));
+    /// ```
+    #[rhai_fn(name = "==")]
+    pub fn equals_reversed(x: Dynamic, z: Complex) -> bool {
+        equals(z, x)
+    }
+
+    /// Tests whether an INT or FLOAT value is not equal to a complex number.
+    /// ```typescript
+    /// assert(2 != complex(2, 1));
+    /// ```
+    #[rhai_fn(name = "!=")]
+    pub fn not_equals_reversed(x: Dynamic, z: Complex) -> bool {
+        !equals(z, x)
+    }
+
+    /// Returns a string representation of a complex number.
+    /// ```typescript
+    /// assert_eq(to_string(complex(1, -2)), "1.0-2.0i");
+    /// ```
+    #[rhai_fn(name = "to_string", name = "to_debug", pure)]
+    pub fn complex_to_string(z: &mut Complex) -> String {
+        z.to_string()
+    }
+}
@@ -2,21 +2,34 @@ use rhai::plugin::*;
 
 #[export_module]
 pub mod cum_functions {
-    use crate::{if_list_convert_to_vec_float_and_do, if_list_do};
+    use crate::{
+        if_list_convert_to_vec_float_and_do, if_list_do, if_list_do_else_map_along_dim,
+        if_numeric_array_do_along_dim,
+    };
     use rhai::{Array, Dynamic, EvalAltResult, Position, FLOAT, INT};
 
-    fn accumulate<G>(arr: &mut Array, mut f: G) -> Result<Array, Box<EvalAltResult>>
+    fn accumulate<G>(
+        arr: &mut Array,
+        dim: Option<INT>,
+        mut f: G,
+    ) -> Result<Array, Box<EvalAltResult>>
     where
         G: FnMut(&mut Array) -> Dynamic,
     {
-        if_list_do(arr, |arr| {
-            let mut new_arr: Array = vec![];
-            let n = arr.len() as INT;
-            for i in 0..n {
-                new_arr.push(f(&mut arr.get(0_usize..=(i as usize)).unwrap().to_vec()))
-            }
-            Ok(new_arr)
-        })
+        let accumulate_list = |arr: &mut Array| {
+            if_list_do(arr, |arr| {
+                let mut new_arr: Array = vec![];
+                let n = arr.len() as INT;
+                for i in 0..n {
+                    new_arr.push(f(&mut arr.get(0_usize..=(i as usize)).unwrap().to_vec()))
+                }
+                Ok(new_arr)
+            })
+        };
+        match dim {
+            Some(_) => if_numeric_array_do_along_dim(arr, dim, accumulate_list),
+            None => if_list_do_else_map_along_dim(arr, accumulate_list),
+        }
     }
 
     /// Returns an array representing the cumulative product of a 1-D array.
@@ -27,7 +40,18 @@ pub mod cum_functions {
     /// ```
     #[rhai_fn(name = "cumprod", return_raw, pure)]
     pub fn cumprod(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
-        accumulate(arr, |x| crate::stats::prod(x).unwrap())
+        accumulate(arr, None, |x| crate::stats::prod(x).unwrap())
+    }
+
+    /// Returns the cumulative product along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [3, 4]];
+    /// assert_eq(cumprod(data, 1), [[1, 2], [3, 8]]);
+    /// ```
+    #[rhai_fn(name = "cumprod", return_raw, pure)]
+    pub fn cumprod_along_dim(arr: &mut Array, dim: INT) -> Result<Array, Box<EvalAltResult>> {
+        accumulate(arr, Some(dim), |x| crate::stats::prod(x).unwrap())
     }
 
     /// Returns an array representing the cumulative maximum of a 1-D array.
@@ -38,7 +62,18 @@ pub mod cum_functions {
     /// ```
     #[rhai_fn(name = "cummax", return_raw, pure)]
     pub fn cummax(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
-        accumulate(arr, |x| crate::stats::array_max(x).unwrap())
+        accumulate(arr, None, |x| crate::stats::array_max(x).unwrap())
+    }
+
+    /// Returns the cumulative maximum along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 5, 3],
+    ///             [4, 2, 6]];
+    /// assert_eq(cummax(data, 2), [[1, 5, 5], [4, 4, 6]]);
+    /// ```
+    #[rhai_fn(name = "cummax", return_raw, pure)]
+    pub fn cummax_along_dim(arr: &mut Array, dim: INT) -> Result<Array, Box<EvalAltResult>> {
+        accumulate(arr, Some(dim), |x| crate::stats::array_max(x).unwrap())
     }
 
     /// Returns an array representing the cumulative minimum of a 1-D array.
@@ -49,18 +84,47 @@ pub mod cum_functions {
     /// ```
     #[rhai_fn(name = "cummin", return_raw, pure)]
     pub fn cummin(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
-        accumulate(arr, |x| crate::stats::array_min(x).unwrap())
+        accumulate(arr, None, |x| crate::stats::array_min(x).unwrap())
     }
 
-    /// Returns an array representing the cumulative product of a 1-D array.
+    /// Returns the cumulative minimum along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 5, 3],
+    ///             [4, 2, 6]];
+    /// assert_eq(cummin(data, 1), [[1, 5, 3], [1, 2, 3]]);
+    /// ```
+    #[rhai_fn(name = "cummin", return_raw, pure)]
+    pub fn cummin_along_dim(arr: &mut Array, dim: INT) -> Result<Array, Box<EvalAltResult>> {
+        accumulate(arr, Some(dim), |x| crate::stats::array_min(x).unwrap())
+    }
+
+    /// Returns an array representing the cumulative sum of a 1-D array. A matrix or N-D array is
+    /// accumulated along its first non-singleton dimension.
     /// ```typescript
     /// let arr = [1.1, 2.5, 3.4];
     /// let c = cumsum(arr);
     /// assert_eq(c, [1.1, 3.6, 7.0]);
     /// ```
+    /// ```typescript
+    /// let c = cumsum([[1, 2],
+    ///                 [3, 4]]);
+    /// assert_eq(c, [[1, 2], [4, 6]]);
+    /// ```
     #[rhai_fn(name = "cumsum", return_raw, pure)]
     pub fn cumsum(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
-        accumulate(arr, |x| crate::stats::sum(x).unwrap())
+        accumulate(arr, None, |x| crate::stats::sum(x).unwrap())
+    }
+
+    /// Returns the cumulative sum along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 2, 3],
+    ///             [4, 5, 6]];
+    /// assert_eq(cumsum(data, 1), [[1, 2, 3], [5, 7, 9]]);
+    /// assert_eq(cumsum(data, 2), [[1, 3, 6], [4, 9, 15]]);
+    /// ```
+    #[rhai_fn(name = "cumsum", return_raw, pure)]
+    pub fn cumsum_along_dim(arr: &mut Array, dim: INT) -> Result<Array, Box<EvalAltResult>> {
+        accumulate(arr, Some(dim), |x| crate::stats::sum(x).unwrap())
     }
 
     /// Returns the cumulative approximate integral of the curve defined by Y and x using the trapezoidal method.
@@ -0,0 +1,876 @@
+use rhai::plugin::*;
+
+/// A probability distribution with fixed parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    /// Normal distribution with mean `mu` and standard deviation `sigma`
+    Normal {
+        /// Mean
+        mu: rhai::FLOAT,
+        /// Standard deviation
+        sigma: rhai::FLOAT,
+    },
+    /// Lognormal distribution, whose logarithm has mean `mu` and standard deviation `sigma`
+    Lognormal {
+        /// Mean of the logarithm
+        mu: rhai::FLOAT,
+        /// Standard deviation of the logarithm
+        sigma: rhai::FLOAT,
+    },
+    /// Continuous uniform distribution on the interval from `a` to `b`
+    Uniform {
+        /// Lower bound
+        a: rhai::FLOAT,
+        /// Upper bound
+        b: rhai::FLOAT,
+    },
+    /// Exponential distribution with mean `mu`
+    Exponential {
+        /// Mean
+        mu: rhai::FLOAT,
+    },
+    /// Gamma distribution with shape `a` and scale `b`
+    Gamma {
+        /// Shape
+        a: rhai::FLOAT,
+        /// Scale
+        b: rhai::FLOAT,
+    },
+    /// Beta distribution with shape parameters `a` and `b`
+    Beta {
+        /// First shape parameter
+        a: rhai::FLOAT,
+        /// Second shape parameter
+        b: rhai::FLOAT,
+    },
+    /// Weibull distribution with scale `a` and shape `b`
+    Weibull {
+        /// Scale
+        a: rhai::FLOAT,
+        /// Shape
+        b: rhai::FLOAT,
+    },
+    /// Student's t distribution with `nu` degrees of freedom
+    StudentT {
+        /// Degrees of freedom
+        nu: rhai::FLOAT,
+    },
+    /// Chi-square distribution with `nu` degrees of freedom
+    ChiSquare {
+        /// Degrees of freedom
+        nu: rhai::FLOAT,
+    },
+    /// F distribution with `nu1` numerator and `nu2` denominator degrees of freedom
+    F {
+        /// Numerator degrees of freedom
+        nu1: rhai::FLOAT,
+        /// Denominator degrees of freedom
+        nu2: rhai::FLOAT,
+    },
+    /// Binomial distribution of the number of successes in `n` trials with success probability `p`
+    Binomial {
+        /// Number of trials
+        n: rhai::INT,
+        /// Probability of success in each trial
+        p: rhai::FLOAT,
+    },
+    /// Poisson distribution with mean `lambda`
+    Poisson {
+        /// Mean
+        lambda: rhai::FLOAT,
+    },
+    /// Geometric distribution of the number of failures before the first success, where each
+    /// trial succeeds with probability `p`
+    Geometric {
+        /// Probability of success in each trial
+        p: rhai::FLOAT,
+    },
+}
+
+impl Distribution {
+    /// Creates a distribution from its name and a map of its parameters. Parameters with a
+    /// conventional default (such as the standard normal distribution) may be omitted.
+    pub fn new(name: &str, params: &rhai::Map) -> Result<Self, Box<EvalAltResult>> {
+        let keys: &[&str] = match name {
+            "normal" | "lognormal" => &["mu", "sigma"],
+            "uniform" | "gamma" | "beta" | "weibull" => &["a", "b"],
+            "exponential" => &["mu"],
+            "t" | "chi2" => &["nu"],
+            "f" => &["nu1", "nu2"],
+            "binomial" => &["n", "p"],
+            "poisson" => &["lambda"],
+            "geometric" => &["p"],
+            _ => return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown distribution '{name}', expected 'normal', 'lognormal', 'uniform', 'exponential', 'gamma', 'beta', 'weibull', 't', 'chi2', 'f', 'binomial', 'poisson' or 'geometric'"),
+                Position::NONE,
+            )
+            .into()),
+        };
+        if let Some(key) = params.keys().find(|k| !keys.contains(&k.as_str())) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Unknown parameter '{key}' for the {name} distribution, expected {}",
+                    keys.join(", ")
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        let get = |key: &str, default: Option<rhai::FLOAT>| match params.get(key) {
+            Some(value) => crate::if_int_convert_to_float_and_do(value.clone(), Ok),
+            None => default.ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    format!("The {name} distribution requires the parameter '{key}'"),
+                    Position::NONE,
+                )
+                .into()
+            }),
+        };
+
+        let dist = match name {
+            "normal" => Distribution::Normal {
+                mu: get("mu", Some(0.0))?,
+                sigma: get("sigma", Some(1.0))?,
+            },
+            "lognormal" => Distribution::Lognormal {
+                mu: get("mu", Some(0.0))?,
+                sigma: get("sigma", Some(1.0))?,
+            },
+            "uniform" => Distribution::Uniform {
+                a: get("a", Some(0.0))?,
+                b: get("b", Some(1.0))?,
+            },
+            "exponential" => Distribution::Exponential {
+                mu: get("mu", Some(1.0))?,
+            },
+            "gamma" => Distribution::Gamma {
+                a: get("a", None)?,
+                b: get("b", Some(1.0))?,
+            },
+            "beta" => Distribution::Beta {
+                a: get("a", None)?,
+                b: get("b", None)?,
+            },
+            "weibull" => Distribution::Weibull {
+                a: get("a", None)?,
+                b: get("b", None)?,
+            },
+            "t" => Distribution::StudentT {
+                nu: get("nu", None)?,
+            },
+            "chi2" => Distribution::ChiSquare {
+                nu: get("nu", None)?,
+            },
+            "f" => Distribution::F {
+                nu1: get("nu1", None)?,
+                nu2: get("nu2", None)?,
+            },
+            "binomial" => {
+                let n = get("n", None)?;
+                if n.fract() != 0.0 || n < 0.0 {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The number of trials must be a non-negative integer".to_string(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                Distribution::Binomial {
+                    n: n as rhai::INT,
+                    p: get("p", None)?,
+                }
+            }
+            "poisson" => Distribution::Poisson {
+                lambda: get("lambda", None)?,
+            },
+            _ => Distribution::Geometric { p: get("p", None)? },
+        };
+
+        let valid = match dist {
+            Distribution::Normal { mu, sigma } | Distribution::Lognormal { mu, sigma } => {
+                mu.is_finite() && sigma > 0.0 && sigma.is_finite()
+            }
+            Distribution::Uniform { a, b } => a.is_finite() && b.is_finite() && a < b,
+            Distribution::Exponential { mu } => mu > 0.0 && mu.is_finite(),
+            Distribution::Gamma { a, b }
+            | Distribution::Beta { a, b }
+            | Distribution::Weibull { a, b } => {
+                a > 0.0 && b > 0.0 && a.is_finite() && b.is_finite()
+            }
+            Distribution::StudentT { nu } | Distribution::ChiSquare { nu } => {
+                nu > 0.0 && nu.is_finite()
+            }
+            Distribution::F { nu1, nu2 } => {
+                nu1 > 0.0 && nu2 > 0.0 && nu1.is_finite() && nu2.is_finite()
+            }
+            Distribution::Binomial { p, .. } => (0.0..=1.0).contains(&p),
+            Distribution::Poisson { lambda } => lambda >= 0.0 && lambda.is_finite(),
+            Distribution::Geometric { p } => p > 0.0 && p <= 1.0,
+        };
+        if valid {
+            Ok(dist)
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid parameters for the {name} distribution: {dist}"),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// The name of the distribution, as accepted by `Distribution::new`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Distribution::Normal { .. } => "normal",
+            Distribution::Lognormal { .. } => "lognormal",
+            Distribution::Uniform { .. } => "uniform",
+            Distribution::Exponential { .. } => "exponential",
+            Distribution::Gamma { .. } => "gamma",
+            Distribution::Beta { .. } => "beta",
+            Distribution::Weibull { .. } => "weibull",
+            Distribution::StudentT { .. } => "t",
+            Distribution::ChiSquare { .. } => "chi2",
+            Distribution::F { .. } => "f",
+            Distribution::Binomial { .. } => "binomial",
+            Distribution::Poisson { .. } => "poisson",
+            Distribution::Geometric { .. } => "geometric",
+        }
+    }
+
+    /// The parameters of the distribution, as pairs of names and values.
+    pub fn params(&self) -> Vec<(&'static str, rhai::FLOAT)> {
+        match *self {
+            Distribution::Normal { mu, sigma } | Distribution::Lognormal { mu, sigma } => {
+                vec![("mu", mu), ("sigma", sigma)]
+            }
+            Distribution::Uniform { a, b }
+            | Distribution::Gamma { a, b }
+            | Distribution::Beta { a, b }
+            | Distribution::Weibull { a, b } => vec![("a", a), ("b", b)],
+            Distribution::Exponential { mu } => vec![("mu", mu)],
+            Distribution::StudentT { nu } | Distribution::ChiSquare { nu } => vec![("nu", nu)],
+            Distribution::F { nu1, nu2 } => vec![("nu1", nu1), ("nu2", nu2)],
+            Distribution::Binomial { n, p } => vec![("n", n as rhai::FLOAT), ("p", p)],
+            Distribution::Poisson { lambda } => vec![("lambda", lambda)],
+            Distribution::Geometric { p } => vec![("p", p)],
+        }
+    }
+
+    /// Whether the distribution is over the non-negative integers.
+    pub fn is_discrete(&self) -> bool {
+        matches!(
+            self,
+            Distribution::Binomial { .. }
+                | Distribution::Poisson { .. }
+                | Distribution::Geometric { .. }
+        )
+    }
+
+    /// The probability density function, or the probability mass function for a discrete
+    /// distribution.
+    pub fn pdf(&self, x: rhai::FLOAT) -> rhai::FLOAT {
+        use crate::ln_gamma;
+        use std::f64::consts::PI;
+        if x.is_nan() {
+            return rhai::FLOAT::NAN;
+        }
+        let ln_beta = |a: rhai::FLOAT, b: rhai::FLOAT| ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b);
+        if self.is_discrete() && (x.fract() != 0.0 || x < 0.0) {
+            return 0.0;
+        }
+        match *self {
+            Distribution::Normal { mu, sigma } => {
+                let z = (x - mu) / sigma;
+                (-z * z / 2.0).exp() / (sigma * (2.0 * PI).sqrt())
+            }
+            Distribution::Lognormal { mu, sigma } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    let z = (x.ln() - mu) / sigma;
+                    (-z * z / 2.0).exp() / (x * sigma * (2.0 * PI).sqrt())
+                }
+            }
+            Distribution::Uniform { a, b } => {
+                if (a..=b).contains(&x) {
+                    1.0 / (b - a)
+                } else {
+                    0.0
+                }
+            }
+            Distribution::Exponential { mu } => {
+                if x < 0.0 {
+                    0.0
+                } else {
+                    (-x / mu).exp() / mu
+                }
+            }
+            Distribution::Gamma { a, b } => {
+                if x < 0.0 {
+                    0.0
+                } else if x == 0.0 {
+                    x.powf(a - 1.0) / b.powf(a) / ln_gamma(a).exp()
+                } else {
+                    ((a - 1.0) * x.ln() - x / b - ln_gamma(a) - a * b.ln()).exp()
+                }
+            }
+            Distribution::Beta { a, b } => {
+                if (0.0..=1.0).contains(&x) {
+                    x.powf(a - 1.0) * (1.0 - x).powf(b - 1.0) / ln_beta(a, b).exp()
+                } else {
+                    0.0
+                }
+            }
+            Distribution::Weibull { a, b } => {
+                if x < 0.0 {
+                    0.0
+                } else {
+                    b / a * (x / a).powf(b - 1.0) * (-(x / a).powf(b)).exp()
+                }
+            }
+            Distribution::StudentT { nu } => {
+                (ln_gamma((nu + 1.0) / 2.0) - ln_gamma(nu / 2.0)).exp() / (nu * PI).sqrt()
+                    * (1.0 + x * x / nu).powf(-(nu + 1.0) / 2.0)
+            }
+            Distribution::ChiSquare { nu } => Distribution::Gamma {
+                a: nu / 2.0,
+                b: 2.0,
+            }
+            .pdf(x),
+            Distribution::F { nu1, nu2 } => {
+                if x < 0.0 {
+                    0.0
+                } else if x == 0.0 {
+                    // The density at zero behaves like x^(nu1 / 2 - 1)
+                    match nu1.partial_cmp(&2.0) {
+                        Some(std::cmp::Ordering::Less) => rhai::FLOAT::INFINITY,
+                        Some(std::cmp::Ordering::Equal) => 1.0,
+                        _ => 0.0,
+                    }
+                } else {
+                    ((nu1 * (nu1 * x).ln() + nu2 * nu2.ln() - (nu1 + nu2) * (nu1 * x + nu2).ln())
+                        / 2.0
+                        - x.ln()
+                        - ln_beta(nu1 / 2.0, nu2 / 2.0))
+                    .exp()
+                }
+            }
+            Distribution::Binomial { n, p } => {
+                let n = n as rhai::FLOAT;
+                if x > n {
+                    0.0
+                } else {
+                    (ln_gamma(n + 1.0) - ln_gamma(x + 1.0) - ln_gamma(n - x + 1.0)).exp()
+                        * p.powf(x)
+                        * (1.0 - p).powf(n - x)
+                }
+            }
+            Distribution::Poisson { lambda } => {
+                if lambda == 0.0 {
+                    (x == 0.0) as rhai::INT as rhai::FLOAT
+                } else {
+                    (x * lambda.ln() - lambda - ln_gamma(x + 1.0)).exp()
+                }
+            }
+            Distribution::Geometric { p } => p * (1.0 - p).powf(x),
+        }
+    }
+
+    /// The cumulative distribution function.
+    pub fn cdf(&self, x: rhai::FLOAT) -> rhai::FLOAT {
+        use crate::{normal_cdf, regularized_beta, regularized_gamma_p, regularized_gamma_q};
+        if x.is_nan() {
+            return rhai::FLOAT::NAN;
+        }
+        let x = if self.is_discrete() { x.floor() } else { x };
+        match *self {
+            Distribution::Normal { mu, sigma } => normal_cdf((x - mu) / sigma),
+            Distribution::Lognormal { mu, sigma } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    normal_cdf((x.ln() - mu) / sigma)
+                }
+            }
+            Distribution::Uniform { a, b } => ((x - a) / (b - a)).clamp(0.0, 1.0),
+            Distribution::Exponential { mu } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    -(-x / mu).exp_m1()
+                }
+            }
+            Distribution::Gamma { a, b } => regularized_gamma_p(a, x / b),
+            Distribution::Beta { a, b } => regularized_beta(a, b, x),
+            Distribution::Weibull { a, b } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    -(-(x / a).powf(b)).exp_m1()
+                }
+            }
+            Distribution::StudentT { nu } => crate::student_t_cdf(x, nu),
+            Distribution::ChiSquare { nu } => regularized_gamma_p(nu / 2.0, x / 2.0),
+            Distribution::F { nu1, nu2 } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    regularized_beta(nu1 / 2.0, nu2 / 2.0, nu1 * x / (nu1 * x + nu2))
+                }
+            }
+            Distribution::Binomial { n, p } => {
+                let n = n as rhai::FLOAT;
+                if x < 0.0 {
+                    0.0
+                } else if x >= n {
+                    1.0
+                } else {
+                    regularized_beta(n - x, x + 1.0, 1.0 - p)
+                }
+            }
+            Distribution::Poisson { lambda } => {
+                if x < 0.0 {
+                    0.0
+                } else {
+                    regularized_gamma_q(x + 1.0, lambda)
+                }
+            }
+            Distribution::Geometric { p } => {
+                if x < 0.0 {
+                    0.0
+                } else {
+                    -((x + 1.0) * (-p).ln_1p()).exp_m1()
+                }
+            }
+        }
+    }
+
+    /// The inverse of the cumulative distribution function. For a discrete distribution, this is
+    /// the smallest value whose cumulative probability is at least `p`.
+    pub fn inv(&self, p: rhai::FLOAT) -> rhai::FLOAT {
+        use crate::normal_inv;
+        if p.is_nan() || !(0.0..=1.0).contains(&p) {
+            return rhai::FLOAT::NAN;
+        }
+        let (low, high) = self.support();
+        if p == 0.0 {
+            return low;
+        }
+        if p == 1.0 {
+            return high;
+        }
+        match *self {
+            Distribution::Normal { mu, sigma } => mu + sigma * normal_inv(p),
+            Distribution::Lognormal { mu, sigma } => (mu + sigma * normal_inv(p)).exp(),
+            Distribution::Uniform { a, b } => a + p * (b - a),
+            Distribution::Exponential { mu } => -mu * (-p).ln_1p(),
+            Distribution::Weibull { a, b } => a * (-(-p).ln_1p()).powf(1.0 / b),
+            _ if self.is_discrete() => {
+                // Find an upper bound by doubling, then bisect over the integers
+                let (mut lower, mut upper) = (-1.0, 1.0);
+                while self.cdf(upper) < p && upper < high {
+                    lower = upper;
+                    upper = (upper * 2.0).min(high);
+                }
+                while upper - lower > 1.0 {
+                    // Past 2^53 not every integer is representable, so the midpoint can stall
+                    let middle = ((lower + upper) / 2.0).floor();
+                    if middle <= lower || middle >= upper {
+                        break;
+                    }
+                    if self.cdf(middle) >= p {
+                        upper = middle;
+                    } else {
+                        lower = middle;
+                    }
+                }
+                upper
+            }
+            _ => {
+                // Bracket the solution, expanding away from zero, then bisect
+                let (mut lower, mut upper) = (low.max(-1.0), high.min(1.0));
+                while self.cdf(lower) > p {
+                    upper = lower;
+                    lower *= 2.0;
+                }
+                while self.cdf(upper) < p {
+                    lower = upper;
+                    upper *= 2.0;
+                }
+                for _ in 0..200 {
+                    let middle = (lower + upper) / 2.0;
+                    if middle <= lower || middle >= upper {
+                        break;
+                    }
+                    if self.cdf(middle) < p {
+                        lower = middle;
+                    } else {
+                        upper = middle;
+                    }
+                }
+                (lower + upper) / 2.0
+            }
+        }
+    }
+
+    /// The smallest and largest values that the distribution can take.
+    pub fn support(&self) -> (rhai::FLOAT, rhai::FLOAT) {
+        match *self {
+            Distribution::Normal { .. } | Distribution::StudentT { .. } => {
+                (rhai::FLOAT::NEG_INFINITY, rhai::FLOAT::INFINITY)
+            }
+            Distribution::Uniform { a, b } => (a, b),
+            Distribution::Beta { .. } => (0.0, 1.0),
+            Distribution::Binomial { n, .. } => (0.0, n as rhai::FLOAT),
+            _ => (0.0, rhai::FLOAT::INFINITY),
+        }
+    }
+
+    /// The mean of the distribution, which is NaN if it is undefined.
+    pub fn mean(&self) -> rhai::FLOAT {
+        use crate::ln_gamma;
+        match *self {
+            Distribution::Normal { mu, .. } => mu,
+            Distribution::Lognormal { mu, sigma } => (mu + sigma * sigma / 2.0).exp(),
+            Distribution::Uniform { a, b } => (a + b) / 2.0,
+            Distribution::Exponential { mu } => mu,
+            Distribution::Gamma { a, b } => a * b,
+            Distribution::Beta { a, b } => a / (a + b),
+            Distribution::Weibull { a, b } => a * ln_gamma(1.0 + 1.0 / b).exp(),
+            Distribution::StudentT { nu } => {
+                if nu > 1.0 {
+                    0.0
+                } else {
+                    rhai::FLOAT::NAN
+                }
+            }
+            Distribution::ChiSquare { nu } => nu,
+            Distribution::F { nu2, .. } => {
+                if nu2 > 2.0 {
+                    nu2 / (nu2 - 2.0)
+                } else {
+                    rhai::FLOAT::NAN
+                }
+            }
+            Distribution::Binomial { n, p } => n as rhai::FLOAT * p,
+            Distribution::Poisson { lambda } => lambda,
+            Distribution::Geometric { p } => (1.0 - p) / p,
+        }
+    }
+
+    /// The variance of the distribution, which is NaN if it is undefined.
+    pub fn variance(&self) -> rhai::FLOAT {
+        use crate::ln_gamma;
+        match *self {
+            Distribution::Normal { sigma, .. } => sigma * sigma,
+            Distribution::Lognormal { mu, sigma } => {
+                (sigma * sigma).exp_m1() * (2.0 * mu + sigma * sigma).exp()
+            }
+            Distribution::Uniform { a, b } => (b - a) * (b - a) / 12.0,
+            Distribution::Exponential { mu } => mu * mu,
+            Distribution::Gamma { a, b } => a * b * b,
+            Distribution::Beta { a, b } => a * b / ((a + b) * (a + b) * (a + b + 1.0)),
+            Distribution::Weibull { a, b } => {
+                let m = ln_gamma(1.0 + 1.0 / b).exp();
+                a * a * (ln_gamma(1.0 + 2.0 / b).exp() - m * m)
+            }
+            Distribution::StudentT { nu } => {
+                if nu > 2.0 {
+                    nu / (nu - 2.0)
+                } else if nu > 1.0 {
+                    rhai::FLOAT::INFINITY
+                } else {
+                    rhai::FLOAT::NAN
+                }
+            }
+            Distribution::ChiSquare { nu } => 2.0 * nu,
+            Distribution::F { nu1, nu2 } => {
+                if nu2 > 4.0 {
+                    2.0 * nu2 * nu2 * (nu1 + nu2 - 2.0)
+                        / (nu1 * (nu2 - 2.0) * (nu2 - 2.0) * (nu2 - 4.0))
+                } else {
+                    rhai::FLOAT::NAN
+                }
+            }
+            Distribution::Binomial { n, p } => n as rhai::FLOAT * p * (1.0 - p),
+            Distribution::Poisson { lambda } => lambda,
+            Distribution::Geometric { p } => (1.0 - p) / (p * p),
+        }
+    }
+
+    /// Draws a random value from the distribution by inverse transform sampling.
+    #[cfg(feature = "rand")]
+    pub fn sample(&self) -> rhai::FLOAT {
+        loop {
+            let u: rhai::FLOAT = randlib::random();
+            // Avoid drawing the (possibly infinite) lower end of the support
+            if u > 0.0 {
+                return self.inv(u);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = self
+            .params()
+            .iter()
+            .map(|(name, value)| format!("{name}={value:?}"))
+            .collect::<Vec<String>>();
+        write!(f, "{}({})", self.name(), params.join(", "))
+    }
+}
+
+#[export_module]
+pub mod distribution_functions {
+    use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, Map, FLOAT};
+
+    /// A probability distribution with fixed parameters. Create one with `dist`.
+    pub type Distribution = super::Distribution;
+
+    /// Evaluates a function of a distribution at a number, or at each element of a numeric array.
+    fn map_elements(
+        d: Distribution,
+        x: Dynamic,
+        f: fn(&Distribution, FLOAT) -> FLOAT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        crate::if_broadcastable_do_elementwise(x, Dynamic::from_int(0), |el, _| {
+            crate::if_int_convert_to_float_and_do(el.clone(), |v| Ok(Dynamic::from_float(f(&d, v))))
+        })
+    }
+
+    /// Replaces each element of a (nested) array with a random value drawn from a distribution.
+    #[cfg(feature = "rand")]
+    fn resample(values: Array, d: Distribution) -> Array {
+        values
+            .into_iter()
+            .map(|el| match el.into_array() {
+                Ok(inner) => Dynamic::from_array(resample(inner, d)),
+                Err(_) => Dynamic::from_float(d.sample()),
+            })
+            .collect()
+    }
+
+    /// Creates a probability distribution from its name and a map of its parameters. The
+    /// distributions and their parameters are:
+    /// - "normal": mean `mu` (default 0) and standard deviation `sigma` (default 1)
+    /// - "lognormal": mean `mu` (default 0) and standard deviation `sigma` (default 1) of the
+    ///   logarithm
+    /// - "uniform": lower bound `a` (default 0) and upper bound `b` (default 1)
+    /// - "exponential": mean `mu` (default 1)
+    /// - "gamma": shape `a` and scale `b` (default 1)
+    /// - "beta": shape parameters `a` and `b`
+    /// - "weibull": scale `a` and shape `b`
+    /// - "t": degrees of freedom `nu`
+    /// - "chi2": degrees of freedom `nu`
+    /// - "f": numerator and denominator degrees of freedom `nu1` and `nu2`
+    /// - "binomial": number of trials `n` and probability of success `p`
+    /// - "poisson": mean `lambda`
+    /// - "geometric": probability of success `p` (the distribution is of the number of failures
+    ///   before the first success)
+    /// ```typescript
+    /// let d = dist("normal", #{mu: 1.0, sigma: 2.0});
+    /// assert_eq(d.name, "normal");
+    /// assert_eq(d.params, #{mu: 1.0, sigma: 2.0});
+    /// assert_eq(to_string(d), "normal(mu=1.0, sigma=2.0)");
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { dist("t", #{nu: 1.0 / 0.0}); } catch { result = "invalid"; }
+    /// assert_eq(result, "invalid");
+    /// ```
+    #[rhai_fn(name = "dist", return_raw)]
+    pub fn dist(name: ImmutableString, params: Map) -> Result<Distribution, Box<EvalAltResult>> {
+        Distribution::new(name.as_str(), &params)
+    }
+
+    /// Creates a probability distribution with its default parameters, which only some
+    /// distributions have.
+    /// ```typescript
+    /// let d = dist("uniform");
+    /// assert_eq(d.params, #{a: 0.0, b: 1.0});
+    /// ```
+    #[rhai_fn(name = "dist", return_raw)]
+    pub fn dist_with_defaults(name: ImmutableString) -> Result<Distribution, Box<EvalAltResult>> {
+        Distribution::new(name.as_str(), &Map::new())
+    }
+
+    /// Returns the name of a distribution.
+    /// ```typescript
+    /// let d = dist("poisson", #{lambda: 3});
+    /// assert_eq(d.name, "poisson");
+    /// ```
+    #[rhai_fn(get = "name", pure)]
+    pub fn get_name(d: &mut Distribution) -> ImmutableString {
+        d.name().into()
+    }
+
+    /// Returns the parameters of a distribution as a map.
+    /// ```typescript
+    /// let d = dist("binomial", #{n: 10, p: 0.5});
+    /// assert_eq(d.params, #{n: 10.0, p: 0.5});
+    /// ```
+    #[rhai_fn(get = "params", pure)]
+    pub fn get_params(d: &mut Distribution) -> Map {
+        d.params()
+            .into_iter()
+            .map(|(name, value)| (name.into(), Dynamic::from_float(value)))
+            .collect()
+    }
+
+    /// Evaluates the probability density function of a distribution (or the probability mass
+    /// function of a discrete distribution) at a number, or at each element of an array.
+    /// ```typescript
+    /// let d = dist("normal");
+    /// assert_approx_eq(pdf(d, 0), 0.3989422804014327);
+    /// assert_approx_eq(pdf(d, [-1, 1]), [0.24197072451914337, 0.24197072451914337]);
+    /// ```
+    /// ```typescript
+    /// assert_approx_eq(pdf(dist("gamma", #{a: 2, b: 3}), 3), 0.12262648039048077);
+    /// assert_approx_eq(pdf(dist("beta", #{a: 2, b: 3}), 0.5), 1.5);
+    /// assert_approx_eq(pdf(dist("t", #{nu: 5}), 1), 0.21967979735098053);
+    /// assert_approx_eq(pdf(dist("f", #{nu1: 3, nu2: 7}), 1), 0.3842510958537741);
+    /// ```
+    #[rhai_fn(name = "pdf", return_raw, pure)]
+    pub fn pdf(d: &mut Distribution, x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        map_elements(*d, x, Distribution::pdf)
+    }
+
+    /// Evaluates the probability mass function of a discrete distribution, which is the
+    /// probability of each value. This is the same as `pdf`.
+    /// ```typescript
+    /// let d = dist("binomial", #{n: 4, p: 0.5});
+    /// assert_approx_eq(pmf(d, [0, 1, 2, 3, 4]), [0.0625, 0.25, 0.375, 0.25, 0.0625]);
+    /// assert_eq(pmf(d, 1.5), 0.0);
+    /// ```
+    /// ```typescript
+    /// assert_approx_eq(pmf(dist("poisson", #{lambda: 2}), 3), 0.18044704431548356);
+    /// assert_approx_eq(pmf(dist("geometric", #{p: 0.25}), 2), 0.140625);
+    /// ```
+    #[rhai_fn(name = "pmf", return_raw, pure)]
+    pub fn pmf(d: &mut Distribution, x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        pdf(d, x)
+    }
+
+    /// Evaluates the cumulative distribution function of a distribution at a number, or at each
+    /// element of an array.
+    /// ```typescript
+    /// let d = dist("normal", #{mu: 1, sigma: 2});
+    /// assert_approx_eq(cdf(d, 1), 0.5);
+    /// assert_approx_eq(cdf(d, [-1, 3]), [0.15865525393145707, 0.8413447460685429]);
+    /// ```
+    /// ```typescript
+    /// assert_approx_eq(cdf(dist("exponential", #{mu: 2}), 2), 1.0 - exp(-1.0));
+    /// assert_approx_eq(cdf(dist("chi2", #{nu: 3}), 2), 0.42759329552912023);
+    /// assert_approx_eq(cdf(dist("weibull", #{a: 2, b: 3}), 1), 0.11750309741540454);
+    /// assert_approx_eq(cdf(dist("poisson", #{lambda: 2}), 3), 0.857123460498547);
+    /// ```
+    #[rhai_fn(name = "cdf", return_raw, pure)]
+    pub fn cdf(d: &mut Distribution, x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        map_elements(*d, x, Distribution::cdf)
+    }
+
+    /// Evaluates the inverse cumulative distribution function (quantile function) of a
+    /// distribution at a probability, or at each element of an array of probabilities. For a
+    /// discrete distribution, this is the smallest value whose cumulative probability is at least
+    /// `p`. The result is NaN for probabilities outside of the interval from 0 to 1.
+    /// ```typescript
+    /// let d = dist("normal");
+    /// assert_approx_eq(inv(d, 0.975), 1.959963984540054);
+    /// assert_approx_eq(inv(d, [0.5, 0.025]), [0.0, -1.959963984540054]);
+    /// ```
+    /// ```typescript
+    /// assert_approx_eq(inv(dist("t", #{nu: 10}), 0.975), 2.228138851986274, 1e-9);
+    /// assert_approx_eq(inv(dist("chi2", #{nu: 4}), 0.95), 9.487729036781154);
+    /// assert_approx_eq(inv(dist("f", #{nu1: 2, nu2: 10}), 0.95), 4.102821015130399);
+    /// assert_eq(inv(dist("binomial", #{n: 10, p: 0.5}), 0.5), 5.0);
+    /// ```
+    /// ```typescript
+    /// let x = inv(dist("poisson", #{lambda: 1e20}), 0.5);
+    /// assert(x > 0.99e20 && x < 1.01e20);
+    /// ```
+    #[rhai_fn(name = "inv", return_raw, pure)]
+    pub fn inv(d: &mut Distribution, p: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        map_elements(*d, p, Distribution::inv)
+    }
+
+    /// Returns the mean of a distribution, which is NaN if it is undefined.
+    /// ```typescript
+    /// assert_eq(mean(dist("gamma", #{a: 2, b: 3})), 6.0);
+    /// assert_eq(mean(dist("binomial", #{n: 10, p: 0.3})), 3.0);
+    /// ```
+    #[rhai_fn(name = "mean", pure)]
+    pub fn mean(d: &mut Distribution) -> FLOAT {
+        d.mean()
+    }
+
+    /// Returns the variance of a distribution, which is NaN if it is undefined.
+    /// ```typescript
+    /// assert_eq(variance(dist("uniform", #{a: 0, b: 6})), 3.0);
+    /// assert_eq(variance(dist("poisson", #{lambda: 4})), 4.0);
+    /// ```
+    #[rhai_fn(name = "variance", pure)]
+    pub fn variance(d: &mut Distribution) -> FLOAT {
+        d.variance()
+    }
+
+    /// Draws a random value from a distribution.
+    /// ```typescript
+    /// let d = dist("uniform", #{a: 2, b: 3});
+    /// let r = rnd(d);
+    /// assert(r >= 2.0 && r <= 3.0);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[rhai_fn(name = "rnd", pure)]
+    pub fn rnd(d: &mut Distribution) -> FLOAT {
+        d.sample()
+    }
+
+    /// Returns an array of random values drawn from a distribution. The size is given as for
+    /// `rand`: a single INT for a square matrix, or an array with the length of each dimension.
+    /// ```typescript
+    /// let d = dist("poisson", #{lambda: 3});
+    /// let samples = rnd(d, [2, 5]);
+    /// assert_eq(size(samples), [2, 5]);
+    /// assert(all(ge(flatten(samples), 0)));
+    /// ```
+    /// ```typescript
+    /// let samples = rnd(dist("normal"), [1000]);
+    /// assert_eq(len(samples), 1000);
+    /// assert(abs(mean(samples)) < 0.2);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[rhai_fn(name = "rnd", return_raw, pure)]
+    pub fn rnd_with_size(d: &mut Distribution, size: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        let d = *d;
+        crate::matrix_functions::rand_single_input(size).map(|values| resample(values, d))
+    }
+
+    /// Converts a distribution into a string that shows its name and parameters.
+    /// ```typescript
+    /// let d = dist("t", #{nu: 4});
+    /// assert_eq(to_string(d), "t(nu=4.0)");
+    /// ```
+    #[rhai_fn(name = "to_string", name = "to_debug", pure)]
+    pub fn to_string(d: &mut Distribution) -> ImmutableString {
+        d.to_string().into()
+    }
+
+    /// Checks whether two distributions are the same, with the same parameters.
+    /// ```typescript
+    /// assert(dist("normal") == dist("normal", #{mu: 0, sigma: 1}));
+    /// ```
+    #[rhai_fn(name = "==")]
+    pub fn eq(a: Distribution, b: Distribution) -> bool {
+        a == b
+    }
+
+    /// Checks whether two distributions differ in type or parameters.
+    /// ```typescript
+    /// assert(dist("normal") != dist("normal", #{sigma: 2}));
+    /// ```
+    #[rhai_fn(name = "!=")]
+    pub fn ne(a: Distribution, b: Distribution) -> bool {
+        a != b
+    }
+}
@@ -0,0 +1,484 @@
+use rhai::plugin::*;
+
+#[export_module]
+pub mod histogram_functions {
+    use crate::if_list_convert_to_vec_float_and_do;
+    use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, Map, Position, FLOAT, INT};
+    use std::collections::BTreeMap;
+
+    /// Flattens an array of INT and/or FLOAT values (of any shape) into a vector of FLOAT values.
+    fn to_values(arr: &mut Array) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        if_list_convert_to_vec_float_and_do(&mut crate::matrix_functions::flatten(arr), Ok)
+    }
+
+    /// The largest number of bins that can be requested or chosen automatically (as in MATLAB).
+    const MAX_BINS: INT = 65536;
+
+    /// The largest total number of bins for a bivariate histogram.
+    const MAX_BINS_2D: usize = 1 << 24;
+
+    /// Returns `nbins` bins of equal width spanning the interval from `low` to `high`. If the
+    /// interval is empty, the bins span one unit around it instead.
+    fn edges_from_count(
+        low: FLOAT,
+        high: FLOAT,
+        nbins: INT,
+    ) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        if !(1..=MAX_BINS).contains(&nbins) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("The number of bins must be between 1 and {MAX_BINS}"),
+                Position::NONE,
+            )
+            .into());
+        }
+        let (low, high) = if high > low {
+            (low, high)
+        } else {
+            (low - 0.5, high + 0.5)
+        };
+        // Divide each end separately, so the width stays finite for a span of nearly 2 * FLOAT::MAX
+        let width = high / nbins as FLOAT - low / nbins as FLOAT;
+        Ok((0..=nbins)
+            .map(|i| match i {
+                0 => low,
+                i if i == nbins => high,
+                i => low + i as FLOAT * width,
+            })
+            .collect())
+    }
+
+    /// Chooses bin edges for data automatically, using the rule "sturges", "scott" or "fd"
+    /// (Freedman–Diaconis). If the rule asks for more than `MAX_BINS` bins, that many bins of equal
+    /// width are used instead.
+    fn automatic_edges(
+        x: Vec<FLOAT>,
+        method: ImmutableString,
+    ) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        let x = x
+            .into_iter()
+            .filter(|v| !v.is_nan())
+            .collect::<Vec<FLOAT>>();
+        let n = x.len() as FLOAT;
+        let low = x.iter().copied().fold(FLOAT::INFINITY, FLOAT::min);
+        let high = x.iter().copied().fold(FLOAT::NEG_INFINITY, FLOAT::max);
+        if x.is_empty() {
+            return edges_from_count(0.0, 1.0, 1);
+        }
+        if !low.is_finite() || !high.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Bin edges cannot be chosen automatically for infinite data".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let width = match method.as_str() {
+            "sturges" => return edges_from_count(low, high, (n.log2() + 1.0).ceil() as INT),
+            "scott" => {
+                let mut arr = x.iter().map(|&v| Dynamic::from_float(v)).collect::<Array>();
+                let sigma = crate::stats::std(&mut arr)?.as_float().unwrap();
+                3.5 * sigma * n.powf(-1.0 / 3.0)
+            }
+            "fd" => {
+                let mut arr = x.iter().map(|&v| Dynamic::from_float(v)).collect::<Array>();
+                let iqr = crate::stats::iqr(&mut arr)?;
+                2.0 * iqr * n.powf(-1.0 / 3.0)
+            }
+            _ => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!(
+                        "Unknown binning method '{method}', expected 'sturges', 'scott' or 'fd'"
+                    ),
+                    Position::NONE,
+                )
+                .into())
+            }
+        };
+        if width > 0.0 && width.is_finite() && high > low {
+            let nbins = ((high - low) / width).ceil().max(1.0);
+            if nbins > MAX_BINS as FLOAT {
+                return edges_from_count(low, high, MAX_BINS);
+            }
+            Ok((0..=nbins as INT)
+                .map(|i| low + i as FLOAT * width)
+                .collect())
+        } else {
+            edges_from_count(low, high, 1)
+        }
+    }
+
+    /// Checks that an array of bin edges is numeric, has at least two elements and is strictly
+    /// increasing, and returns it as FLOAT values.
+    fn check_edges(mut edges: Array) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        let edges = if_list_convert_to_vec_float_and_do(&mut edges, Ok)?;
+        if edges.len() < 2
+            || edges
+                .windows(2)
+                .any(|w| w[0].partial_cmp(&w[1]) != Some(std::cmp::Ordering::Less))
+        {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The bin edges must have at least two elements and be strictly increasing"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok(edges)
+        }
+    }
+
+    /// Resolves a bin specification, which is either a number of bins (INT), an array of bin
+    /// edges, or the name of an automatic binning rule, into bin edges for the data `x`.
+    fn resolve_edges(x: Vec<FLOAT>, bins: Dynamic) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        if bins.is_int() {
+            let finite = x.iter().copied().filter(|v| v.is_finite());
+            let low = finite.clone().fold(FLOAT::INFINITY, FLOAT::min);
+            let high = finite.fold(FLOAT::NEG_INFINITY, FLOAT::max);
+            if low.is_finite() {
+                edges_from_count(low, high, bins.as_int().unwrap())
+            } else {
+                edges_from_count(0.0, 1.0, bins.as_int().unwrap())
+            }
+        } else if bins.is_array() {
+            check_edges(bins.into_array().unwrap())
+        } else if bins.is_string() {
+            automatic_edges(x, bins.into_immutable_string().unwrap())
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The bins must be specified as a number of bins, an array of edges, or the name of a binning rule".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Returns the bin that each value falls into, where bin `i` covers the interval from
+    /// `edges[i]` (inclusive) to `edges[i + 1]` (exclusive), except for the last bin, which also
+    /// includes its right edge. Values outside of the edges, and NaN, fall into no bin.
+    fn bin_indices(x: Vec<FLOAT>, edges: Vec<FLOAT>) -> Vec<Option<usize>> {
+        let last = edges.len() - 1;
+        x.iter()
+            .map(|&v| {
+                if v.is_nan() || v < edges[0] || v > edges[last] {
+                    None
+                } else if v == edges[last] {
+                    Some(last - 1)
+                } else {
+                    Some(edges.partition_point(|&e| e <= v).saturating_sub(1))
+                }
+            })
+            .collect()
+    }
+
+    /// Converts bin edges to an array of FLOAT values.
+    fn edges_to_array(edges: Vec<FLOAT>) -> Array {
+        edges.into_iter().map(Dynamic::from_float).collect()
+    }
+
+    /// Normalizes bin counts using the mode "count", "probability" (the fraction of the `total`
+    /// values in each bin), "pdf" (the probability divided by the size of each bin) or "cdf" (the
+    /// cumulative probability up to the end of each bin, in order).
+    fn normalize_counts(
+        counts: Vec<INT>,
+        sizes: Vec<FLOAT>,
+        total: INT,
+        normalization: ImmutableString,
+    ) -> Result<Vec<Dynamic>, Box<EvalAltResult>> {
+        let total = total as FLOAT;
+        match normalization.as_str() {
+            "count" => Ok(counts.into_iter().map(Dynamic::from_int).collect()),
+            "probability" => Ok(counts
+                .into_iter()
+                .map(|c| Dynamic::from_float(c as FLOAT / total))
+                .collect()),
+            "pdf" => Ok(counts
+                .into_iter()
+                .zip(sizes)
+                .map(|(c, s)| Dynamic::from_float(c as FLOAT / total / s))
+                .collect()),
+            "cdf" => Ok(counts
+                .into_iter()
+                .scan(0, |running, c| {
+                    *running += c;
+                    Some(Dynamic::from_float(*running as FLOAT / total))
+                })
+                .collect()),
+            _ => Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown normalization '{normalization}', expected 'count', 'probability', 'pdf' or 'cdf'"),
+                Position::NONE,
+            )
+            .into()),
+        }
+    }
+
+    /// Bins the values of an array, choosing the bin edges automatically with Scott's rule.
+    /// Returns a map with the keys `counts` (the number of values in each bin) and `edges`. See
+    /// `histcounts(arr, bins, normalization)` for details.
+    /// ```typescript
+    /// let result = histcounts([1, 2, 2, 3, 5]);
+    /// assert_eq(sum(result.counts), 5);
+    /// assert_eq(result.edges.len, result.counts.len + 1);
+    /// ```
+    #[rhai_fn(name = "histcounts", return_raw, pure)]
+    pub fn histcounts(arr: &mut Array) -> Result<Map, Box<EvalAltResult>> {
+        histcounts_with_normalization(arr, Dynamic::from("scott"), "count".into())
+    }
+
+    /// Bins the values of an array, where `bins` is a number of bins of equal width spanning the
+    /// data, an array of bin edges, or the name of a rule for choosing the bin width
+    /// automatically: "sturges", "scott" or "fd" (Freedman–Diaconis). There are at most 65536
+    /// bins. Returns a map with the keys `counts` (the number of values in each bin) and `edges`.
+    /// ```typescript
+    /// let result = histcounts([1, 2, 2, 3, 5], 2);
+    /// assert_eq(result.counts, [3, 2]);
+    /// assert_eq(result.edges, [1.0, 3.0, 5.0]);
+    /// ```
+    /// ```typescript
+    /// let result = histcounts([1, 2, 2, 3, 5], [0, 2, 4, 6]);
+    /// assert_eq(result.counts, [1, 3, 1]);
+    /// ```
+    /// ```typescript
+    /// let result = histcounts([1, 2, 2, 3, 5], "sturges");
+    /// assert_eq(result.counts, [1, 2, 1, 1]);
+    /// assert_eq(result.edges, [1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// ```
+    /// ```typescript
+    /// let result = histcounts([0.0, 1e-9, 2e-9, 3e-9, 1e9], "fd");
+    /// assert_eq(result.counts.len, 65536);
+    /// ```
+    /// ```typescript
+    /// let result = histcounts([-1e308, 1e308], 2);
+    /// assert_eq(result.counts, [1, 1]);
+    /// assert_eq(result.edges, [-1e308, 0.0, 1e308]);
+    /// assert_eq(sum(histcounts([-1e308, 1e308], "scott").counts), 2);
+    /// ```
+    #[rhai_fn(name = "histcounts", return_raw, pure)]
+    pub fn histcounts_with_bins(arr: &mut Array, bins: Dynamic) -> Result<Map, Box<EvalAltResult>> {
+        histcounts_with_normalization(arr, bins, "count".into())
+    }
+
+    /// Bins the values of an array and normalizes the counts, where `normalization` is "count",
+    /// "probability" (the fraction of the values in each bin), "pdf" (the probability divided by
+    /// the bin width) or "cdf" (the cumulative probability up to the end of each bin). Each bin
+    /// includes its left edge, and the last bin also includes its right edge. Values outside of
+    /// the edges are not counted, but are included in the total used for normalization. NaN
+    /// values are ignored.
+    /// ```typescript
+    /// let data = [1, 2, 2, 3, 5];
+    /// let edges = [0, 2, 4, 6];
+    /// assert_eq(histcounts(data, edges, "probability").counts, [0.2, 0.6, 0.2]);
+    /// assert_eq(histcounts(data, edges, "pdf").counts, [0.1, 0.3, 0.1]);
+    /// assert_eq(histcounts(data, edges, "cdf").counts, [0.2, 0.8, 1.0]);
+    /// ```
+    #[rhai_fn(name = "histcounts", return_raw, pure)]
+    pub fn histcounts_with_normalization(
+        arr: &mut Array,
+        bins: Dynamic,
+        normalization: ImmutableString,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let x = to_values(arr)?;
+        let edges = resolve_edges(x.clone(), bins)?;
+        let total = x.iter().filter(|v| !v.is_nan()).count() as INT;
+
+        let mut counts = vec![0; edges.len() - 1];
+        for i in bin_indices(x, edges.clone()).into_iter().flatten() {
+            counts[i] += 1;
+        }
+        let widths = edges.windows(2).map(|w| w[1] - w[0]).collect();
+
+        let mut result = BTreeMap::new();
+        result.insert(
+            "counts".into(),
+            Dynamic::from_array(normalize_counts(counts, widths, total, normalization)?),
+        );
+        result.insert("edges".into(), Dynamic::from_array(edges_to_array(edges)));
+        Ok(result)
+    }
+
+    /// Counts the values of an array that fall between consecutive edges. Element `i` of the
+    /// result counts the values `v` such that `edges[i] <= v < edges[i + 1]`, and the last element
+    /// counts the values equal to the last edge, so the result is as long as `edges`.
+    /// ```typescript
+    /// let counts = histc([1, 2, 2, 3, 5], [1, 2, 3, 4, 5]);
+    /// assert_eq(counts, [1, 2, 1, 0, 1]);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { histc([1, 2], [[0, 1], [2, 3]]); } catch { result = "nested edges"; }
+    /// assert_eq(result, "nested edges");
+    /// ```
+    #[rhai_fn(name = "histc", return_raw, pure)]
+    pub fn histc(arr: &mut Array, edges: Array) -> Result<Array, Box<EvalAltResult>> {
+        let x = to_values(arr)?;
+        let edges = check_edges(edges)?;
+        let last = edges.len() - 1;
+        let mut counts = vec![0 as INT; edges.len()];
+        for (v, i) in x.iter().zip(bin_indices(x.clone(), edges.clone())) {
+            if let Some(i) = i {
+                counts[if *v == edges[last] { last } else { i }] += 1;
+            }
+        }
+        Ok(counts.into_iter().map(Dynamic::from_int).collect())
+    }
+
+    /// Returns the index (counting from 0) of the bin that each value of an array falls into,
+    /// where bin `i` covers the interval from `edges[i]` (inclusive) to `edges[i + 1]`
+    /// (exclusive), except for the last bin, which also includes its right edge. The index is -1
+    /// for values outside of the edges and for NaN. The result has the same shape as the input.
+    /// ```typescript
+    /// let bins = discretize([1, 2.5, 4, 7], [0, 2, 4]);
+    /// assert_eq(bins, [0, 1, 1, -1]);
+    /// ```
+    /// ```typescript
+    /// let bins = discretize([[0, 3], [5, 1]], [0, 2, 4, 6]);
+    /// assert_eq(bins, [[0, 1], [2, 0]]);
+    /// ```
+    #[rhai_fn(name = "discretize", return_raw, pure)]
+    pub fn discretize(arr: &mut Array, edges: Array) -> Result<Array, Box<EvalAltResult>> {
+        let shape = crate::if_numeric_array_get_shape(arr)?;
+        let x = to_values(arr)?;
+        let indices = bin_indices(x, check_edges(edges)?)
+            .into_iter()
+            .map(|i| Dynamic::from_int(i.map_or(-1, |i| i as INT)))
+            .collect::<Array>();
+        Ok(crate::flat_to_nested_array(&indices, &shape))
+    }
+
+    /// Bins pairs of values from two arrays of equal length into a two-dimensional histogram,
+    /// choosing the bin edges for each array automatically with Scott's rule. Returns a map with
+    /// the keys `counts` (a matrix whose rows correspond to the bins of `x` and whose columns
+    /// correspond to the bins of `y`), `xedges` and `yedges`.
+    /// ```typescript
+    /// let result = histcounts2([1, 2, 3, 4], [1, 1, 2, 2]);
+    /// assert_eq(sum(flatten(result.counts)), 4);
+    /// ```
+    #[rhai_fn(name = "histcounts2", return_raw, pure)]
+    pub fn histcounts2(x: &mut Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        histcounts2_with_normalization(x, y, Dynamic::from("scott"), "count".into())
+    }
+
+    /// Bins pairs of values from two arrays of equal length into a two-dimensional histogram. The
+    /// `bins` are specified as for `histcounts`, and apply to both arrays, or as an array of two
+    /// such specifications, one for `x` and one for `y`.
+    /// ```typescript
+    /// let result = histcounts2([1, 2, 3, 4], [1, 1, 2, 2], 2);
+    /// assert_eq(result.counts, [[2, 0], [0, 2]]);
+    /// assert_eq(result.xedges, [1.0, 2.5, 4.0]);
+    /// assert_eq(result.yedges, [1.0, 1.5, 2.0]);
+    /// ```
+    /// ```typescript
+    /// let result = histcounts2([1, 2, 3, 4], [1, 1, 2, 2], [[0, 5], 2]);
+    /// assert_eq(result.counts, [[2, 2]]);
+    /// ```
+    #[rhai_fn(name = "histcounts2", return_raw, pure)]
+    pub fn histcounts2_with_bins(
+        x: &mut Array,
+        y: Array,
+        bins: Dynamic,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        histcounts2_with_normalization(x, y, bins, "count".into())
+    }
+
+    /// Bins pairs of values into a two-dimensional histogram and normalizes the counts, where
+    /// `normalization` is "count", "probability", "pdf" (the probability divided by the area of
+    /// each bin) or "cdf" (the cumulative probability over all bins up to and including each bin
+    /// in both directions).
+    /// ```typescript
+    /// let result = histcounts2([1, 2, 3, 4], [1, 1, 2, 2], 2, "cdf");
+    /// assert_eq(result.counts, [[0.5, 0.5], [0.5, 1.0]]);
+    /// ```
+    #[rhai_fn(name = "histcounts2", return_raw, pure)]
+    pub fn histcounts2_with_normalization(
+        x: &mut Array,
+        mut y: Array,
+        bins: Dynamic,
+        normalization: ImmutableString,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let x = to_values(x)?;
+        let y = to_values(&mut y)?;
+        if x.len() != y.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The input arrays must have the same length ({} and {})",
+                    x.len(),
+                    y.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let (xbins, ybins) = match bins.clone().into_array() {
+            Ok(pair) if pair.len() == 2 && !pair.iter().all(|b| b.is_int() || b.is_float()) => {
+                (pair[0].clone(), pair[1].clone())
+            }
+            _ => (bins.clone(), bins),
+        };
+        let xedges = resolve_edges(x.clone(), xbins)?;
+        let yedges = resolve_edges(y.clone(), ybins)?;
+        let (nx, ny) = (xedges.len() - 1, yedges.len() - 1);
+        if nx.saturating_mul(ny) > MAX_BINS_2D {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("The total number of bins ({nx} x {ny}) must be at most {MAX_BINS_2D}"),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut counts = vec![0 as INT; nx * ny];
+        let xbin = bin_indices(x.clone(), xedges.clone());
+        let ybin = bin_indices(y.clone(), yedges.clone());
+        for (i, j) in xbin.into_iter().zip(ybin) {
+            if let (Some(i), Some(j)) = (i, j) {
+                counts[i * ny + j] += 1;
+            }
+        }
+        if normalization == "cdf" {
+            for i in 0..nx {
+                for j in 0..ny {
+                    let above = if i > 0 { counts[(i - 1) * ny + j] } else { 0 };
+                    let left = if j > 0 { counts[i * ny + j - 1] } else { 0 };
+                    let diagonal = if i > 0 && j > 0 {
+                        counts[(i - 1) * ny + j - 1]
+                    } else {
+                        0
+                    };
+                    counts[i * ny + j] += above + left - diagonal;
+                }
+            }
+        }
+
+        let total = x
+            .iter()
+            .zip(y.iter())
+            .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+            .count() as INT;
+        let areas = (0..nx * ny)
+            .map(|k| {
+                let (i, j) = (k / ny, k % ny);
+                (xedges[i + 1] - xedges[i]) * (yedges[j + 1] - yedges[j])
+            })
+            .collect();
+        // The cumulative sums are already taken above, so only the scaling remains.
+        let mode = if normalization == "cdf" {
+            "probability".into()
+        } else {
+            normalization
+        };
+        let normalized = normalize_counts(counts, areas, total, mode)?;
+
+        let mut result = BTreeMap::new();
+        result.insert(
+            "counts".into(),
+            Dynamic::from_array(
+                normalized
+                    .chunks(ny)
+                    .map(|row| Dynamic::from_array(row.to_vec()))
+                    .collect(),
+            ),
+        );
+        result.insert("xedges".into(), Dynamic::from_array(edges_to_array(xedges)));
+        result.insert("yedges".into(), Dynamic::from_array(edges_to_array(yedges)));
+        Ok(result)
+    }
+}
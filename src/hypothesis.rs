@@ -0,0 +1,949 @@
+use rhai::plugin::*;
+
+#[export_module]
+pub mod hypothesis_functions {
+    use crate::{if_list_convert_to_vec_float_and_do, normal_cdf, Distribution};
+    use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, Map, Position, FLOAT, INT};
+    use std::cmp::Ordering;
+    use std::collections::BTreeMap;
+
+    /// Reads the significance level `alpha` (default 0.05) and the alternative hypothesis `tail`
+    /// (default "both") from a map of options, checking that it has no keys other than those in
+    /// `allowed`.
+    fn read_options(
+        options: Map,
+        allowed: Vec<&str>,
+    ) -> Result<(FLOAT, ImmutableString), Box<EvalAltResult>> {
+        if let Some(key) = options.keys().find(|k| !allowed.contains(&k.as_str())) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown option '{key}', expected {}", allowed.join(", ")),
+                Position::NONE,
+            )
+            .into());
+        }
+        let alpha = match options.get("alpha") {
+            Some(alpha) => crate::if_int_convert_to_float_and_do(alpha.clone(), Ok)?,
+            None => 0.05,
+        };
+        if !(alpha > 0.0 && alpha < 1.0) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The significance level must be between 0 and 1".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let tail = match options.get("tail") {
+            Some(tail) => tail.clone().into_immutable_string().unwrap_or_default(),
+            None => "both".into(),
+        };
+        if !["both", "left", "right"].contains(&tail.as_str()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown tail '{tail}', expected 'both', 'left' or 'right'"),
+                Position::NONE,
+            )
+            .into());
+        }
+        Ok((alpha, tail))
+    }
+
+    /// Converts a list of INT and/or FLOAT values into a vector of FLOAT values, checking that it
+    /// has at least `min_len` elements and no NaN values.
+    fn to_sample(mut arr: Array, min_len: INT) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        let x = if_list_convert_to_vec_float_and_do(&mut arr, Ok)?;
+        if x.iter().any(|v| v.is_nan()) {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The sample must not contain NaN values".to_string(),
+                Position::NONE,
+            )
+            .into())
+        } else if (x.len() as INT) < min_len {
+            Err(EvalAltResult::ErrorArithmetic(
+                format!("The sample must have at least {min_len} values"),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok(x)
+        }
+    }
+
+    /// Returns the mean and the sample variance (normalized by N - 1) of a sample.
+    fn mean_and_variance(x: Vec<FLOAT>) -> (FLOAT, FLOAT) {
+        let n = x.len() as FLOAT;
+        let mean = x.iter().sum::<FLOAT>() / n;
+        let variance = x.iter().map(|v| (v - mean).powi(2)).sum::<FLOAT>() / (n - 1.0);
+        (mean, variance)
+    }
+
+    /// Builds the result of a test from its p-value, the significance level, the test statistic,
+    /// the confidence interval and the degrees of freedom. The null hypothesis is rejected (`h` is
+    /// true) if the p-value is less than the significance level.
+    fn test_result(p: FLOAT, alpha: FLOAT, stat: FLOAT, ci: Dynamic, df: Dynamic) -> Map {
+        let mut result = BTreeMap::new();
+        result.insert("h".into(), Dynamic::from_bool(p < alpha));
+        result.insert("p".into(), Dynamic::from_float(p));
+        result.insert("stat".into(), Dynamic::from_float(stat));
+        result.insert("ci".into(), ci);
+        result.insert("df".into(), df);
+        result
+    }
+
+    /// Returns the p-value of a statistic with a distribution that is symmetric about zero, for
+    /// the alternative hypothesis `tail`.
+    fn symmetric_p_value(stat: FLOAT, d: Distribution, tail: ImmutableString) -> FLOAT {
+        match tail.as_str() {
+            "left" => d.cdf(stat),
+            "right" => d.cdf(-stat),
+            _ => (2.0 * d.cdf(-stat.abs())).min(1.0),
+        }
+    }
+
+    /// Returns the confidence interval for an estimate with standard error `se`, whose
+    /// standardized value has the symmetric distribution `d`, for the alternative hypothesis
+    /// `tail`. One-sided intervals are unbounded on one side.
+    fn confidence_interval(
+        estimate: FLOAT,
+        se: FLOAT,
+        d: Distribution,
+        alpha: FLOAT,
+        tail: ImmutableString,
+    ) -> Dynamic {
+        let (low, high) = match tail.as_str() {
+            "left" => (FLOAT::NEG_INFINITY, estimate + d.inv(1.0 - alpha) * se),
+            "right" => (estimate - d.inv(1.0 - alpha) * se, FLOAT::INFINITY),
+            _ => {
+                let c = d.inv(1.0 - alpha / 2.0);
+                (estimate - c * se, estimate + c * se)
+            }
+        };
+        Dynamic::from_array(vec![Dynamic::from_float(low), Dynamic::from_float(high)])
+    }
+
+    /// Performs a one-sample t-test of the hypothesis that the data come from a normal
+    /// distribution with mean zero. See `ttest(x, m, options)`.
+    /// ```typescript
+    /// let result = ttest([-0.5, 0.2, 0.4, -0.1, 0.3]);
+    /// assert_eq(result.h, false);
+    /// assert_eq(result.df, 4.0);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { ttest([[1, 2], [3, 4]]); } catch { result = "not a list"; }
+    /// assert_eq(result, "not a list");
+    /// ```
+    #[rhai_fn(name = "ttest", return_raw)]
+    pub fn ttest(x: Array) -> Result<Map, Box<EvalAltResult>> {
+        ttest_with_options(x, Dynamic::from_int(0), Map::new())
+    }
+
+    /// Performs a one-sample t-test of the hypothesis that the data come from a normal
+    /// distribution with mean `m`, or, if `m` is an array of the same length as `x`, a paired
+    /// t-test of the hypothesis that the differences `x - m` have mean zero.
+    /// ```typescript
+    /// let x = [5.1, 4.9, 5.6, 5.8, 6.0, 5.5, 5.3];
+    /// let result = ttest(x, 5);
+    /// assert_eq(result.h, true);
+    /// assert_approx_eq(result.stat, 3.1278490197651534);
+    /// assert_approx_eq(result.p, 0.02038029857335181, 1e-8);
+    /// assert_approx_eq(result.ci, [5.099520648481035, 5.814765065804678], 1e-8);
+    /// ```
+    /// ```typescript
+    /// let before = [200, 190, 210, 220, 205];
+    /// let after = [195, 185, 200, 215, 200];
+    /// let result = ttest(before, after);
+    /// assert_eq(result.stat, 6.0);
+    /// assert_eq(result.df, 4.0);
+    /// ```
+    #[rhai_fn(name = "ttest", return_raw)]
+    pub fn ttest_with_mean(x: Array, m: Dynamic) -> Result<Map, Box<EvalAltResult>> {
+        ttest_with_options(x, m, Map::new())
+    }
+
+    /// Performs a one-sample or paired t-test with options: the significance level `alpha`
+    /// (default 0.05) and the alternative hypothesis `tail`, which is "both" (the mean is not
+    /// `m`, the default), "left" (the mean is less than `m`) or "right" (the mean is greater than
+    /// `m`). Returns a map with the keys `h` (whether the null hypothesis is rejected at the
+    /// significance level), `p` (the p-value), `stat` (the t statistic), `ci` (the confidence
+    /// interval for the mean, or for the mean difference of a paired test) and `df` (the degrees
+    /// of freedom).
+    /// ```typescript
+    /// let x = [5.1, 4.9, 5.6, 5.8, 6.0, 5.5, 5.3];
+    /// let result = ttest(x, 5, #{tail: "right", alpha: 0.01});
+    /// assert_eq(result.h, false);
+    /// assert_approx_eq(result.p, 0.010190149286675904, 1e-8);
+    /// assert_eq(result.ci[1], inf);
+    /// ```
+    #[rhai_fn(name = "ttest", return_raw)]
+    pub fn ttest_with_options(
+        x: Array,
+        m: Dynamic,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, tail) = read_options(options, vec!["alpha", "tail"])?;
+        let (x, m) = if m.is_array() {
+            let x = to_sample(x, 2)?;
+            let y = to_sample(m.into_array().unwrap(), 2)?;
+            if x.len() != y.len() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The samples of a paired t-test must have the same length".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            (x.iter().zip(y).map(|(a, b)| a - b).collect(), 0.0)
+        } else {
+            (
+                to_sample(x, 2)?,
+                crate::if_int_convert_to_float_and_do(m, Ok)?,
+            )
+        };
+
+        let n = x.len() as FLOAT;
+        let (mean, variance) = mean_and_variance(x);
+        let se = (variance / n).sqrt();
+        let t = (mean - m) / se;
+        let d = Distribution::StudentT { nu: n - 1.0 };
+        Ok(test_result(
+            symmetric_p_value(t, d, tail.clone()),
+            alpha,
+            t,
+            confidence_interval(mean, se, d, alpha, tail),
+            Dynamic::from_float(n - 1.0),
+        ))
+    }
+
+    /// Performs a two-sample t-test of the hypothesis that two independent samples come from
+    /// normal distributions with equal means and equal, unknown variances. See
+    /// `ttest2(x, y, options)`.
+    /// ```typescript
+    /// let x = [20.1, 19.8, 21.3, 20.7, 20.4];
+    /// let y = [18.9, 19.5, 19.2, 18.6, 19.8];
+    /// let result = ttest2(x, y);
+    /// assert_eq(result.h, true);
+    /// assert_eq(result.df, 8.0);
+    /// assert_approx_eq(result.stat, 3.771711342562289, 1e-10);
+    /// ```
+    #[rhai_fn(name = "ttest2", return_raw)]
+    pub fn ttest2(x: Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        ttest2_with_options(x, y, Map::new())
+    }
+
+    /// Performs a two-sample t-test with options: the significance level `alpha` (default 0.05),
+    /// the alternative hypothesis `tail` ("both", "left" or "right", comparing the mean of `x` to
+    /// the mean of `y`), and `vartype`, which is "equal" (a pooled-variance test, the default) or
+    /// "unequal" (Welch's test, with the Welch–Satterthwaite degrees of freedom). The confidence
+    /// interval is for the difference between the means of `x` and `y`.
+    /// ```typescript
+    /// let x = [20.1, 19.8, 21.3, 20.7, 20.4];
+    /// let y = [18.9, 19.5, 19.2, 18.6, 19.8, 19.0, 19.1];
+    /// let result = ttest2(x, y, #{vartype: "unequal"});
+    /// assert_eq(result.h, true);
+    /// assert_approx_eq(result.df, 6.632873587676351, 1e-8);
+    /// ```
+    #[rhai_fn(name = "ttest2", return_raw)]
+    pub fn ttest2_with_options(
+        x: Array,
+        y: Array,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let vartype = match options.get("vartype") {
+            Some(v) => v.clone().into_immutable_string().unwrap_or_default(),
+            None => "equal".into(),
+        };
+        let (alpha, tail) = read_options(options, vec!["alpha", "tail", "vartype"])?;
+        let x = to_sample(x, 2)?;
+        let y = to_sample(y, 2)?;
+        let (nx, ny) = (x.len() as FLOAT, y.len() as FLOAT);
+        let (mx, vx) = mean_and_variance(x);
+        let (my, vy) = mean_and_variance(y);
+
+        let (se, df) = match vartype.as_str() {
+            "equal" => {
+                let df = nx + ny - 2.0;
+                let pooled = ((nx - 1.0) * vx + (ny - 1.0) * vy) / df;
+                ((pooled * (1.0 / nx + 1.0 / ny)).sqrt(), df)
+            }
+            "unequal" => {
+                let (ax, ay) = (vx / nx, vy / ny);
+                let df = (ax + ay).powi(2) / (ax.powi(2) / (nx - 1.0) + ay.powi(2) / (ny - 1.0));
+                ((ax + ay).sqrt(), df)
+            }
+            _ => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!("Unknown variance type '{vartype}', expected 'equal' or 'unequal'"),
+                    Position::NONE,
+                )
+                .into())
+            }
+        };
+        let t = (mx - my) / se;
+        let d = Distribution::StudentT { nu: df };
+        Ok(test_result(
+            symmetric_p_value(t, d, tail.clone()),
+            alpha,
+            t,
+            confidence_interval(mx - my, se, d, alpha, tail),
+            Dynamic::from_float(df),
+        ))
+    }
+
+    /// Performs a z-test of the hypothesis that the data come from a normal distribution with
+    /// mean `mu` and known standard deviation `sigma`. See `ztest(x, mu, sigma, options)`.
+    /// ```typescript
+    /// let x = [10.2, 9.8, 10.5, 10.1, 10.4, 9.9];
+    /// let result = ztest(x, 10, 0.3);
+    /// assert_eq(result.h, false);
+    /// assert_approx_eq(result.stat, 1.2247448713915918, 1e-10);
+    /// assert_approx_eq(result.p, 0.22067136191984577, 1e-8);
+    /// ```
+    #[rhai_fn(name = "ztest", return_raw)]
+    pub fn ztest(x: Array, mu: Dynamic, sigma: Dynamic) -> Result<Map, Box<EvalAltResult>> {
+        ztest_with_options(x, mu, sigma, Map::new())
+    }
+
+    /// Performs a z-test with options: the significance level `alpha` (default 0.05) and the
+    /// alternative hypothesis `tail` ("both", "left" or "right"). The confidence interval is for
+    /// the mean, and the degrees of freedom are `()` because they do not apply.
+    /// ```typescript
+    /// let x = [10.2, 9.8, 10.5, 10.1, 10.4, 9.9];
+    /// let result = ztest(x, 10, 0.3, #{tail: "right"});
+    /// assert_eq(result.h, false);
+    /// assert_approx_eq(result.p, 0.11033568095992288, 1e-8);
+    /// assert_eq(result.df, ());
+    /// ```
+    #[rhai_fn(name = "ztest", return_raw)]
+    pub fn ztest_with_options(
+        x: Array,
+        mu: Dynamic,
+        sigma: Dynamic,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, tail) = read_options(options, vec!["alpha", "tail"])?;
+        let x = to_sample(x, 1)?;
+        let mu = crate::if_int_convert_to_float_and_do(mu, Ok)?;
+        let sigma = crate::if_int_convert_to_float_and_do(sigma, Ok)?;
+        if sigma.partial_cmp(&0.0) != Some(Ordering::Greater) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The standard deviation must be positive".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let n = x.len() as FLOAT;
+        let mean = x.iter().sum::<FLOAT>() / n;
+        let se = sigma / n.sqrt();
+        let z = (mean - mu) / se;
+        let d = Distribution::Normal {
+            mu: 0.0,
+            sigma: 1.0,
+        };
+        Ok(test_result(
+            symmetric_p_value(z, d, tail.clone()),
+            alpha,
+            z,
+            confidence_interval(mean, se, d, alpha, tail),
+            Dynamic::UNIT,
+        ))
+    }
+
+    /// Performs a one-way analysis of variance of the hypothesis that several groups of data,
+    /// given as an array of arrays, come from normal distributions with the same mean. See
+    /// `anova1(groups, options)`.
+    /// ```typescript
+    /// let groups = [[6, 8, 4, 5, 3, 4],
+    ///               [8, 12, 9, 11, 6, 8],
+    ///               [13, 9, 11, 8, 7, 12]];
+    /// let result = anova1(groups);
+    /// assert_eq(result.h, true);
+    /// assert_approx_eq(result.stat, 9.264705882352942, 1e-10);
+    /// assert_approx_eq(result.p, 0.0023987773293929083, 1e-8);
+    /// assert_eq(result.df, [2.0, 15.0]);
+    /// ```
+    #[rhai_fn(name = "anova1", return_raw)]
+    pub fn anova1(groups: Array) -> Result<Map, Box<EvalAltResult>> {
+        anova1_with_options(groups, Map::new())
+    }
+
+    /// Performs a one-way analysis of variance with options: the significance level `alpha`
+    /// (default 0.05). The statistic is the F ratio, `df` holds the between-group and
+    /// within-group degrees of freedom, and the confidence interval is `()` because it does not
+    /// apply. The groups may have different sizes.
+    /// ```typescript
+    /// let groups = [[1, 2, 3], [2, 3, 4, 5]];
+    /// let result = anova1(groups, #{alpha: 0.1});
+    /// assert_eq(result.h, false);
+    /// assert_eq(result.ci, ());
+    /// ```
+    #[rhai_fn(name = "anova1", return_raw)]
+    pub fn anova1_with_options(groups: Array, options: Map) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, _) = read_options(options, vec!["alpha"])?;
+        let groups = groups
+            .into_iter()
+            .map(|g| match g.into_array() {
+                Ok(g) => to_sample(g, 1),
+                Err(_) => Err(EvalAltResult::ErrorArithmetic(
+                    "The groups must be given as an array of arrays".to_string(),
+                    Position::NONE,
+                )
+                .into()),
+            })
+            .collect::<Result<Vec<Vec<FLOAT>>, Box<EvalAltResult>>>()?;
+        let k = groups.len() as FLOAT;
+        let n = groups.iter().map(|g| g.len()).sum::<usize>() as FLOAT;
+        if k < 2.0 || n <= k {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "There must be at least two groups, and more values than groups".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let grand_mean = groups.iter().flatten().sum::<FLOAT>() / n;
+        let (mut between, mut within) = (0.0, 0.0);
+        for g in &groups {
+            let mean = g.iter().sum::<FLOAT>() / g.len() as FLOAT;
+            between += g.len() as FLOAT * (mean - grand_mean).powi(2);
+            within += g.iter().map(|v| (v - mean).powi(2)).sum::<FLOAT>();
+        }
+        let (df1, df2) = (k - 1.0, n - k);
+        let f = (between / df1) / (within / df2);
+        let p = crate::regularized_beta(df2 / 2.0, df1 / 2.0, df2 / (df2 + df1 * f));
+        Ok(test_result(
+            p,
+            alpha,
+            f,
+            Dynamic::UNIT,
+            Dynamic::from_array(vec![Dynamic::from_float(df1), Dynamic::from_float(df2)]),
+        ))
+    }
+
+    /// Performs a chi-square goodness-of-fit test of the hypothesis that observed counts come
+    /// from a uniform distribution over the categories. See
+    /// `chi2gof(observed, expected, options)`.
+    /// ```typescript
+    /// let result = chi2gof([18, 22, 20, 25, 15]);
+    /// assert_eq(result.h, false);
+    /// assert_approx_eq(result.stat, 2.9, 1e-10);
+    /// assert_eq(result.df, 4.0);
+    /// ```
+    #[rhai_fn(name = "chi2gof", return_raw)]
+    pub fn chi2gof(observed: Array) -> Result<Map, Box<EvalAltResult>> {
+        let n = observed.len();
+        let total = to_sample(observed.clone(), 1)?.iter().sum::<FLOAT>();
+        let expected = vec![Dynamic::from_float(total / n as FLOAT); n];
+        chi2gof_with_options(observed, expected, Map::new())
+    }
+
+    /// Performs a chi-square goodness-of-fit test of the hypothesis that observed counts come
+    /// from a distribution with the given expected counts.
+    /// ```typescript
+    /// let observed = [50, 30, 20];
+    /// let expected = [40, 40, 20];
+    /// let result = chi2gof(observed, expected);
+    /// assert_eq(result.h, false);
+    /// assert_approx_eq(result.stat, 5.0, 1e-10);
+    /// assert_approx_eq(result.p, 0.0820849986238988, 1e-10);
+    /// ```
+    #[rhai_fn(name = "chi2gof", return_raw)]
+    pub fn chi2gof_with_expected(
+        observed: Array,
+        expected: Array,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        chi2gof_with_options(observed, expected, Map::new())
+    }
+
+    /// Performs a chi-square goodness-of-fit test with options: the significance level `alpha`
+    /// (default 0.05) and `nparams`, the number of parameters of the hypothesized distribution
+    /// that were estimated from the data (default 0), which reduces the degrees of freedom. The
+    /// observed and expected counts must have the same total, and the confidence interval is `()`
+    /// because it does not apply.
+    /// ```typescript
+    /// let observed = [50, 30, 20];
+    /// let expected = [40, 40, 20];
+    /// let result = chi2gof(observed, expected, #{alpha: 0.1});
+    /// assert_eq(result.h, true);
+    /// ```
+    #[rhai_fn(name = "chi2gof", return_raw)]
+    pub fn chi2gof_with_options(
+        observed: Array,
+        expected: Array,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let nparams = match options.get("nparams") {
+            Some(n) => n.as_int().map_err(|_| {
+                EvalAltResult::ErrorArithmetic(
+                    "The number of estimated parameters must be an INT".to_string(),
+                    Position::NONE,
+                )
+            })?,
+            None => 0,
+        };
+        let (alpha, _) = read_options(options, vec!["alpha", "nparams"])?;
+        let observed = to_sample(observed, 2)?;
+        let expected = to_sample(expected, 2)?;
+        let (total_observed, total_expected) = (
+            observed.iter().sum::<FLOAT>(),
+            expected.iter().sum::<FLOAT>(),
+        );
+        if observed.len() != expected.len()
+            || (total_observed - total_expected).abs() > 1e-8 * total_observed.abs().max(1.0)
+            || expected
+                .iter()
+                .any(|e| e.partial_cmp(&0.0) != Some(Ordering::Greater))
+        {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The observed and expected counts must have the same length and total, and the expected counts must be positive".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let df = (observed.len() as INT - 1 - nparams) as FLOAT;
+        if df < 1.0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "There must be more categories than estimated parameters plus one".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let stat = observed
+            .iter()
+            .zip(expected.iter())
+            .map(|(o, e)| (o - e).powi(2) / e)
+            .sum::<FLOAT>();
+        let p = crate::regularized_gamma_q(df / 2.0, stat / 2.0);
+        Ok(test_result(
+            p,
+            alpha,
+            stat,
+            Dynamic::UNIT,
+            Dynamic::from_float(df),
+        ))
+    }
+
+    /// Returns the probability that the Kolmogorov distribution exceeds `lambda`, which is the
+    /// asymptotic p-value of a Kolmogorov–Smirnov statistic.
+    fn kolmogorov_p_value(lambda: FLOAT) -> FLOAT {
+        if lambda <= 0.0 {
+            return 1.0;
+        }
+        let p = if lambda < 1.18 {
+            let y = (-std::f64::consts::PI.powi(2) as FLOAT / (8.0 * lambda * lambda)).exp();
+            1.0 - (2.0 * std::f64::consts::PI as FLOAT).sqrt() / lambda
+                * (y + y.powi(9) + y.powi(25) + y.powi(49))
+        } else {
+            let y = (-2.0 * lambda * lambda).exp();
+            2.0 * (y - y.powi(4) + y.powi(9))
+        };
+        p.clamp(0.0, 1.0)
+    }
+
+    /// Performs a one-sample Kolmogorov–Smirnov test of the hypothesis that the data come from
+    /// the standard normal distribution. See `kstest(x, d, options)`.
+    /// ```typescript
+    /// let x = [-1.2, -0.6, -0.3, 0.1, 0.4, 0.8, 1.5];
+    /// let result = kstest(x);
+    /// assert_eq(result.h, false);
+    /// ```
+    #[rhai_fn(name = "kstest", return_raw)]
+    pub fn kstest(x: Array) -> Result<Map, Box<EvalAltResult>> {
+        kstest_with_options(
+            x,
+            Distribution::Normal {
+                mu: 0.0,
+                sigma: 1.0,
+            },
+            Map::new(),
+        )
+    }
+
+    /// Performs a one-sample Kolmogorov–Smirnov test of the hypothesis that the data come from
+    /// the distribution `d` (created with `dist`).
+    /// ```typescript
+    /// let x = [0.05, 0.1, 0.15, 0.2, 0.3, 0.35, 0.4, 0.5, 0.55, 0.6];
+    /// let result = kstest(x, dist("uniform"));
+    /// assert_eq(result.h, false);
+    /// assert_approx_eq(result.stat, 0.4, 1e-10);
+    /// ```
+    #[rhai_fn(name = "kstest", return_raw)]
+    pub fn kstest_with_distribution(x: Array, d: Distribution) -> Result<Map, Box<EvalAltResult>> {
+        kstest_with_options(x, d, Map::new())
+    }
+
+    /// Performs a one-sample Kolmogorov–Smirnov test with options: the significance level
+    /// `alpha` (default 0.05). The statistic is the largest distance between the empirical and
+    /// hypothesized cumulative distribution functions, and the p-value uses the asymptotic
+    /// Kolmogorov distribution with Stephens' small-sample correction. The confidence interval and
+    /// degrees of freedom are `()` because they do not apply.
+    /// ```typescript
+    /// let x = [2.1, 2.5, 2.9, 3.4, 3.8, 4.4, 5.2];
+    /// let result = kstest(x, dist("normal", #{mu: 0, sigma: 1}), #{alpha: 0.01});
+    /// assert_eq(result.h, true);
+    /// ```
+    #[rhai_fn(name = "kstest", return_raw)]
+    pub fn kstest_with_options(
+        x: Array,
+        d: Distribution,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, _) = read_options(options, vec!["alpha"])?;
+        let mut x = to_sample(x, 1)?;
+        x.sort_by(crate::compare_floats_nan_last);
+        let n = x.len() as FLOAT;
+        let stat = x
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let f = d.cdf(v);
+                ((i + 1) as FLOAT / n - f).max(f - i as FLOAT / n)
+            })
+            .fold(0.0, FLOAT::max);
+        let lambda = (n.sqrt() + 0.12 + 0.11 / n.sqrt()) * stat;
+        Ok(test_result(
+            kolmogorov_p_value(lambda),
+            alpha,
+            stat,
+            Dynamic::UNIT,
+            Dynamic::UNIT,
+        ))
+    }
+
+    /// Performs a two-sample Kolmogorov–Smirnov test of the hypothesis that two samples come from
+    /// the same continuous distribution. See `kstest2(x, y, options)`.
+    /// ```typescript
+    /// let x = [0.1, 0.4, 0.7, 1.1, 1.3, 1.8];
+    /// let y = [2.2, 2.6, 2.9, 3.4, 3.5, 4.0];
+    /// let result = kstest2(x, y);
+    /// assert_eq(result.h, true);
+    /// assert_eq(result.stat, 1.0);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { kstest2([1.0, nan], [2.0, nan]); } catch { result = "NaN"; }
+    /// assert_eq(result, "NaN");
+    /// ```
+    #[rhai_fn(name = "kstest2", return_raw)]
+    pub fn kstest2(x: Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        kstest2_with_options(x, y, Map::new())
+    }
+
+    /// Performs a two-sample Kolmogorov–Smirnov test with options: the significance level `alpha`
+    /// (default 0.05). The statistic is the largest distance between the empirical cumulative
+    /// distribution functions of the two samples.
+    /// ```typescript
+    /// let x = [0.1, 0.4, 0.7, 1.1, 1.3, 1.8];
+    /// let y = [0.2, 0.5, 0.9, 1.0, 1.4, 1.6];
+    /// let result = kstest2(x, y, #{alpha: 0.1});
+    /// assert_eq(result.h, false);
+    /// ```
+    #[rhai_fn(name = "kstest2", return_raw)]
+    pub fn kstest2_with_options(
+        x: Array,
+        y: Array,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, _) = read_options(options, vec!["alpha"])?;
+        let mut x = to_sample(x, 1)?;
+        let mut y = to_sample(y, 1)?;
+        x.sort_by(crate::compare_floats_nan_last);
+        y.sort_by(crate::compare_floats_nan_last);
+        let (nx, ny) = (x.len() as FLOAT, y.len() as FLOAT);
+        let (mut i, mut j, mut stat) = (0, 0, 0.0 as FLOAT);
+        while i < x.len() && j < y.len() {
+            let v = x[i].min(y[j]);
+            while i < x.len() && x[i] <= v {
+                i += 1;
+            }
+            while j < y.len() && y[j] <= v {
+                j += 1;
+            }
+            stat = stat.max((i as FLOAT / nx - j as FLOAT / ny).abs());
+        }
+        let ne = (nx * ny / (nx + ny)).sqrt();
+        let lambda = (ne + 0.12 + 0.11 / ne) * stat;
+        Ok(test_result(
+            kolmogorov_p_value(lambda),
+            alpha,
+            stat,
+            Dynamic::UNIT,
+            Dynamic::UNIT,
+        ))
+    }
+
+    /// Returns the sum of `t^3 - t` over the groups of `t` tied values in a sample, which
+    /// corrects the variance of rank statistics for ties.
+    fn tie_correction(mut x: Vec<FLOAT>) -> FLOAT {
+        x.sort_by(crate::compare_floats_nan_last);
+        let mut total = 0.0;
+        let mut start = 0;
+        while start < x.len() {
+            let mut end = start + 1;
+            while end < x.len() && x[end] == x[start] {
+                end += 1;
+            }
+            let t = (end - start) as FLOAT;
+            total += t * t * t - t;
+            start = end;
+        }
+        total
+    }
+
+    /// Returns the p-value of a rank statistic with the given mean and standard deviation, using
+    /// the normal approximation with a continuity correction.
+    fn rank_p_value(stat: FLOAT, mean: FLOAT, sd: FLOAT, tail: ImmutableString) -> FLOAT {
+        if sd.partial_cmp(&0.0) != Some(Ordering::Greater) {
+            return 1.0;
+        }
+        match tail.as_str() {
+            "left" => normal_cdf((stat - mean + 0.5) / sd),
+            "right" => normal_cdf(-(stat - mean - 0.5) / sd),
+            _ => {
+                let z = ((stat - mean).abs() - 0.5).max(0.0) / sd;
+                (2.0 * normal_cdf(-z)).min(1.0)
+            }
+        }
+    }
+
+    /// Performs a Wilcoxon rank sum test (equivalent to the Mann–Whitney U test) of the
+    /// hypothesis that two independent samples come from continuous distributions with equal
+    /// medians. See `ranksum(x, y, options)`.
+    /// ```typescript
+    /// let x = [1.1, 2.3, 1.8, 2.9, 1.5, 2.0];
+    /// let y = [3.2, 2.8, 3.9, 4.1, 3.5, 3.0];
+    /// let result = ranksum(x, y);
+    /// assert_eq(result.h, true);
+    /// assert_eq(result.stat, 22.0);
+    /// ```
+    #[rhai_fn(name = "ranksum", return_raw)]
+    pub fn ranksum(x: Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        ranksum_with_options(x, y, Map::new())
+    }
+
+    /// Performs a Wilcoxon rank sum test with options: the significance level `alpha` (default
+    /// 0.05) and the alternative hypothesis `tail` ("both", "left" or "right", comparing `x` to
+    /// `y`). The statistic is the sum of the ranks of `x` in the combined sample, and the p-value
+    /// uses the normal approximation with corrections for ties and continuity. The confidence
+    /// interval and degrees of freedom are `()` because they do not apply.
+    /// ```typescript
+    /// let x = [1.1, 2.3, 1.8, 2.9, 1.5, 2.0];
+    /// let y = [3.2, 2.8, 3.9, 4.1, 3.5, 3.0];
+    /// let result = ranksum(x, y, #{tail: "left"});
+    /// assert_eq(result.h, true);
+    /// assert_approx_eq(result.p, 0.004119509412862324, 1e-8);
+    /// ```
+    #[rhai_fn(name = "ranksum", return_raw)]
+    pub fn ranksum_with_options(
+        x: Array,
+        y: Array,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, tail) = read_options(options, vec!["alpha", "tail"])?;
+        let x = to_sample(x, 1)?;
+        let y = to_sample(y, 1)?;
+        let (nx, ny) = (x.len() as FLOAT, y.len() as FLOAT);
+        let combined = x.iter().chain(y.iter()).copied().collect::<Vec<FLOAT>>();
+        let ranks = crate::tied_ranks(&combined);
+        let stat = ranks[..x.len()].iter().sum::<FLOAT>();
+
+        let n = nx + ny;
+        let mean = nx * (n + 1.0) / 2.0;
+        let variance = nx * ny / 12.0 * ((n + 1.0) - tie_correction(combined) / (n * (n - 1.0)));
+        Ok(test_result(
+            rank_p_value(stat, mean, variance.sqrt(), tail),
+            alpha,
+            stat,
+            Dynamic::UNIT,
+            Dynamic::UNIT,
+        ))
+    }
+
+    /// Performs a Wilcoxon signed rank test of the hypothesis that the data come from a
+    /// continuous distribution symmetric about zero. See `signrank(x, m, options)`.
+    /// ```typescript
+    /// let x = [0.5, -0.2, 0.3, 0.1, -0.4, 0.2];
+    /// let result = signrank(x);
+    /// assert_eq(result.h, false);
+    /// ```
+    #[rhai_fn(name = "signrank", return_raw)]
+    pub fn signrank(x: Array) -> Result<Map, Box<EvalAltResult>> {
+        signrank_with_options(x, Dynamic::from_int(0), Map::new())
+    }
+
+    /// Performs a Wilcoxon signed rank test of the hypothesis that the data come from a
+    /// continuous distribution symmetric about the median `m`, or, if `m` is an array of the same
+    /// length as `x`, that the differences `x - m` are symmetric about zero.
+    /// ```typescript
+    /// let before = [125, 130, 118, 140, 135, 128, 132, 138];
+    /// let after = [120, 124, 117, 131, 130, 121, 127, 130];
+    /// let result = signrank(before, after);
+    /// assert_eq(result.h, true);
+    /// assert_eq(result.stat, 36.0);
+    /// ```
+    #[rhai_fn(name = "signrank", return_raw)]
+    pub fn signrank_with_median(x: Array, m: Dynamic) -> Result<Map, Box<EvalAltResult>> {
+        signrank_with_options(x, m, Map::new())
+    }
+
+    /// Performs a Wilcoxon signed rank test with options: the significance level `alpha` (default
+    /// 0.05) and the alternative hypothesis `tail` ("both", "left" or "right"). Differences of
+    /// zero are discarded. The statistic is the sum of the ranks of the positive differences, and
+    /// the p-value uses the normal approximation with corrections for ties and continuity. The
+    /// confidence interval and degrees of freedom are `()` because they do not apply.
+    /// ```typescript
+    /// let x = [1.7, 2.4, 2.2, 3.1, 2.7, 2.9, 3.4, 2.5];
+    /// let result = signrank(x, 2, #{tail: "right"});
+    /// assert_eq(result.h, true);
+    /// assert_approx_eq(result.p, 0.014986986793277923, 1e-8);
+    /// assert_eq(result.stat, 34.0);
+    /// ```
+    #[rhai_fn(name = "signrank", return_raw)]
+    pub fn signrank_with_options(
+        x: Array,
+        m: Dynamic,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, tail) = read_options(options, vec!["alpha", "tail"])?;
+        let x = to_sample(x, 1)?;
+        let differences = if m.is_array() {
+            let y = to_sample(m.into_array().unwrap(), 1)?;
+            if x.len() != y.len() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The samples of a paired signed rank test must have the same length"
+                        .to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            x.iter().zip(y).map(|(a, b)| a - b).collect::<Vec<FLOAT>>()
+        } else {
+            let m = crate::if_int_convert_to_float_and_do(m, Ok)?;
+            x.iter().map(|a| a - m).collect()
+        }
+        .into_iter()
+        .filter(|&d| d != 0.0)
+        .collect::<Vec<FLOAT>>();
+
+        let magnitudes = differences.iter().map(|d| d.abs()).collect::<Vec<FLOAT>>();
+        let ranks = crate::tied_ranks(&magnitudes);
+        let stat = ranks
+            .iter()
+            .zip(differences.iter())
+            .filter(|(_, &d)| d > 0.0)
+            .map(|(r, _)| r)
+            .sum::<FLOAT>();
+
+        let n = differences.len() as FLOAT;
+        let mean = n * (n + 1.0) / 4.0;
+        let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction(magnitudes) / 48.0;
+        Ok(test_result(
+            rank_p_value(stat, mean, variance.sqrt(), tail),
+            alpha,
+            stat,
+            Dynamic::UNIT,
+            Dynamic::UNIT,
+        ))
+    }
+
+    /// Performs a Shapiro–Wilk test of the hypothesis that the data come from a normal
+    /// distribution. See `shapiro(x, options)`.
+    /// ```typescript
+    /// let x = [148, 154, 158, 160, 161, 162, 166, 170, 182, 195, 236];
+    /// let result = shapiro(x);
+    /// assert_eq(result.h, true);
+    /// assert_approx_eq(result.stat, 0.7888146948294595, 1e-6);
+    /// assert_approx_eq(result.p, 0.006704063283619108, 1e-6);
+    /// ```
+    #[rhai_fn(name = "shapiro", return_raw)]
+    pub fn shapiro(x: Array) -> Result<Map, Box<EvalAltResult>> {
+        shapiro_with_options(x, Map::new())
+    }
+
+    /// Performs a Shapiro–Wilk test with options: the significance level `alpha` (default 0.05).
+    /// The statistic is W, and the coefficients and p-value use Royston's approximations, which
+    /// are valid for 3 to 5000 values. The confidence interval and degrees of freedom are `()`
+    /// because they do not apply.
+    /// ```typescript
+    /// let x = [-1.1, -0.5, -0.2, 0.0, 0.3, 0.6, 0.9, 1.4];
+    /// let result = shapiro(x, #{alpha: 0.1});
+    /// assert_eq(result.h, false);
+    /// ```
+    #[rhai_fn(name = "shapiro", return_raw)]
+    pub fn shapiro_with_options(x: Array, options: Map) -> Result<Map, Box<EvalAltResult>> {
+        let (alpha, _) = read_options(options, vec!["alpha"])?;
+        let mut x = to_sample(x, 3)?;
+        x.sort_by(crate::compare_floats_nan_last);
+        let n = x.len();
+        let nf = n as FLOAT;
+        let mean = x.iter().sum::<FLOAT>() / nf;
+        let ss = x.iter().map(|v| (v - mean).powi(2)).sum::<FLOAT>();
+        if ss.partial_cmp(&0.0) != Some(Ordering::Greater) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The sample must not have all of its values equal".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Coefficients, following Royston (1992)
+        let mut a = vec![0.0; n];
+        if n == 3 {
+            a[0] = -(0.5 as FLOAT).sqrt();
+            a[2] = (0.5 as FLOAT).sqrt();
+        } else {
+            let m = (1..=n)
+                .map(|i| crate::normal_inv((i as FLOAT - 0.375) / (nf + 0.25)))
+                .collect::<Vec<FLOAT>>();
+            let mm = m.iter().map(|v| v * v).sum::<FLOAT>();
+            let u = 1.0 / nf.sqrt();
+            let polynomial =
+                |c: [FLOAT; 6]| c.iter().fold(0.0, |acc, coefficient| acc * u + coefficient);
+            let an = polynomial([-2.706056, 4.434685, -2.071190, -0.147981, 0.221157, 0.0])
+                + m[n - 1] / mm.sqrt();
+            let (fixed, phi) = if n > 5 {
+                let an1 = polynomial([-3.582633, 5.682633, -1.752461, -0.293762, 0.042981, 0.0])
+                    + m[n - 2] / mm.sqrt();
+                a[n - 2] = an1;
+                a[1] = -an1;
+                (
+                    2,
+                    (mm - 2.0 * m[n - 1].powi(2) - 2.0 * m[n - 2].powi(2))
+                        / (1.0 - 2.0 * an.powi(2) - 2.0 * an1.powi(2)),
+                )
+            } else {
+                (1, (mm - 2.0 * m[n - 1].powi(2)) / (1.0 - 2.0 * an.powi(2)))
+            };
+            a[n - 1] = an;
+            a[0] = -an;
+            for i in fixed..n - fixed {
+                a[i] = m[i] / phi.sqrt();
+            }
+        }
+        let w = (a
+            .iter()
+            .zip(x.iter())
+            .map(|(a, v)| a * v)
+            .sum::<FLOAT>()
+            .powi(2)
+            / ss)
+            .min(1.0);
+
+        // P-value, following Royston (1995)
+        let p = if n == 3 {
+            let pi = std::f64::consts::PI as FLOAT;
+            (6.0 / pi * (w.sqrt().asin() - (0.75 as FLOAT).sqrt().asin())).max(0.0)
+        } else if n <= 11 {
+            let gamma = 0.459 * nf - 2.273;
+            let transformed = -(gamma - (1.0 - w).ln()).ln();
+            let mu = 0.544 - 0.39978 * nf + 0.025054 * nf.powi(2) - 0.0006714 * nf.powi(3);
+            let sigma =
+                (1.3822 - 0.77857 * nf + 0.062767 * nf.powi(2) - 0.0020322 * nf.powi(3)).exp();
+            1.0 - normal_cdf((transformed - mu) / sigma)
+        } else {
+            let ln_n = nf.ln();
+            let mu = -1.5861 - 0.31082 * ln_n - 0.083751 * ln_n.powi(2) + 0.0038915 * ln_n.powi(3);
+            let sigma = (-0.4803 - 0.082676 * ln_n + 0.0030302 * ln_n.powi(2)).exp();
+            1.0 - normal_cdf(((1.0 - w).ln() - mu) / sigma)
+        };
+        Ok(test_result(p, alpha, w, Dynamic::UNIT, Dynamic::UNIT))
+    }
+}
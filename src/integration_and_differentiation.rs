@@ -3,7 +3,7 @@ use rhai::plugin::*;
 #[export_module]
 pub mod int_and_diff {
     use crate::if_list_convert_to_vec_float_and_do;
-    use rhai::{Array, Dynamic, EvalAltResult, Position, FLOAT};
+    use rhai::{Array, Dynamic, EvalAltResult, Position, FLOAT, INT};
 
     /// Returns the approximate integral of the curve defined by `y` and `x` using the trapezoidal method.
     /// ```typescript
@@ -62,14 +62,38 @@ pub mod int_and_diff {
         })
     }
 
-    /// Returns the difference between successive elements of a 1-D array.
+    /// Returns the difference between successive elements of a 1-D array. A matrix or N-D array is
+    /// differenced along its first non-singleton dimension.
     /// ```typescript
     /// let arr = [2, 5, 1, 7, 8];
     /// let d = diff(arr);
     /// assert_eq(d, [3, -4, 6, 1]);
     /// ```
+    /// ```typescript
+    /// let d = diff([[1, 2],
+    ///               [4, 8]]);
+    /// assert_eq(d, [[3, 6]]);
+    /// ```
     #[rhai_fn(name = "diff", return_raw, pure)]
     pub fn diff(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        crate::if_list_do_else_map_along_dim(arr, diff_list)
+    }
+
+    /// Returns the difference between successive elements along dimension `dim` (counting from 1)
+    /// of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 2, 4],
+    ///             [3, 7, 15]];
+    /// assert_eq(diff(data, 1), [[2, 5, 11]]);
+    /// assert_eq(diff(data, 2), [[1, 2], [4, 8]]);
+    /// ```
+    #[rhai_fn(name = "diff", return_raw, pure)]
+    pub fn diff_along_dim(arr: &mut Array, dim: INT) -> Result<Array, Box<EvalAltResult>> {
+        crate::if_numeric_array_do_along_dim(arr, Some(dim), diff_list)
+    }
+
+    /// Returns the difference between successive elements of a list.
+    fn diff_list(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
         crate::if_list_do_int_or_do_float(
             arr,
             |arr| {
@@ -10,6 +10,8 @@ pub use patterns::*;
 use rhai::{def_package, packages::Package, plugin::*, Engine, EvalAltResult};
 mod matrices_and_arrays;
 pub use matrices_and_arrays::matrix_functions;
+#[cfg(feature = "nalgebra")]
+pub use matrices_and_arrays::Matrix;
 mod statistics;
 pub use statistics::stats;
 mod misc;
@@ -30,6 +32,25 @@ mod validate;
 pub use validate::validation_functions;
 mod trig;
 pub use trig::trig_functions;
+mod arithmetic;
+pub use arithmetic::arithmetic_functions;
+mod logical;
+pub use logical::logical_functions;
+mod histograms;
+pub use histograms::histogram_functions;
+mod distributions;
+pub use distributions::{distribution_functions, Distribution};
+mod hypothesis;
+pub use hypothesis::hypothesis_functions;
+mod polynomials;
+pub use polynomials::polynomial_functions;
+mod optimization;
+pub use optimization::optimization_functions;
+mod complex;
+pub use complex::{complex_functions, Complex};
+mod sparse;
+#[cfg(feature = "nalgebra")]
+pub use sparse::{sparse_functions, SparseMatrix};
 
 def_package! {
     /// Package for scientific computing
@@ -46,6 +67,16 @@ def_package! {
         combine_with_exported_module!(lib, "rhai_sci_moving", moving_functions);
         combine_with_exported_module!(lib, "rhai_sci_validation", validation_functions);
         combine_with_exported_module!(lib, "rhai_sci_trig", trig_functions);
+        combine_with_exported_module!(lib, "rhai_sci_arithmetic", arithmetic_functions);
+        combine_with_exported_module!(lib, "rhai_sci_logical", logical_functions);
+        combine_with_exported_module!(lib, "rhai_sci_histograms", histogram_functions);
+        combine_with_exported_module!(lib, "rhai_sci_distributions", distribution_functions);
+        combine_with_exported_module!(lib, "rhai_sci_hypothesis", hypothesis_functions);
+        combine_with_exported_module!(lib, "rhai_sci_polynomials", polynomial_functions);
+        combine_with_exported_module!(lib, "rhai_sci_optimization", optimization_functions);
+        combine_with_exported_module!(lib, "rhai_sci_complex", complex_functions);
+        #[cfg(feature = "nalgebra")]
+        combine_with_exported_module!(lib, "rhai_sci_sparse", sparse_functions);
     }
 }
 
@@ -0,0 +1,521 @@
+use rhai::plugin::*;
+
+#[export_module]
+pub mod logical_functions {
+    use crate::{if_broadcastable_do_elementwise, numeric_shape};
+    use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, Position, FLOAT, INT};
+    use std::cmp::Ordering;
+
+    /// Compares two numeric arrays (or an array and a scalar) element-by-element, broadcasting the
+    /// inputs against each other. Each element of the result is the outcome of `test` applied to
+    /// the ordering of the pair of elements, which is `None` if either of them is NaN.
+    fn compare(
+        x: Dynamic,
+        y: Dynamic,
+        test: fn(Option<Ordering>) -> bool,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_broadcastable_do_elementwise(x, y, |a, b| {
+            let ordering = if a.is_int() && b.is_int() {
+                Some(a.as_int().unwrap().cmp(&b.as_int().unwrap()))
+            } else {
+                let as_float = |d: &Dynamic| {
+                    if d.is_float() {
+                        d.as_float().unwrap()
+                    } else {
+                        d.as_int().unwrap() as FLOAT
+                    }
+                };
+                as_float(a).partial_cmp(&as_float(b))
+            };
+            Ok(Dynamic::from_bool(test(ordering)))
+        })
+    }
+
+    /// Converts the BOOL elements of a mask to INT (1 for `true` and 0 for `false`), leaving any
+    /// other elements unchanged.
+    fn mask_to_numeric(mask: Dynamic) -> Dynamic {
+        if mask.is_bool() {
+            Dynamic::from_int(mask.as_bool().unwrap() as INT)
+        } else if mask.is_array() {
+            Dynamic::from_array(
+                mask.into_array()
+                    .unwrap()
+                    .into_iter()
+                    .map(mask_to_numeric)
+                    .collect(),
+            )
+        } else {
+            mask
+        }
+    }
+
+    /// Returns the shape of a mask, together with whether each of its elements is set, in
+    /// row-major order. BOOL elements are set if `true`, and INT or FLOAT elements if nonzero.
+    fn mask_shape_and_flags(mask: Dynamic) -> Result<(Vec<usize>, Vec<bool>), Box<EvalAltResult>> {
+        let mask = mask_to_numeric(mask);
+        let shape = numeric_shape(&mask).ok_or_else(|| {
+            EvalAltResult::ErrorArithmetic(
+                "The mask must be an array with consistent dimensions containing only BOOL, INT or FLOAT".to_string(),
+                Position::NONE,
+            )
+        })?;
+        let elements = if mask.is_array() {
+            crate::matrix_functions::flatten(&mut mask.into_array().unwrap())
+        } else {
+            vec![mask]
+        };
+        let flags = elements
+            .iter()
+            .map(|el| {
+                el.as_int()
+                    .map_or_else(|_| el.as_float().unwrap() != 0.0, |v| v != 0)
+            })
+            .collect();
+        Ok((shape, flags))
+    }
+
+    /// Reduces a mask with `test` along a dimension, returning BOOL values.
+    fn reduce_mask(
+        arr: Array,
+        dim: Option<INT>,
+        test: fn(Vec<bool>) -> bool,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut numeric = mask_to_numeric(Dynamic::from_array(arr))
+            .into_array()
+            .unwrap();
+        let f = |x: &mut Array| {
+            let flags = if x.is_empty() {
+                vec![]
+            } else {
+                mask_shape_and_flags(Dynamic::from_array(x.clone()))?.1
+            };
+            Ok(Dynamic::from_bool(test(flags)))
+        };
+        match dim {
+            Some(_) => crate::if_numeric_array_reduce_along_dim(&mut numeric, dim, f),
+            None if numeric.is_empty() => f(&mut numeric),
+            None => crate::if_list_do_else_reduce_along_dim(&mut numeric, f),
+        }
+    }
+
+    /// Converts an array of INT subscripts (one per dimension) into a linear index, checking that
+    /// each subscript is within the given shape.
+    fn linear_index(shape: Vec<usize>, subscripts: Dynamic) -> Result<INT, Box<EvalAltResult>> {
+        let subscripts = subscripts.into_array().map_err(|_| {
+            EvalAltResult::ErrorArithmetic(
+                "The subscripts must be an array of INT".to_string(),
+                Position::NONE,
+            )
+        })?;
+        if subscripts.len() != shape.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Expected {} subscripts, but found {}",
+                    shape.len(),
+                    subscripts.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        let mut idx: usize = 0;
+        for (s, &n) in subscripts.iter().zip(&shape) {
+            match s.as_int() {
+                Ok(s) if s >= 0 && (s as usize) < n => {
+                    idx = idx
+                        .checked_mul(n)
+                        .and_then(|idx| idx.checked_add(s as usize))
+                        .ok_or_else(|| too_many_elements(shape.clone()))?
+                }
+                _ => {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        format!(
+                        "The subscripts {subscripts:?} are out of range for the shape {shape:?}"
+                    ),
+                        Position::NONE,
+                    )
+                    .into())
+                }
+            }
+        }
+        INT::try_from(idx).map_err(|_| too_many_elements(shape.clone()))
+    }
+
+    /// Converts a linear index into an array of INT subscripts (one per dimension), checking that
+    /// the index is within the given shape.
+    fn subscripts(shape: Vec<usize>, idx: INT) -> Result<Array, Box<EvalAltResult>> {
+        let len = shape
+            .iter()
+            .try_fold(1_usize, |len, &n| len.checked_mul(n))
+            .ok_or_else(|| too_many_elements(shape.clone()))?;
+        if idx < 0 || idx as usize >= len {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("The index {idx} is out of range for the shape {shape:?}"),
+                Position::NONE,
+            )
+            .into());
+        }
+        let mut idx = idx as usize;
+        let mut subs = vec![Dynamic::UNIT; shape.len()];
+        for d in (0..shape.len()).rev() {
+            subs[d] = Dynamic::from_int((idx % shape[d]) as INT);
+            idx /= shape[d];
+        }
+        Ok(subs)
+    }
+
+    /// The error for a shape with more elements than can be indexed.
+    fn too_many_elements(shape: Vec<usize>) -> Box<EvalAltResult> {
+        EvalAltResult::ErrorArithmetic(
+            format!("The shape {shape:?} has too many elements to index"),
+            Position::NONE,
+        )
+        .into()
+    }
+
+    /// Converts an array of dimension lengths into a shape.
+    fn to_shape(shape: Array) -> Result<Vec<usize>, Box<EvalAltResult>> {
+        shape
+            .iter()
+            .map(|n| match n.as_int() {
+                Ok(n) if n >= 0 => Ok(n as usize),
+                _ => Err(EvalAltResult::ErrorArithmetic(
+                    "The shape must only contain nonnegative INT values".to_string(),
+                    Position::NONE,
+                )
+                .into()),
+            })
+            .collect()
+    }
+
+    /// Tests whether the elements of one numeric array (or scalar) are greater than those of
+    /// another, broadcasting the inputs against each other. Returns a mask of BOOL values.
+    /// ```typescript
+    /// let mask = gt([1, 5, 3, 8], 3);
+    /// assert_eq(mask, [false, true, false, true]);
+    /// ```
+    /// ```typescript
+    /// let mask = gt([[1, 2], [3, 4]], [2, 1]);
+    /// assert_eq(mask, [[false, true], [true, true]]);
+    /// ```
+    #[rhai_fn(name = "gt", return_raw)]
+    pub fn gt(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        compare(x, y, |o| o == Some(Ordering::Greater))
+    }
+
+    /// Tests whether the elements of one numeric array (or scalar) are greater than or equal to
+    /// those of another, broadcasting the inputs against each other.
+    /// ```typescript
+    /// let mask = ge([1, 5, 3, 8], 3);
+    /// assert_eq(mask, [false, true, true, true]);
+    /// ```
+    #[rhai_fn(name = "ge", return_raw)]
+    pub fn ge(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        compare(x, y, |o| {
+            matches!(o, Some(Ordering::Greater | Ordering::Equal))
+        })
+    }
+
+    /// Tests whether the elements of one numeric array (or scalar) are less than those of
+    /// another, broadcasting the inputs against each other.
+    /// ```typescript
+    /// let mask = lt([1, 5, 3, 8], 3);
+    /// assert_eq(mask, [true, false, false, false]);
+    /// ```
+    #[rhai_fn(name = "lt", return_raw)]
+    pub fn lt(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        compare(x, y, |o| o == Some(Ordering::Less))
+    }
+
+    /// Tests whether the elements of one numeric array (or scalar) are less than or equal to those
+    /// of another, broadcasting the inputs against each other.
+    /// ```typescript
+    /// let mask = le([1, 5, 3, 8], 3);
+    /// assert_eq(mask, [true, false, true, false]);
+    /// ```
+    #[rhai_fn(name = "le", return_raw)]
+    pub fn le(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        compare(x, y, |o| {
+            matches!(o, Some(Ordering::Less | Ordering::Equal))
+        })
+    }
+
+    /// Tests whether the elements of two numeric arrays (or an array and a scalar) are equal,
+    /// broadcasting the inputs against each other. INT and FLOAT elements with the same value are
+    /// equal.
+    /// ```typescript
+    /// let mask = eq([1, 2.0, 3], [1.0, 2, 4]);
+    /// assert_eq(mask, [true, true, false]);
+    /// ```
+    #[rhai_fn(name = "eq", return_raw)]
+    pub fn eq(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        compare(x, y, |o| o == Some(Ordering::Equal))
+    }
+
+    /// Tests whether the elements of two numeric arrays (or an array and a scalar) are not equal,
+    /// broadcasting the inputs against each other. NaN is not equal to anything.
+    /// ```typescript
+    /// let mask = ne([1, 2, 3], 2);
+    /// assert_eq(mask, [true, false, true]);
+    /// ```
+    #[rhai_fn(name = "ne", return_raw)]
+    pub fn ne(x: Dynamic, y: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        compare(x, y, |o| o != Some(Ordering::Equal))
+    }
+
+    /// Returns the linear indices (in row-major order, starting at 0) of the elements of a mask
+    /// that are set. BOOL elements are set if `true`, and INT or FLOAT elements if nonzero.
+    /// ```typescript
+    /// let idx = find([false, true, true, false]);
+    /// assert_eq(idx, [1, 2]);
+    /// ```
+    /// ```typescript
+    /// let idx = find(gt([[1, 5], [7, 2]], 4));
+    /// assert_eq(idx, [1, 2]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(find([]), []);
+    /// ```
+    #[rhai_fn(name = "find", return_raw)]
+    pub fn find(mask: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        let (_, flags) = mask_shape_and_flags(mask)?;
+        Ok(flags
+            .iter()
+            .enumerate()
+            .filter(|(_, &set)| set)
+            .map(|(i, _)| Dynamic::from_int(i as INT))
+            .collect())
+    }
+
+    /// Returns the indices of the elements of a mask that are set, either as `"linear"` indices or
+    /// as `"subscripts"`. Subscripts are given as an array with one index per dimension (such as
+    /// `[row, column]` for a matrix) for each element found.
+    /// ```typescript
+    /// let mask = gt([[1, 5], [7, 2]], 4);
+    /// assert_eq(find(mask, "linear"), [1, 2]);
+    /// assert_eq(find(mask, "subscripts"), [[0, 1], [1, 0]]);
+    /// ```
+    #[rhai_fn(name = "find", return_raw)]
+    pub fn find_with_output(
+        mask: Dynamic,
+        output: ImmutableString,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        match output.as_str() {
+            "linear" => find(mask),
+            "subscripts" => {
+                let shape = mask_shape_and_flags(mask.clone())?.0;
+                find(mask)?
+                    .into_iter()
+                    .map(|idx| {
+                        subscripts(shape.clone(), idx.as_int().unwrap()).map(Dynamic::from_array)
+                    })
+                    .collect()
+            }
+            _ => Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown output type '{output}', expected 'linear' or 'subscripts'"),
+                Position::NONE,
+            )
+            .into()),
+        }
+    }
+
+    /// Returns the elements of an array where a mask of the same shape is set, as a 1-D array in
+    /// row-major order.
+    /// ```typescript
+    /// let data = [1, 5, 3, 8];
+    /// let big = select(data, gt(data, 2));
+    /// assert_eq(big, [5, 3, 8]);
+    /// ```
+    /// ```typescript
+    /// let data = [[1, 5], [7, 2]];
+    /// assert_eq(select(data, [[true, false], [true, false]]), [1, 7]);
+    /// ```
+    #[rhai_fn(name = "select", return_raw, pure)]
+    pub fn select(arr: &mut Array, mask: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        let (shape, flags) = mask_shape_and_flags(mask)?;
+        if numeric_shape(&Dynamic::from_array(arr.clone())) != Some(shape) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The mask must have the same shape as the array".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        Ok(crate::matrix_functions::flatten(arr)
+            .into_iter()
+            .zip(flags)
+            .filter(|(_, set)| *set)
+            .map(|(el, _)| el)
+            .collect())
+    }
+
+    /// Returns a copy of an array in which the elements where a mask of the same shape is set are
+    /// replaced. The replacement is either a single value, or an array with one value for each
+    /// element that is set (in row-major order).
+    /// ```typescript
+    /// let data = [1, -5, 3, -8];
+    /// let clipped = assign(data, lt(data, 0), 0);
+    /// assert_eq(clipped, [1, 0, 3, 0]);
+    /// ```
+    /// ```typescript
+    /// let data = [[1, 5], [7, 2]];
+    /// let x = assign(data, gt(data, 4), [50, 70]);
+    /// assert_eq(x, [[1, 50], [70, 2]]);
+    /// ```
+    #[rhai_fn(name = "assign", return_raw, pure)]
+    pub fn assign(
+        arr: &mut Array,
+        mask: Dynamic,
+        value: Dynamic,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let (shape, flags) = mask_shape_and_flags(mask)?;
+        if numeric_shape(&Dynamic::from_array(arr.clone())).as_ref() != Some(&shape) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The mask must have the same shape as the array".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let n_set = flags.iter().filter(|&&set| set).count();
+        let values = if value.is_array() {
+            let values = value.into_array().unwrap();
+            if values.len() != n_set {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!(
+                        "Expected {n_set} values to assign, but found {}",
+                        values.len()
+                    ),
+                    Position::NONE,
+                )
+                .into());
+            }
+            values
+        } else {
+            vec![value; n_set]
+        };
+
+        let mut values = values.into_iter();
+        let flat = crate::matrix_functions::flatten(arr)
+            .into_iter()
+            .zip(flags)
+            .map(|(el, set)| if set { values.next().unwrap() } else { el })
+            .collect::<Array>();
+        Ok(crate::flat_to_nested_array(&flat, &shape))
+    }
+
+    /// Tests whether any element of a mask is set. For a matrix or N-D array, the test is done
+    /// along the first non-singleton dimension.
+    /// ```typescript
+    /// assert_eq(any([false, false, true]), true);
+    /// assert_eq(any([0, 0.0]), false);
+    /// ```
+    /// ```typescript
+    /// let mask = [[true, false],
+    ///             [false, false]];
+    /// assert_eq(any(mask), [[true, false]]);
+    /// ```
+    #[rhai_fn(name = "any", return_raw, pure)]
+    pub fn any(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        reduce_mask(arr.clone(), None, |flags| flags.iter().any(|&set| set))
+    }
+
+    /// Tests whether any element of a mask is set along dimension `dim` (counting from 1).
+    /// ```typescript
+    /// let mask = [[true, false],
+    ///             [false, false]];
+    /// assert_eq(any(mask, 2), [[true], [false]]);
+    /// ```
+    #[rhai_fn(name = "any", return_raw, pure)]
+    pub fn any_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        reduce_mask(arr.clone(), Some(dim), |flags| flags.iter().any(|&set| set))
+    }
+
+    /// Tests whether every element of a mask is set. For a matrix or N-D array, the test is done
+    /// along the first non-singleton dimension.
+    /// ```typescript
+    /// assert_eq(all([true, true, true]), true);
+    /// assert_eq(all(gt([1, 2, 3], 1)), false);
+    /// ```
+    /// ```typescript
+    /// let mask = [[true, false],
+    ///             [true, true]];
+    /// assert_eq(all(mask), [[true, false]]);
+    /// ```
+    #[rhai_fn(name = "all", return_raw, pure)]
+    pub fn all(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        reduce_mask(arr.clone(), None, |flags| flags.iter().all(|&set| set))
+    }
+
+    /// Tests whether every element of a mask is set along dimension `dim` (counting from 1).
+    /// ```typescript
+    /// let mask = [[true, false],
+    ///             [true, true]];
+    /// assert_eq(all(mask, 2), [[false], [true]]);
+    /// ```
+    #[rhai_fn(name = "all", return_raw, pure)]
+    pub fn all_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        reduce_mask(arr.clone(), Some(dim), |flags| flags.iter().all(|&set| set))
+    }
+
+    /// Converts subscripts into a linear index (in row-major order, starting at 0) for an array
+    /// with the given shape. The subscripts are either a single array with one index per
+    /// dimension, or an array of such arrays.
+    /// ```typescript
+    /// let idx = sub2ind([3, 4], [1, 2]);
+    /// assert_eq(idx, 6);
+    /// ```
+    /// ```typescript
+    /// let idx = sub2ind([2, 3, 4], [[0, 0, 1], [1, 2, 3]]);
+    /// assert_eq(idx, [1, 23]);
+    /// ```
+    /// ```typescript
+    /// let n = 4294967296;
+    /// let result = "ok";
+    /// try { sub2ind([n, n, n], [1, 1, 1]); } catch { result = "too many elements"; }
+    /// assert_eq(result, "too many elements");
+    /// ```
+    #[rhai_fn(name = "sub2ind", return_raw)]
+    pub fn sub2ind(shape: Array, subs: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        let shape = to_shape(shape)?;
+        if subs.iter().all(|s| s.is_array()) && !subs.is_empty() {
+            subs.into_iter()
+                .map(|s| linear_index(shape.clone(), s).map(Dynamic::from_int))
+                .collect::<Result<Array, Box<EvalAltResult>>>()
+                .map(Dynamic::from_array)
+        } else {
+            linear_index(shape, Dynamic::from_array(subs)).map(Dynamic::from_int)
+        }
+    }
+
+    /// Converts a linear index (in row-major order, starting at 0) into an array of subscripts,
+    /// one per dimension, for an array with the given shape.
+    /// ```typescript
+    /// let subs = ind2sub([3, 4], 6);
+    /// assert_eq(subs, [1, 2]);
+    /// ```
+    #[rhai_fn(name = "ind2sub", return_raw)]
+    pub fn ind2sub(shape: Array, idx: INT) -> Result<Array, Box<EvalAltResult>> {
+        subscripts(to_shape(shape)?, idx)
+    }
+
+    /// Converts an array of linear indices (in row-major order, starting at 0) into an array of
+    /// subscripts for an array with the given shape.
+    /// ```typescript
+    /// let subs = ind2sub([2, 3, 4], [1, 23]);
+    /// assert_eq(subs, [[0, 0, 1], [1, 2, 3]]);
+    /// ```
+    #[rhai_fn(name = "ind2sub", return_raw)]
+    pub fn ind2sub_array(shape: Array, idx: Array) -> Result<Array, Box<EvalAltResult>> {
+        let shape = to_shape(shape)?;
+        idx.iter()
+            .map(|i| match i.as_int() {
+                Ok(i) => subscripts(shape.clone(), i).map(Dynamic::from_array),
+                Err(_) => Err(EvalAltResult::ErrorArithmetic(
+                    "The indices must be INT".to_string(),
+                    Position::NONE,
+                )
+                .into()),
+            })
+            .collect()
+    }
+}
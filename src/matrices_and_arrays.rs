@@ -1,22 +1,138 @@
 use nalgebralib::{Dyn, OMatrix};
 use rhai::plugin::*;
 
+/// A dense, two-dimensional matrix of FLOAT values. The values are stored directly as an
+/// `nalgebra` matrix, so chained matrix operations never convert to and from nested arrays.
+#[cfg(feature = "nalgebra")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix(pub nalgebralib::DMatrix<rhai::FLOAT>);
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebralib::DMatrix<rhai::FLOAT>> for Matrix {
+    fn from(dm: nalgebralib::DMatrix<rhai::FLOAT>) -> Self {
+        Matrix(dm)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl std::fmt::Display for Matrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, row) in self.0.row_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[")?;
+            for (j, el) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{el:?}")?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "]")
+    }
+}
+
 #[export_module]
 pub mod matrix_functions {
     use crate::{
-        array_to_vec_float, if_int_convert_to_float_and_do, if_int_do_else_if_array_do, if_list_do,
-        if_matrix_convert_to_vec_array_and_do,
+        array_to_vec_float, flat_to_nested_array, if_int_convert_to_float_and_do,
+        if_int_do_else_if_array_do, if_list_do, if_matrix_convert_to_vec_array_and_do,
+        if_numeric_array_get_shape,
     };
     #[cfg(feature = "nalgebra")]
     use crate::{
-        if_matrices_and_compatible_convert_to_vec_array_and_do, if_matrix_do,
-        omatrix_to_vec_dynamic, ovector_to_vec_dynamic, FOIL,
+        if_matrices_and_compatible_convert_to_dmatrix_and_do,
+        if_matrices_and_compatible_convert_to_vec_array_and_do,
+        if_matrix_convert_to_dmatrix_and_do, if_matrix_do, omatrix_to_vec_dynamic,
+        ovector_to_vec_dynamic, FOIL,
     };
     #[cfg(feature = "nalgebra")]
     use nalgebralib::DMatrix;
     use rhai::{Array, Dynamic, EvalAltResult, Map, Position, FLOAT, INT};
     use std::collections::BTreeMap;
 
+    /// A dense, two-dimensional matrix of FLOAT values. Create one from a nested array with
+    /// `to_matrix` and turn it back into a nested array with `to_array`.
+    #[cfg(feature = "nalgebra")]
+    pub type Matrix = super::Matrix;
+
+    /// Wraps a matrix in a `Dynamic` as a nested array.
+    #[cfg(feature = "nalgebra")]
+    fn to_dynamic_array(dm: DMatrix<FLOAT>) -> Dynamic {
+        Dynamic::from_array(omatrix_to_vec_dynamic(dm))
+    }
+
+    /// Wraps a matrix in a `Dynamic` as a `Matrix`.
+    #[cfg(feature = "nalgebra")]
+    fn to_dynamic_matrix(dm: DMatrix<FLOAT>) -> Dynamic {
+        Dynamic::from(Matrix::from(dm))
+    }
+
+    /// Converts a nested array into a `Matrix`. Fails if the input is not a matrix, or if the
+    /// elements of the matrix aren't FLOAT or INT.
+    /// ```typescript
+    /// let m = to_matrix([[1, 2],
+    ///                    [3, 4]]);
+    /// assert_eq(size(m), [2, 2]);
+    /// ```
+    /// ```typescript
+    /// let m = to_matrix([[1.0, 2],
+    ///                    [3,   4.5]]);
+    /// assert_eq(to_array(m), [[1.0, 2.0],
+    ///                         [3.0, 4.5]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "to_matrix", return_raw, pure)]
+    pub fn to_matrix(matrix: &mut Array) -> Result<Matrix, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| Ok(Matrix::from(dm)))
+    }
+
+    /// Converts a `Matrix` into a nested array of FLOAT values.
+    /// ```typescript
+    /// let m = to_matrix(eye(2));
+    /// assert_eq(to_array(m), [[1.0, 0.0],
+    ///                         [0.0, 1.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "to_array", pure)]
+    pub fn to_array(matrix: &mut Matrix) -> Array {
+        omatrix_to_vec_dynamic(matrix.0.clone())
+    }
+
+    /// Returns a string representation of a `Matrix`.
+    /// ```typescript
+    /// let m = to_matrix([[1, 2]]);
+    /// assert_eq(to_string(m), "[[1.0, 2.0]]");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "to_string", pure)]
+    pub fn matrix_to_string(matrix: &mut Matrix) -> String {
+        matrix.to_string()
+    }
+
+    /// Returns a debug representation of a `Matrix`.
+    /// ```typescript
+    /// let m = to_matrix([[1, 2]]);
+    /// assert_eq(to_debug(m), "[[1.0, 2.0]]");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "to_debug", pure)]
+    pub fn matrix_to_debug(matrix: &mut Matrix) -> String {
+        matrix.to_string()
+    }
+
+    /// Inverts a matrix, failing if it is singular.
+    #[cfg(feature = "nalgebra")]
+    fn try_invert(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        dm.try_inverse().ok_or_else(|| {
+            EvalAltResult::ErrorArithmetic("Matrix cannot be inverted".to_string(), Position::NONE)
+                .into()
+        })
+    }
+
     /// Calculates the inverse of a matrix. Fails if the matrix if not invertible, or if the
     /// elements of the matrix aren't FLOAT or INT.
     /// ```typescript
@@ -40,31 +156,1332 @@ pub mod matrix_functions {
     #[cfg(feature = "nalgebra")]
     #[rhai_fn(name = "inv", return_raw, pure)]
     pub fn invert_matrix(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
-        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
-            let dm = DMatrix::from_fn(matrix_as_vec.len(), matrix_as_vec[0].len(), |i, j| {
-                if matrix_as_vec[0][0].is_float() {
-                    matrix_as_vec[i][j].as_float().unwrap()
-                } else {
-                    matrix_as_vec[i][j].as_int().unwrap() as FLOAT
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| try_invert(dm).map(omatrix_to_vec_dynamic))
+    }
+
+    /// Calculates the inverse of a `Matrix`. Fails if the matrix if not invertible.
+    /// ```typescript
+    /// let x = to_matrix([[1, 2],
+    ///                    [3, 4]]);
+    /// let x_inverted = inv(x);
+    /// assert_eq(to_array(x_inverted), [[-2.0, 1.0],
+    ///                                  [1.5, -0.5]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "inv", return_raw, pure)]
+    pub fn invert_matrix_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        try_invert(matrix.0.clone()).map(Matrix::from)
+    }
+
+    /// Computes the matrix exponential with a degree 13 Padé approximant and scaling and
+    /// squaring (Higham, 2005).
+    #[cfg(feature = "nalgebra")]
+    fn matrix_exponential(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if_square_do(finite_or_error(dm)?, |dm| {
+            const B: [FLOAT; 14] = [
+                64764752532480000.0,
+                32382376266240000.0,
+                7771770303897600.0,
+                1187353796428800.0,
+                129060195264000.0,
+                10559470521600.0,
+                670442572800.0,
+                33522128640.0,
+                1323241920.0,
+                40840800.0,
+                960960.0,
+                16380.0,
+                182.0,
+                1.0,
+            ];
+            const THETA_13: FLOAT = 5.371920351148152;
+
+            let n = dm.nrows();
+            let norm_1 = dm
+                .column_iter()
+                .map(|c| c.iter().map(|x| x.abs()).sum::<FLOAT>())
+                .fold(0.0, FLOAT::max);
+            if !norm_1.is_finite() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The matrix is too large for its exponential to be computed".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            let s = if norm_1 > THETA_13 {
+                (norm_1 / THETA_13).log2().ceil() as i32
+            } else {
+                0
+            };
+
+            let a = dm / (2.0 as FLOAT).powi(s);
+            let eye = DMatrix::<FLOAT>::identity(n, n);
+            let a2 = &a * &a;
+            let a4 = &a2 * &a2;
+            let a6 = &a4 * &a2;
+            let u = &a
+                * (&a6 * (&a6 * B[13] + &a4 * B[11] + &a2 * B[9])
+                    + &a6 * B[7]
+                    + &a4 * B[5]
+                    + &a2 * B[3]
+                    + &eye * B[1]);
+            let v = &a6 * (&a6 * B[12] + &a4 * B[10] + &a2 * B[8])
+                + &a6 * B[6]
+                + &a4 * B[4]
+                + &a2 * B[2]
+                + &eye * B[0];
+
+            let mut result = match (&v - &u).lu().solve(&(&v + &u)) {
+                Some(r) => r,
+                None => {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The matrix exponential could not be computed".to_string(),
+                        Position::NONE,
+                    )
+                    .into())
                 }
-            });
+            };
+            for _ in 0..s {
+                result = &result * &result;
+            }
+            Ok(result)
+        })
+    }
 
-            // Try to invert
-            let dm = dm.try_inverse();
+    /// Computes the principal square root of a matrix. Symmetric positive semi-definite matrices
+    /// use an eigendecomposition, and all others use the scaled Denman-Beavers iteration.
+    #[cfg(feature = "nalgebra")]
+    fn matrix_sqrt(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if_square_do(dm, |dm| {
+            let n = dm.nrows();
+            if dm == dm.transpose() {
+                let se = nalgebralib::linalg::SymmetricEigen::new(dm.clone());
+                let tol = (n as FLOAT) * FLOAT::EPSILON * se.eigenvalues.amax();
+                if se.eigenvalues.iter().all(|&l| l >= -tol) {
+                    let roots = se.eigenvalues.map(|l| l.max(0.0).sqrt());
+                    return Ok(&se.eigenvectors
+                        * DMatrix::from_diagonal(&roots)
+                        * se.eigenvectors.transpose());
+                }
+            }
 
-            dm.map(omatrix_to_vec_dynamic).ok_or_else(|| {
+            let not_converged = || -> Box<EvalAltResult> {
                 EvalAltResult::ErrorArithmetic(
-                    "Matrix cannot be inverted".to_string(),
+                    "The matrix square root iteration failed to converge".to_string(),
                     Position::NONE,
                 )
                 .into()
+            };
+            let mut y = dm;
+            let mut z = DMatrix::<FLOAT>::identity(n, n);
+            for _ in 0..100 {
+                let y_inv = y.clone().try_inverse().ok_or_else(not_converged)?;
+                let z_inv = z.clone().try_inverse().ok_or_else(not_converged)?;
+                let gamma = (y.determinant() * z.determinant())
+                    .abs()
+                    .powf(-1.0 / (2.0 * n as FLOAT));
+                if !gamma.is_finite() {
+                    return Err(not_converged());
+                }
+                let y_next = (&y * gamma + z_inv / gamma) / 2.0;
+                z = (&z * gamma + y_inv / gamma) / 2.0;
+                let change = (&y_next - &y).norm();
+                y = y_next;
+                if change <= 10.0 * (n as FLOAT) * FLOAT::EPSILON * y.norm() {
+                    return Ok(y);
+                }
+            }
+            Err(not_converged())
+        })
+    }
+
+    /// Computes the principal logarithm of a matrix. Symmetric positive-definite matrices use an
+    /// eigendecomposition. All others use inverse scaling and squaring, taking square roots until
+    /// the matrix is close to the identity and then applying a Padé approximant of `log(I + X)`
+    /// evaluated with 8-point Gauss-Legendre quadrature.
+    #[cfg(feature = "nalgebra")]
+    fn matrix_log(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if_square_do(dm, |dm| {
+            let n = dm.nrows();
+            if dm == dm.transpose() {
+                let se = nalgebralib::linalg::SymmetricEigen::new(dm.clone());
+                if se.eigenvalues.iter().all(|&l| l > 0.0) {
+                    let logs = se.eigenvalues.map(|l| l.ln());
+                    return Ok(&se.eigenvectors
+                        * DMatrix::from_diagonal(&logs)
+                        * se.eigenvectors.transpose());
+                }
+            }
+
+            let eye = DMatrix::<FLOAT>::identity(n, n);
+            let mut a = dm;
+            let mut k = 0;
+            while (&a - &eye)
+                .column_iter()
+                .map(|c| c.iter().map(|x| x.abs()).sum::<FLOAT>())
+                .fold(0.0, FLOAT::max)
+                > 0.25
+            {
+                if k == 64 {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The matrix logarithm failed to converge".to_string(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                a = matrix_sqrt(a)?;
+                k += 1;
+            }
+
+            const NODES: [FLOAT; 8] = [
+                -0.9602898564975363,
+                -0.7966664774136267,
+                -0.525532409916329,
+                -0.1834346424956498,
+                0.1834346424956498,
+                0.525532409916329,
+                0.7966664774136267,
+                0.9602898564975363,
+            ];
+            const WEIGHTS: [FLOAT; 8] = [
+                0.1012285362903763,
+                0.2223810344533745,
+                0.3137066458778873,
+                0.362683783378362,
+                0.362683783378362,
+                0.3137066458778873,
+                0.2223810344533745,
+                0.1012285362903763,
+            ];
+            let x = a - &eye;
+            let mut result = DMatrix::<FLOAT>::zeros(n, n);
+            for (node, weight) in NODES.iter().zip(WEIGHTS.iter()) {
+                let t = (node + 1.0) / 2.0;
+                let term = (&eye + &x * t).lu().solve(&x).ok_or_else(|| {
+                    Box::new(EvalAltResult::ErrorArithmetic(
+                        "The matrix logarithm failed to converge".to_string(),
+                        Position::NONE,
+                    ))
+                })?;
+                result += term * (weight / 2.0);
+            }
+            Ok(result * (2.0 as FLOAT).powi(k))
+        })
+    }
+
+    /// Raises a square matrix to an integer power by repeated squaring. Negative powers use the
+    /// inverse of the matrix.
+    #[cfg(feature = "nalgebra")]
+    fn matrix_power_int(dm: DMatrix<FLOAT>, k: INT) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if_square_do(dm, |dm| {
+            let n = dm.nrows();
+            let mut base = if k < 0 { try_invert(dm)? } else { dm };
+            let mut exponent = k.unsigned_abs();
+            let mut result = DMatrix::<FLOAT>::identity(n, n);
+            while exponent > 0 {
+                if exponent % 2 == 1 {
+                    result = &result * &base;
+                }
+                exponent /= 2;
+                if exponent > 0 {
+                    base = &base * &base;
+                }
+            }
+            Ok(result)
+        })
+    }
+
+    /// Raises a square matrix to a real power as `expm(k * logm(m))`, using repeated squaring
+    /// when the power is a whole number.
+    #[cfg(feature = "nalgebra")]
+    fn matrix_power_float(
+        dm: DMatrix<FLOAT>,
+        k: FLOAT,
+    ) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if k.fract() == 0.0 && k.abs() <= INT::MAX as FLOAT {
+            matrix_power_int(dm, k as INT)
+        } else {
+            matrix_exponential(matrix_log(dm)? * k)
+        }
+    }
+
+    /// Calculates the matrix exponential of a square matrix using a Padé approximant with
+    /// scaling and squaring.
+    /// ```typescript
+    /// let x = [[0, 1],
+    ///          [0, 0]];
+    /// assert_approx_eq(flatten(expm(x)), [1.0, 1.0, 0.0, 1.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 0],
+    ///          [0, 2]];
+    /// assert_approx_eq(flatten(expm(x)), [e, 0.0, 0.0, e**2]);
+    /// ```
+    /// ```typescript
+    /// let result = "";
+    /// try { expm([[1.0 / 0.0, 0.0], [0.0, 1.0]]); } catch { result = "not finite"; }
+    /// assert_eq(result, "not finite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "expm", return_raw, pure)]
+    pub fn expm(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            matrix_exponential(dm).map(omatrix_to_vec_dynamic)
+        })
+    }
+
+    /// Calculates the matrix exponential of a square `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[0, 1],
+    ///                    [0, 0]]);
+    /// assert_approx_eq(flatten(expm(x)), [1.0, 1.0, 0.0, 1.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "expm", return_raw, pure)]
+    pub fn expm_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        matrix_exponential(matrix.0.clone()).map(Matrix)
+    }
+
+    /// Calculates the principal logarithm of a square matrix, i.e., the inverse of `expm`. Fails
+    /// if the matrix is singular or has eigenvalues on the negative real axis.
+    /// ```typescript
+    /// let x = [[1.0, 2.0],
+    ///          [0.0, 3.0]];
+    /// assert_approx_eq(flatten(logm(expm(x))), [1.0, 2.0, 0.0, 3.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[2, 1],
+    ///          [1, 2]];
+    /// assert_approx_eq(flatten(expm(logm(x))), [2.0, 1.0, 1.0, 2.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "logm", return_raw, pure)]
+    pub fn logm(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| matrix_log(dm).map(omatrix_to_vec_dynamic))
+    }
+
+    /// Calculates the principal logarithm of a square `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1.0, 2.0],
+    ///                    [0.0, 3.0]]);
+    /// assert_approx_eq(flatten(logm(expm(x))), [1.0, 2.0, 0.0, 3.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "logm", return_raw, pure)]
+    pub fn logm_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        matrix_log(matrix.0.clone()).map(Matrix)
+    }
+
+    /// Calculates the principal square root of a square matrix, i.e., the matrix `s` with
+    /// `mtimes(s, s) == matrix` whose eigenvalues have nonnegative real parts. Fails if the
+    /// iteration does not converge, e.g., for matrices with eigenvalues on the negative real axis.
+    /// ```typescript
+    /// let x = [[4, 0],
+    ///          [0, 9]];
+    /// assert_approx_eq(flatten(sqrtm(x)), [2.0, 0.0, 0.0, 3.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [0, 1]];
+    /// assert_approx_eq(flatten(sqrtm(x)), [1.0, 1.0, 0.0, 1.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[-1,  0],
+    ///          [ 0, -1]];
+    /// let result = "converged";
+    /// try { sqrtm(x); } catch { result = "failed"; }
+    /// assert_eq(result, "failed");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "sqrtm", return_raw, pure)]
+    pub fn sqrtm(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            matrix_sqrt(dm).map(omatrix_to_vec_dynamic)
+        })
+    }
+
+    /// Calculates the principal square root of a square `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[4, 0],
+    ///                    [0, 9]]);
+    /// assert_approx_eq(flatten(sqrtm(x)), [2.0, 0.0, 0.0, 3.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "sqrtm", return_raw, pure)]
+    pub fn sqrtm_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        matrix_sqrt(matrix.0.clone()).map(Matrix)
+    }
+
+    /// Raises a square matrix to an integer power. Negative powers use the inverse of the
+    /// matrix, and a power of zero gives the identity matrix.
+    /// ```typescript
+    /// let x = [[1, 1],
+    ///          [0, 1]];
+    /// assert_eq(mpower(x, 3), [[1.0, 3.0], [0.0, 1.0]]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [3, 4]];
+    /// assert_approx_eq(flatten(mpower(x, -1)), flatten(inv(x)));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mpower", return_raw, pure)]
+    pub fn mpower_int(matrix: &mut Array, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            matrix_power_int(dm, k).map(omatrix_to_vec_dynamic)
+        })
+    }
+
+    /// Raises a square matrix to a real power, computed as `expm(k * logm(matrix))`.
+    /// ```typescript
+    /// let x = [[4, 0],
+    ///          [0, 9]];
+    /// assert_approx_eq(flatten(mpower(x, 0.5)), [2.0, 0.0, 0.0, 3.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[2, 1],
+    ///          [0, 2]];
+    /// let y = mpower(x, 1.5);
+    /// assert_approx_eq(flatten(mtimes(y, y)), flatten(mpower(x, 3)));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mpower", return_raw, pure)]
+    pub fn mpower_float(matrix: &mut Array, k: FLOAT) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            matrix_power_float(dm, k).map(omatrix_to_vec_dynamic)
+        })
+    }
+
+    /// Raises a square `Matrix` to an integer power.
+    /// ```typescript
+    /// let x = to_matrix([[1, 1],
+    ///                    [0, 1]]);
+    /// assert_eq(to_array(mpower(x, 3)), [[1.0, 3.0], [0.0, 1.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mpower", return_raw, pure)]
+    pub fn mpower_int_native(matrix: &mut Matrix, k: INT) -> Result<Matrix, Box<EvalAltResult>> {
+        matrix_power_int(matrix.0.clone(), k).map(Matrix)
+    }
+
+    /// Raises a square `Matrix` to a real power.
+    /// ```typescript
+    /// let x = to_matrix([[4, 0],
+    ///                    [0, 9]]);
+    /// assert_approx_eq(flatten(mpower(x, 0.5)), [2.0, 0.0, 0.0, 3.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mpower", return_raw, pure)]
+    pub fn mpower_float_native(
+        matrix: &mut Matrix,
+        k: FLOAT,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        matrix_power_float(matrix.0.clone(), k).map(Matrix)
+    }
+
+    /// Solves `a * x = b` for `x`. Square systems use a Cholesky decomposition if `a` is symmetric
+    /// positive definite and an LU decomposition otherwise. Overdetermined systems are solved in
+    /// the least-squares sense with a QR decomposition, and underdetermined (or rank-deficient)
+    /// systems use the minimum-norm solution from an SVD.
+    #[cfg(feature = "nalgebra")]
+    fn left_divide(
+        a: DMatrix<FLOAT>,
+        b: DMatrix<FLOAT>,
+    ) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if a.nrows() != b.nrows() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The coefficient matrix and right-hand side must have the same number of rows"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let (m, n) = a.shape();
+        let tol = (m.max(n) as FLOAT) * FLOAT::EPSILON * a.amax();
+
+        if a.is_square() {
+            if a == a.transpose() {
+                if let Some(chol) = a.clone().cholesky() {
+                    return Ok(chol.solve(&b));
+                }
+            }
+            let lu = a.lu();
+            if lu.u().diagonal().iter().any(|d| d.abs() <= tol) {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "Matrix is singular".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            return lu.solve(&b).ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic("Matrix is singular".to_string(), Position::NONE)
+                    .into()
+            });
+        }
+
+        if m > n {
+            let qr = a.clone().qr();
+            let r = qr.r();
+            if r.diagonal().iter().all(|d| d.abs() > tol) {
+                if let Some(x) = r.solve_upper_triangular(&(qr.q().transpose() * &b)) {
+                    return Ok(x);
+                }
+            }
+        }
+
+        a.svd(true, true)
+            .solve(&b, tol)
+            .map_err(|e| EvalAltResult::ErrorArithmetic(e.to_string(), Position::NONE).into())
+    }
+
+    /// Solves the linear system `a * x = b` for `x`, where `b` is either a matrix or a 1-D array
+    /// (treated as a column vector, in which case the result is also a 1-D array). Square systems
+    /// are solved with a Cholesky decomposition when `a` is symmetric positive definite and with an
+    /// LU decomposition otherwise, and fail if `a` is singular. Overdetermined systems are solved
+    /// in the least-squares sense, and underdetermined systems return the minimum-norm solution.
+    /// This function is also available as `mldivide`.
+    /// ```typescript
+    /// let a = [[2, 1],
+    ///          [1, 3]];
+    /// let x = solve(a, [3, 5]);
+    /// assert_approx_eq(x, [0.8, 1.4]);
+    /// ```
+    /// ```typescript
+    /// let a = [[2, 1],
+    ///          [1, 3]];
+    /// let x = mldivide(a, [3, 5.0]);
+    /// assert_approx_eq(x, [0.8, 1.4]);
+    /// ```
+    /// ```typescript
+    /// let a = [[1, 2],
+    ///          [3, 4]];
+    /// let x = mldivide(a, [[5], [6]]);
+    /// assert_approx_eq(flatten(x), [-4.0, 4.5]);
+    /// ```
+    /// ```typescript
+    /// let a = [[1, 0],
+    ///          [0, 1],
+    ///          [1, 1]];
+    /// let x = solve(a, [1, 1, 3]);
+    /// assert_approx_eq(x, [4.0/3.0, 4.0/3.0]);
+    /// ```
+    /// ```typescript
+    /// let a = [[1, 1]];
+    /// let x = solve(a, [2]);
+    /// assert_approx_eq(x, [1.0, 1.0]);
+    /// ```
+    /// ```typescript
+    /// let a = [[1, 2],
+    ///          [2, 4]];
+    /// let result = "solved";
+    /// try { solve(a, [1, 2]); } catch { result = "singular"; }
+    /// assert_eq(result, "singular");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "solve", name = "mldivide", return_raw)]
+    pub fn solve(a: Array, b: Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(&mut a.clone(), |am| {
+            if crate::is_int_or_float_list(&b) {
+                let bm = DMatrix::from_column_slice(
+                    b.len(),
+                    1,
+                    &crate::if_list_convert_to_vec_float_and_do(&mut b.clone(), Ok)?,
+                );
+                left_divide(am, bm).map(|x| ovector_to_vec_dynamic(x.column(0).into_owned()))
+            } else {
+                if_matrix_convert_to_dmatrix_and_do(&mut b.clone(), |bm| {
+                    left_divide(am.clone(), bm).map(omatrix_to_vec_dynamic)
+                })
+            }
+        })
+    }
+
+    /// Solves the linear system `a * x = b` for `x`, where `a` and `b` are both a `Matrix`. This
+    /// function is also available as `mldivide`.
+    /// ```typescript
+    /// let a = to_matrix([[2, 1],
+    ///                    [1, 3]]);
+    /// let b = to_matrix([[3],
+    ///                    [5]]);
+    /// let x = solve(a, b);
+    /// assert_approx_eq(flatten(x), [0.8, 1.4]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "solve", name = "mldivide", return_raw)]
+    pub fn solve_native(a: Matrix, b: Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        left_divide(a.0, b.0).map(Matrix::from)
+    }
+
+    /// Throws an error unless the matrix is square.
+    #[cfg(feature = "nalgebra")]
+    fn if_square_do<T, F>(dm: DMatrix<FLOAT>, f: F) -> Result<T, Box<EvalAltResult>>
+    where
+        F: FnOnce(DMatrix<FLOAT>) -> Result<T, Box<EvalAltResult>>,
+    {
+        if dm.is_square() {
+            f(dm)
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The input must be a square matrix".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Calculates the determinant of a square matrix.
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [3, 4]];
+    /// assert_approx_eq(det(x), -2.0);
+    /// ```
+    /// ```typescript
+    /// assert_eq(det(eye(4)), 1.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "det", return_raw, pure)]
+    pub fn det(matrix: &mut Array) -> Result<FLOAT, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            if_square_do(dm, |dm| Ok(dm.determinant()))
+        })
+    }
+
+    /// Calculates the determinant of a square `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 2],
+    ///                    [3, 4]]);
+    /// assert_approx_eq(det(x), -2.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "det", return_raw, pure)]
+    pub fn det_native(matrix: &mut Matrix) -> Result<FLOAT, Box<EvalAltResult>> {
+        if_square_do(matrix.0.clone(), |dm| Ok(dm.determinant()))
+    }
+
+    /// Calculates the trace (the sum of the diagonal elements) of a square matrix.
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [3, 4]];
+    /// assert_eq(trace(x), 5.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "trace", return_raw, pure)]
+    pub fn trace(matrix: &mut Array) -> Result<FLOAT, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| if_square_do(dm, |dm| Ok(dm.trace())))
+    }
+
+    /// Calculates the trace (the sum of the diagonal elements) of a square `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 2],
+    ///                    [3, 4]]);
+    /// assert_eq(trace(x), 5.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "trace", return_raw, pure)]
+    pub fn trace_native(matrix: &mut Matrix) -> Result<FLOAT, Box<EvalAltResult>> {
+        if_square_do(matrix.0.clone(), |dm| Ok(dm.trace()))
+    }
+
+    /// Passes a matrix through unchanged if all of its elements are finite. Decompositions such
+    /// as the SVD panic on NaN, so this is checked before handing a matrix to nalgebra.
+    #[cfg(feature = "nalgebra")]
+    fn finite_or_error(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if dm.iter().all(|x| x.is_finite()) {
+            Ok(dm)
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "Matrix must contain only finite values".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Pulls the singular values out of the results of `svd`.
+    #[cfg(feature = "nalgebra")]
+    fn singular_values(svd_results: Map) -> Vec<FLOAT> {
+        svd_results["s"]
+            .clone()
+            .into_array()
+            .unwrap()
+            .iter()
+            .map(|s| s.as_float().unwrap())
+            .collect()
+    }
+
+    /// Counts the singular values above `tol`. If no tolerance is given, the default is
+    /// `max(rows, columns) * epsilon * largest singular value`.
+    #[cfg(feature = "nalgebra")]
+    fn rank_from_singular_values(s: Vec<FLOAT>, shape: (usize, usize), tol: Option<FLOAT>) -> INT {
+        let s_max = s.iter().copied().fold(0.0, FLOAT::max);
+        let tol = tol.unwrap_or((shape.0.max(shape.1) as FLOAT) * FLOAT::EPSILON * s_max);
+        s.iter().filter(|&&v| v > tol).count() as INT
+    }
+
+    /// Calculates the rank of a matrix, i.e., the number of singular values that are larger than
+    /// `max(rows, columns) * epsilon * largest singular value`.
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [2, 4]];
+    /// assert_eq(rank(x), 1);
+    /// ```
+    /// ```typescript
+    /// assert_eq(rank(eye(3)), 3);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rank", return_raw, pure)]
+    pub fn rank(matrix: &mut Array) -> Result<INT, Box<EvalAltResult>> {
+        let s = singular_values(svd_decomp(matrix)?);
+        let shape = matrix_size_by_reference(matrix);
+        Ok(rank_from_singular_values(
+            s,
+            (
+                shape[0].as_int().unwrap() as usize,
+                shape[1].as_int().unwrap() as usize,
+            ),
+            None,
+        ))
+    }
+
+    /// Calculates the rank of a matrix as the number of singular values larger than `tol`.
+    /// ```typescript
+    /// let x = [[1.0, 0.0],
+    ///          [0.0, 1e-6]];
+    /// assert_eq(rank(x, 1e-3), 1);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rank", return_raw, pure)]
+    pub fn rank_with_tol(matrix: &mut Array, tol: FLOAT) -> Result<INT, Box<EvalAltResult>> {
+        let s = singular_values(svd_decomp(matrix)?);
+        Ok(rank_from_singular_values(s, (0, 0), Some(tol)))
+    }
+
+    /// Calculates the rank of a matrix as the number of singular values larger than an INT `tol`.
+    /// ```typescript
+    /// let x = [[1, 0],
+    ///          [0, 0]];
+    /// assert_eq(rank(x, 0), 1);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rank", return_raw, pure)]
+    pub fn rank_with_int_tol(matrix: &mut Array, tol: INT) -> Result<INT, Box<EvalAltResult>> {
+        rank_with_tol(matrix, tol as FLOAT)
+    }
+
+    /// Calculates the rank of a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 2],
+    ///                    [2, 4]]);
+    /// assert_eq(rank(x), 1);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rank", return_raw, pure)]
+    pub fn rank_native(matrix: &mut Matrix) -> Result<INT, Box<EvalAltResult>> {
+        let s = singular_values(svd_decomp_native(matrix)?);
+        Ok(rank_from_singular_values(s, matrix.0.shape(), None))
+    }
+
+    /// Calculates the rank of a `Matrix` as the number of singular values larger than `tol`.
+    /// ```typescript
+    /// let x = to_matrix([[1.0, 0.0],
+    ///                    [0.0, 1e-6]]);
+    /// assert_eq(rank(x, 1e-3), 1);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rank", return_raw, pure)]
+    pub fn rank_with_tol_native(
+        matrix: &mut Matrix,
+        tol: FLOAT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        let s = singular_values(svd_decomp_native(matrix)?);
+        Ok(rank_from_singular_values(s, matrix.0.shape(), Some(tol)))
+    }
+
+    /// Calculates the rank of a `Matrix` as the number of singular values larger than an INT
+    /// `tol`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 0],
+    ///                    [0, 0]]);
+    /// assert_eq(rank(x, 0), 1);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rank", return_raw, pure)]
+    pub fn rank_with_int_tol_native(
+        matrix: &mut Matrix,
+        tol: INT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        rank_with_tol_native(matrix, tol as FLOAT)
+    }
+
+    /// Calculates the condition number of a matrix in the 2-norm, i.e., the ratio of the largest
+    /// to the smallest singular value. Singular matrices have an infinite condition number.
+    /// ```typescript
+    /// let x = [[2, 0],
+    ///          [0, 1]];
+    /// assert_approx_eq(cond(x), 2.0);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 1],
+    ///          [1, 1]];
+    /// assert_eq(cond(x), inf);
+    /// ```
+    /// ```typescript
+    /// let result = "";
+    /// try { cond([[0.0 / 0.0, 1.0], [2.0, 3.0]]); } catch { result = "not finite"; }
+    /// assert_eq(result, "not finite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "cond", return_raw, pure)]
+    pub fn cond(matrix: &mut Array) -> Result<FLOAT, Box<EvalAltResult>> {
+        svd_decomp(matrix).map(|svd_results| condition_number(singular_values(svd_results)))
+    }
+
+    /// Calculates the condition number of a `Matrix` in the 2-norm.
+    /// ```typescript
+    /// let x = to_matrix([[2, 0],
+    ///                    [0, 1]]);
+    /// assert_approx_eq(cond(x), 2.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "cond", return_raw, pure)]
+    pub fn cond_native(matrix: &mut Matrix) -> Result<FLOAT, Box<EvalAltResult>> {
+        svd_decomp_native(matrix).map(|svd_results| condition_number(singular_values(svd_results)))
+    }
+
+    /// Computes the ratio of the largest to the smallest singular value.
+    #[cfg(feature = "nalgebra")]
+    fn condition_number(s: Vec<FLOAT>) -> FLOAT {
+        let s_max = s.iter().copied().fold(0.0, FLOAT::max);
+        let s_min = s.iter().copied().fold(FLOAT::INFINITY, FLOAT::min);
+        if s_min == 0.0 {
+            FLOAT::INFINITY
+        } else {
+            s_max / s_min
+        }
+    }
+
+    /// Interprets the order of a norm, which may be an INT, a FLOAT (including `inf` and `-inf`),
+    /// or the string `"fro"` or `"inf"`. Returns the order and whether it is the Frobenius norm.
+    #[cfg(feature = "nalgebra")]
+    fn norm_order(p: Dynamic) -> Result<(FLOAT, bool), Box<EvalAltResult>> {
+        if p.is_string() {
+            match p.into_string().unwrap().as_str() {
+                "fro" => Ok((2.0, true)),
+                "inf" | "Inf" => Ok((FLOAT::INFINITY, false)),
+                other => Err(EvalAltResult::ErrorArithmetic(
+                    format!("Unknown norm type: {other}"),
+                    Position::NONE,
+                )
+                .into()),
+            }
+        } else {
+            if_int_convert_to_float_and_do(p, |p| Ok((p, false)))
+        }
+    }
+
+    /// Calculates the p-norm of a vector.
+    #[cfg(feature = "nalgebra")]
+    fn vector_norm(v: Vec<FLOAT>, p: FLOAT) -> Result<FLOAT, Box<EvalAltResult>> {
+        let abs = v.into_iter().map(|x| x.abs());
+        if p == FLOAT::INFINITY {
+            Ok(abs.fold(0.0, FLOAT::max))
+        } else if p == FLOAT::NEG_INFINITY {
+            Ok(abs.fold(FLOAT::INFINITY, FLOAT::min))
+        } else if p > 0.0 {
+            Ok(abs.map(|x| x.powf(p)).sum::<FLOAT>().powf(1.0 / p))
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The order of a vector norm must be positive, inf, or -inf".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Calculates the 1-, 2-, infinity-, or Frobenius norm of a matrix. Row and column vectors
+    /// use the vector norm instead.
+    #[cfg(feature = "nalgebra")]
+    fn matrix_norm(dm: DMatrix<FLOAT>, p: Dynamic) -> Result<FLOAT, Box<EvalAltResult>> {
+        let (p, frobenius) = norm_order(p)?;
+        if frobenius {
+            Ok(dm.norm())
+        } else if dm.nrows() == 1 || dm.ncols() == 1 {
+            vector_norm(dm.as_slice().to_vec(), p)
+        } else if p == 1.0 {
+            Ok(dm
+                .column_iter()
+                .map(|c| c.iter().map(|x| x.abs()).sum::<FLOAT>())
+                .fold(0.0, FLOAT::max))
+        } else if p == 2.0 {
+            Ok(finite_or_error(dm)?.singular_values().max())
+        } else if p == FLOAT::INFINITY {
+            Ok(dm
+                .row_iter()
+                .map(|r| r.iter().map(|x| x.abs()).sum::<FLOAT>())
+                .fold(0.0, FLOAT::max))
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "Matrix norms are only supported for p = 1, 2, inf, or \"fro\"".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Calculates the 2-norm of a vector (a 1-D array) or matrix. For a matrix, this is the
+    /// largest singular value.
+    /// ```typescript
+    /// assert_eq(norm([3, 4]), 5.0);
+    /// ```
+    /// ```typescript
+    /// let x = [[2, 0],
+    ///          [0, 1]];
+    /// assert_approx_eq(norm(x), 2.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "norm", return_raw, pure)]
+    pub fn norm(matrix: &mut Array) -> Result<FLOAT, Box<EvalAltResult>> {
+        norm_with_order(matrix, Dynamic::from_int(2))
+    }
+
+    /// Calculates the norm of a vector (a 1-D array) or matrix. For vectors, `p` can be any
+    /// positive number, `inf`, or `-inf`. For matrices, `p` can be 1 (maximum absolute column
+    /// sum), 2 (largest singular value), `inf` (maximum absolute row sum), or `"fro"` (Frobenius
+    /// norm).
+    /// ```typescript
+    /// assert_eq(norm([3, -4], 1), 7.0);
+    /// ```
+    /// ```typescript
+    /// assert_eq(norm([3, 4.0]), 5.0);
+    /// ```
+    /// ```typescript
+    /// assert_eq(norm([3, -4], inf), 4.0);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, -2],
+    ///          [3,  4]];
+    /// assert_eq(norm(x, 1), 6.0);
+    /// assert_eq(norm(x, inf), 7.0);
+    /// assert_approx_eq(norm(x, "fro"), sqrt(30.0));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "norm", return_raw, pure)]
+    pub fn norm_with_order(matrix: &mut Array, p: Dynamic) -> Result<FLOAT, Box<EvalAltResult>> {
+        if crate::is_int_or_float_list(matrix) {
+            let (p, _) = norm_order(p)?;
+            crate::if_list_convert_to_vec_float_and_do(matrix, |v| vector_norm(v, p))
+        } else {
+            if_matrix_convert_to_dmatrix_and_do(matrix, |dm| matrix_norm(dm, p.clone()))
+        }
+    }
+
+    /// Calculates the 2-norm of a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[3, 4]]);
+    /// assert_approx_eq(norm(x), 5.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "norm", return_raw, pure)]
+    pub fn norm_native(matrix: &mut Matrix) -> Result<FLOAT, Box<EvalAltResult>> {
+        matrix_norm(matrix.0.clone(), Dynamic::from_int(2))
+    }
+
+    /// Calculates the norm of a `Matrix`, where `p` can be 1, 2, `inf`, or `"fro"`.
+    /// ```typescript
+    /// let x = to_matrix([[1, -2],
+    ///                    [3,  4]]);
+    /// assert_eq(norm(x, 1), 6.0);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "norm", return_raw, pure)]
+    pub fn norm_with_order_native(
+        matrix: &mut Matrix,
+        p: Dynamic,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        matrix_norm(matrix.0.clone(), p)
+    }
+
+    /// Eigenvalues along with a matrix whose columns are the corresponding eigenvectors.
+    #[cfg(feature = "nalgebra")]
+    type EigenPairs = (
+        Vec<nalgebralib::Complex<FLOAT>>,
+        DMatrix<nalgebralib::Complex<FLOAT>>,
+    );
+
+    /// Converts a real matrix into a complex one.
+    #[cfg(feature = "nalgebra")]
+    fn to_complex_dmatrix(dm: DMatrix<FLOAT>) -> DMatrix<nalgebralib::Complex<FLOAT>> {
+        dm.map(|x| nalgebralib::Complex::new(x, 0.0))
+    }
+
+    /// Sorts eigenvalues (and their eigenvectors) by real part, then by imaginary part, so that
+    /// the output does not depend on the order in which the solver found them.
+    #[cfg(feature = "nalgebra")]
+    fn sort_eigenpairs(
+        values: Vec<nalgebralib::Complex<FLOAT>>,
+        vectors: DMatrix<nalgebralib::Complex<FLOAT>>,
+    ) -> EigenPairs {
+        let mut order = (0..values.len()).collect::<Vec<usize>>();
+        order.sort_by(|&i, &j| {
+            values[i]
+                .re
+                .total_cmp(&values[j].re)
+                .then(values[i].im.total_cmp(&values[j].im))
+        });
+        let sorted_values = order.iter().map(|&i| values[i]).collect();
+        let sorted_vectors =
+            DMatrix::from_fn(vectors.nrows(), order.len(), |r, c| vectors[(r, order[c])]);
+        (sorted_values, sorted_vectors)
+    }
+
+    /// Scales each eigenvector to unit length and rotates it so that its largest component is
+    /// real and positive. Eigenvectors of real eigenvalues are then real.
+    #[cfg(feature = "nalgebra")]
+    fn normalize_eigenvectors(
+        values: Vec<nalgebralib::Complex<FLOAT>>,
+        mut vectors: DMatrix<nalgebralib::Complex<FLOAT>>,
+    ) -> EigenPairs {
+        for (idx, mut column) in vectors.column_iter_mut().enumerate() {
+            let pivot =
+                column
+                    .iter()
+                    .copied()
+                    .fold(nalgebralib::Complex::new(0.0, 0.0), |acc, c| {
+                        if c.norm() > acc.norm() {
+                            c
+                        } else {
+                            acc
+                        }
+                    });
+            if pivot.norm() > 0.0 {
+                let scale = pivot.conj() / (pivot.norm() * column.norm());
+                column.iter_mut().for_each(|c| *c *= scale);
+            }
+            if values[idx].im == 0.0 {
+                column.iter_mut().for_each(|c| c.im = 0.0);
+            }
+        }
+        (values, vectors)
+    }
+
+    /// Solves the symmetric eigenvalue problem with nalgebra's `SymmetricEigen`.
+    #[cfg(feature = "nalgebra")]
+    fn symmetric_eig(dm: DMatrix<FLOAT>) -> EigenPairs {
+        let se = nalgebralib::linalg::SymmetricEigen::new(dm);
+        let values = se
+            .eigenvalues
+            .iter()
+            .map(|&v| nalgebralib::Complex::new(v, 0.0))
+            .collect();
+        let (values, vectors) = normalize_eigenvectors(values, to_complex_dmatrix(se.eigenvectors));
+        sort_eigenpairs(values, vectors)
+    }
+
+    /// Solves the general eigenvalue problem. The eigenvalues come from the real Schur form, and
+    /// the eigenvectors for each distinct eigenvalue `l` are the right singular vectors of
+    /// `dm - l * I` belonging to its smallest singular values.
+    #[cfg(feature = "nalgebra")]
+    fn general_eig(dm: DMatrix<FLOAT>) -> Result<EigenPairs, Box<EvalAltResult>> {
+        let n = dm.nrows();
+        let values = match nalgebralib::linalg::Schur::try_new(
+            dm.clone(),
+            FLOAT::EPSILON,
+            crate::schur_iterations(n),
+        ) {
+            Some(schur) => schur.complex_eigenvalues().iter().copied().collect(),
+            None => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The eigenvalue computation failed to converge".to_string(),
+                    Position::NONE,
+                )
+                .into())
+            }
+        };
+        let (values, _) = sort_eigenpairs(values, DMatrix::zeros(n, n));
+
+        let a = to_complex_dmatrix(dm.clone());
+        let scale = dm.amax().max(1.0);
+        let mut vectors = DMatrix::zeros(n, n);
+        let mut idx = 0;
+        while idx < n {
+            // Group repeated eigenvalues so that each gets its own eigenvector
+            let mut multiplicity = 1;
+            while idx + multiplicity < n
+                && (values[idx + multiplicity] - values[idx]).norm()
+                    <= FLOAT::EPSILON.sqrt() * scale
+            {
+                multiplicity += 1;
+            }
+
+            let shifted = a.clone() - DMatrix::from_diagonal_element(n, n, values[idx]);
+            let svd = shifted.svd(false, true);
+            let v_t = svd.v_t.unwrap();
+            let mut order = (0..n).collect::<Vec<usize>>();
+            order.sort_by(|&i, &j| svd.singular_values[i].total_cmp(&svd.singular_values[j]));
+
+            // A defective eigenvalue has fewer independent eigenvectors than its multiplicity, in
+            // which case the last one is repeated
+            let nullity = svd
+                .singular_values
+                .iter()
+                .filter(|&&s| s <= FLOAT::EPSILON.sqrt() * scale)
+                .count()
+                .clamp(1, multiplicity);
+            for k in 0..multiplicity {
+                let row = order[k.min(nullity - 1)];
+                vectors.set_column(idx + k, &v_t.row(row).adjoint());
+            }
+            idx += multiplicity;
+        }
+        Ok(normalize_eigenvectors(values, vectors))
+    }
+
+    /// Solves the eigenvalue problem for a square matrix, using the symmetric solver when possible.
+    #[cfg(feature = "nalgebra")]
+    fn eig_core(dm: DMatrix<FLOAT>) -> Result<EigenPairs, Box<EvalAltResult>> {
+        if_square_do(finite_or_error(dm)?, |dm| {
+            if dm == dm.transpose() {
+                Ok(symmetric_eig(dm))
+            } else {
+                general_eig(dm)
+            }
+        })
+    }
+
+    /// Solves the generalized eigenvalue problem `a * v = l * b * v`. When `a` is symmetric and
+    /// `b` is symmetric positive-definite, the problem is reduced to a symmetric one with the
+    /// Cholesky factor of `b`. Otherwise, `b` must be nonsingular.
+    #[cfg(feature = "nalgebra")]
+    fn generalized_eig_core(
+        a: DMatrix<FLOAT>,
+        b: DMatrix<FLOAT>,
+    ) -> Result<EigenPairs, Box<EvalAltResult>> {
+        if !a.is_square() || a.shape() != b.shape() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The inputs must be square matrices of the same size".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let (a, b) = (finite_or_error(a)?, finite_or_error(b)?);
+
+        if a == a.transpose() && b == b.transpose() {
+            if let Some(chol) = nalgebralib::linalg::Cholesky::new(b.clone()) {
+                let l = chol.l();
+                let l_inv_a = l.solve_lower_triangular(&a).unwrap();
+                let c = l
+                    .solve_lower_triangular(&l_inv_a.transpose())
+                    .unwrap()
+                    .transpose();
+                let (values, y) = symmetric_eig((c.clone() + c.transpose()) / 2.0);
+                let x = l
+                    .transpose()
+                    .solve_upper_triangular(&y.map(|c| c.re))
+                    .unwrap();
+                return Ok(normalize_eigenvectors(values, to_complex_dmatrix(x)));
+            }
+        }
+
+        eig_core(left_divide(b, a)?)
+    }
+
+    /// Puts the output of an eigenvalue solver into a map with `values` and `vectors` entries,
+    /// each of which has `re` and `im` parts. The vectors are wrapped with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn eig_to_map(
+        values: Vec<nalgebralib::Complex<FLOAT>>,
+        vectors: DMatrix<nalgebralib::Complex<FLOAT>>,
+        wrap: fn(DMatrix<FLOAT>) -> Dynamic,
+    ) -> Map {
+        let mut value_parts = BTreeMap::new();
+        let mut reid = smartstring::SmartString::new();
+        reid.push_str("re");
+        value_parts.insert(
+            reid,
+            Dynamic::from_array(values.iter().map(|v| Dynamic::from_float(v.re)).collect()),
+        );
+        let mut imid = smartstring::SmartString::new();
+        imid.push_str("im");
+        value_parts.insert(
+            imid,
+            Dynamic::from_array(values.iter().map(|v| Dynamic::from_float(v.im)).collect()),
+        );
+
+        let mut vector_parts = BTreeMap::new();
+        let mut reid = smartstring::SmartString::new();
+        reid.push_str("re");
+        vector_parts.insert(reid, wrap(vectors.map(|v| v.re)));
+        let mut imid = smartstring::SmartString::new();
+        imid.push_str("im");
+        vector_parts.insert(imid, wrap(vectors.map(|v| v.im)));
+
+        let mut result = BTreeMap::new();
+        let mut vid = smartstring::SmartString::new();
+        vid.push_str("values");
+        result.insert(vid, Dynamic::from_map(value_parts));
+        let mut eid = smartstring::SmartString::new();
+        eid.push_str("vectors");
+        result.insert(eid, Dynamic::from_map(vector_parts));
+
+        result
+    }
+
+    /// Calculates the eigenvalues and eigenvectors of a square matrix. The output is an object
+    /// map with entries for `values` and `vectors`, each of which is a map with the real (`re`)
+    /// and imaginary (`im`) parts. The eigenvectors are the columns of `vectors`, have unit length,
+    /// and are sorted along with the eigenvalues by real part and then by imaginary part. Symmetric
+    /// matrices are handled by a dedicated solver and always have real results.
+    /// ```typescript
+    /// let x = [[2, 1],
+    ///          [1, 2]];
+    /// let e = eig(x);
+    /// assert_approx_eq(e.values.re, [1.0, 3.0]);
+    /// assert_eq(e.values.im, [0.0, 0.0]);
+    /// let v = e.vectors.re;
+    /// assert_approx_eq(flatten(mtimes(x, v)), flatten(mtimes(v, diag(e.values.re))));
+    /// ```
+    /// ```typescript
+    /// let x = [[ 0, 1],
+    ///          [-2, -3]];
+    /// let e = eig(x);
+    /// assert_approx_eq(e.values.re, [-2.0, -1.0]);
+    /// let v = e.vectors.re;
+    /// assert_approx_eq(flatten(mtimes(x, v)), flatten(mtimes(v, diag(e.values.re))));
+    /// ```
+    /// ```typescript
+    /// let x = [[ 0, 1],
+    ///          [-2, 0]];
+    /// let e = eig(x);
+    /// assert_approx_eq(e.values.re, [0.0, 0.0]);
+    /// assert_approx_eq(e.values.im, [-sqrt(2.0), sqrt(2.0)]);
+    /// assert_approx_eq(flatten(e.vectors.re), [0.0, 0.0, sqrt(2.0/3.0), sqrt(2.0/3.0)]);
+    /// assert_approx_eq(flatten(e.vectors.im), [sqrt(1.0/3.0), -sqrt(1.0/3.0), 0.0, 0.0]);
+    /// ```
+    /// ```typescript
+    /// let result = "";
+    /// try { eig([[0.0 / 0.0, 1.0], [2.0, 3.0]]); } catch { result = "not finite"; }
+    /// assert_eq(result, "not finite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "eig", return_raw, pure)]
+    pub fn eig(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            let (values, vectors) = eig_core(dm)?;
+            Ok(eig_to_map(values, vectors, to_dynamic_array))
+        })
+    }
+
+    /// Calculates the generalized eigenvalues and eigenvectors of two square matrices, i.e., the
+    /// solutions of `mtimes(a, v) == l * mtimes(b, v)`. The output has the same form as for the
+    /// single-matrix version.
+    /// ```typescript
+    /// let a = [[2, 0],
+    ///          [0, 3]];
+    /// let b = [[1, 0],
+    ///          [0, 2]];
+    /// let e = eig(a, b);
+    /// assert_approx_eq(e.values.re, [1.5, 2.0]);
+    /// assert_eq(e.vectors.re, [[0.0, 1.0], [1.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "eig", return_raw)]
+    pub fn generalized_eig(mut a: Array, mut b: Array) -> Result<Map, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(&mut a, |da| {
+            if_matrix_convert_to_dmatrix_and_do(&mut b, |db| {
+                let (values, vectors) = generalized_eig_core(da.clone(), db)?;
+                Ok(eig_to_map(values, vectors, to_dynamic_array))
             })
         })
     }
 
+    /// Calculates the eigenvalues and eigenvectors of a square `Matrix`, returning the parts of
+    /// the eigenvectors as a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[2, 1],
+    ///                    [1, 2]]);
+    /// let e = eig(x);
+    /// assert_approx_eq(e.values.re, [1.0, 3.0]);
+    /// assert_eq(size(e.vectors.re), [2, 2]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "eig", return_raw, pure)]
+    pub fn eig_native(matrix: &mut Matrix) -> Result<Map, Box<EvalAltResult>> {
+        let (values, vectors) = eig_core(matrix.0.clone())?;
+        Ok(eig_to_map(values, vectors, to_dynamic_matrix))
+    }
+
+    /// Calculates the generalized eigenvalues and eigenvectors of two square `Matrix` values.
+    /// ```typescript
+    /// let a = to_matrix([[2, 0],
+    ///                    [0, 3]]);
+    /// let b = to_matrix([[1, 0],
+    ///                    [0, 2]]);
+    /// let e = eig(a, b);
+    /// assert_approx_eq(e.values.re, [1.5, 2.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "eig", return_raw)]
+    pub fn generalized_eig_native(a: Matrix, b: Matrix) -> Result<Map, Box<EvalAltResult>> {
+        let (values, vectors) = generalized_eig_core(a.0, b.0)?;
+        Ok(eig_to_map(values, vectors, to_dynamic_matrix))
+    }
+
+    /// Computes the eigenvalues and eigenvectors of a square matrix, wrapping each matrix in the
+    /// output with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn eigs_to_map(
+        dm: DMatrix<FLOAT>,
+        wrap: fn(DMatrix<FLOAT>) -> Dynamic,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let a = to_complex_dmatrix(dm.clone());
+        let (values, vectors) = eig_core(dm)?;
+
+        // Verify solution
+        let residuals = values
+            .iter()
+            .zip(vectors.column_iter())
+            .map(|(&ev, v)| Dynamic::from_float((&a * v - v * ev).camax()))
+            .collect::<Array>();
+
+        let mut result = BTreeMap::new();
+        let mut vid = smartstring::SmartString::new();
+        vid.push_str("eigenvectors");
+        result.insert(vid, wrap(vectors.map(|v| v.re)));
+        let mut did = smartstring::SmartString::new();
+        did.push_str("real_eigenvalues");
+        result.insert(
+            did,
+            Dynamic::from_array(values.iter().map(|v| Dynamic::from_float(v.re)).collect()),
+        );
+        let mut eid = smartstring::SmartString::new();
+        eid.push_str("imaginary_eigenvalues");
+        result.insert(
+            eid,
+            Dynamic::from_array(values.iter().map(|v| Dynamic::from_float(v.im)).collect()),
+        );
+        let mut cid = smartstring::SmartString::new();
+        cid.push_str("eigenvalues");
+        result.insert(
+            cid,
+            Dynamic::from_array(
+                values
+                    .iter()
+                    .map(|&v| Dynamic::from(crate::Complex::from(v)))
+                    .collect(),
+            ),
+        );
+        let mut rid = smartstring::SmartString::new();
+        rid.push_str("residuals");
+        result.insert(rid, Dynamic::from_array(residuals));
+
+        Ok(result)
+    }
+
     /// Calculate the eigenvalues and eigenvectors for a matrix. Specifically, the output is an
-    /// object map with entries for real_eigenvalues, imaginary_eigenvalues, eigenvectors, and
-    /// residuals.
+    /// object map with entries for eigenvalues (as an array of `Complex` values), real_eigenvalues,
+    /// imaginary_eigenvalues, eigenvectors, and residuals. The eigenvectors are the real parts of
+    /// those returned by `eig`, which should be used instead for matrices with complex eigenvalues.
     /// ```typescript
     /// let matrix = eye(5);
     /// let eig = eigs(matrix);
@@ -76,76 +1493,78 @@ pub mod matrix_functions {
     /// let eig = eigs(matrix);
     /// assert(sum(eig.residuals) < 0.000001);
     /// ```
+    /// ```typescript
+    /// let matrix = [[ 0.0, 1.0],
+    ///               [-1.0, 0.0]];
+    /// let eig = eigs(matrix);
+    /// assert(eig.eigenvalues[0] == complex(0, -1));
+    /// assert(eig.eigenvalues[1] == conj(eig.eigenvalues[0]));
+    /// ```
     #[cfg(feature = "nalgebra")]
     #[rhai_fn(name = "eigs", return_raw, pure)]
     pub fn matrix_eigs_alt(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
-        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
-            // Convert vec_array to omatrix
-            let mut dm = DMatrix::from_fn(matrix_as_vec.len(), matrix_as_vec[0].len(), |i, j| {
-                if matrix_as_vec[0][0].is_float() {
-                    matrix_as_vec[i][j].as_float().unwrap()
-                } else {
-                    matrix_as_vec[i][j].as_int().unwrap() as FLOAT
-                }
-            });
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| eigs_to_map(dm, to_dynamic_array))
+    }
 
-            // Grab shape for later
-            let dms = dm.shape().1;
-
-            // Get teh eigenvalues
-            let eigenvalues = dm.complex_eigenvalues();
-
-            // Iterate through eigenvalues to get eigenvectors
-            let mut imaginary_values = vec![Dynamic::from_float(1.0); 0];
-            let mut real_values = vec![Dynamic::from_float(1.0); 0];
-            let mut residuals = vec![Dynamic::from_float(1.0); 0];
-            let mut eigenvectors = DMatrix::from_element(dms, 0, 0.0);
-            for (idx, ev) in eigenvalues.iter().enumerate() {
-                // Eigenvalue components
-                imaginary_values.push(Dynamic::from_float(ev.im));
-                real_values.push(Dynamic::from_float(ev.re));
-
-                // Get eigenvector
-                let mut A = dm.clone() - DMatrix::from_diagonal_element(dms, dms, ev.re);
-                A = A.insert_column(0, 0.0);
-                A = A.insert_row(0, 0.0);
-                A[(0, idx + 1)] = 1.0;
-                let mut b = DMatrix::from_element(dms + 1, 1, 0.0);
-                b[(0, 0)] = 1.0;
-                let eigenvector = A
-                    .svd(true, true)
-                    .solve(&b, 1e-10)
-                    .unwrap()
-                    .remove_rows(0, 1)
-                    .normalize();
+    /// Calculate the eigenvalues and eigenvectors for a `Matrix`. The output has the same entries
+    /// as for a nested array, except that the eigenvectors are returned as a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix([[ 0.0,  1.0],
+    ///                         [-2.0, -3.0]]);
+    /// let eig = eigs(matrix);
+    /// assert(sum(eig.residuals) < 0.000001);
+    /// assert_eq(size(eig.eigenvectors), [2, 2]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "eigs", return_raw, pure)]
+    pub fn matrix_eigs_native(matrix: &mut Matrix) -> Result<Map, Box<EvalAltResult>> {
+        eigs_to_map(matrix.0.clone(), to_dynamic_matrix)
+    }
 
-                // Verify solution
-                residuals.push(Dynamic::from_float(
-                    (dm.clone() * eigenvector.clone() - ev.re * eigenvector.clone()).amax(),
-                ));
+    /// Computes the singular value decomposition of a matrix, wrapping each matrix in the output
+    /// with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn svd_to_map(
+        dm: DMatrix<FLOAT>,
+        wrap: fn(DMatrix<FLOAT>) -> Dynamic,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let svd = nalgebralib::linalg::SVD::new(finite_or_error(dm)?, true, true);
+
+        let mut result = BTreeMap::new();
+        let mut uid = smartstring::SmartString::new();
+        uid.push_str("u");
+        match svd.u {
+            Some(u) => result.insert(uid, wrap(u)),
+            None => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!("SVD decomposition cannot be computed for this matrix."),
+                    Position::NONE,
+                )
+                .into());
+            }
+        };
 
-                eigenvectors.extend(eigenvector.column_iter());
+        let mut vid = smartstring::SmartString::new();
+        vid.push_str("v");
+        match svd.v_t {
+            Some(v) => result.insert(vid, wrap(v)),
+            None => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!("SVD decomposition cannot be computed for this matrix."),
+                    Position::NONE,
+                )
+                .into());
             }
+        };
 
-            let mut result = BTreeMap::new();
-            let mut vid = smartstring::SmartString::new();
-            vid.push_str("eigenvectors");
-            result.insert(
-                vid,
-                Dynamic::from_array(omatrix_to_vec_dynamic(eigenvectors)),
-            );
-            let mut did = smartstring::SmartString::new();
-            did.push_str("real_eigenvalues");
-            result.insert(did, Dynamic::from_array(real_values));
-            let mut eid = smartstring::SmartString::new();
-            eid.push_str("imaginary_eigenvalues");
-            result.insert(eid, Dynamic::from_array(imaginary_values));
-            let mut rid = smartstring::SmartString::new();
-            rid.push_str("residuals");
-            result.insert(rid, Dynamic::from_array(residuals));
+        let mut sid = smartstring::SmartString::new();
+        sid.push_str("s");
+        result.insert(
+            sid,
+            Dynamic::from_array(ovector_to_vec_dynamic(svd.singular_values)),
+        );
 
-            Ok(result)
-        })
+        Ok(result)
     }
 
     /// Calculates the singular value decomposition of a matrix
@@ -157,56 +1576,256 @@ pub mod matrix_functions {
     #[cfg(feature = "nalgebra")]
     #[rhai_fn(name = "svd", return_raw, pure)]
     pub fn svd_decomp(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
-        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
-            let dm = DMatrix::from_fn(matrix_as_vec.len(), matrix_as_vec[0].len(), |i, j| {
-                if matrix_as_vec[0][0].is::<FLOAT>() {
-                    matrix_as_vec[i][j].as_float().unwrap()
-                } else {
-                    matrix_as_vec[i][j].as_int().unwrap() as FLOAT
-                }
-            });
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| svd_to_map(dm, to_dynamic_array))
+    }
 
-            // Try ot invert
-            let svd = nalgebralib::linalg::SVD::new(dm, true, true);
+    /// Calculates the singular value decomposition of a `Matrix`. The `u` and `v` entries of the
+    /// output are returned as a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix(eye(5));
+    /// let svd_results = svd(matrix);
+    /// assert_eq(svd_results.s, ones([5]));
+    /// assert_eq(to_array(svd_results.u), eye(5));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "svd", return_raw, pure)]
+    pub fn svd_decomp_native(matrix: &mut Matrix) -> Result<Map, Box<EvalAltResult>> {
+        svd_to_map(matrix.0.clone(), to_dynamic_matrix)
+    }
 
-            let mut result = BTreeMap::new();
-            let mut uid = smartstring::SmartString::new();
-            uid.push_str("u");
-            match svd.u {
-                Some(u) => result.insert(uid, Dynamic::from_array(omatrix_to_vec_dynamic(u))),
-                None => {
-                    return Err(EvalAltResult::ErrorArithmetic(
-                        format!("SVD decomposition cannot be computed for this matrix."),
-                        Position::NONE,
-                    )
-                    .into());
-                }
-            };
+    /// The singular value decomposition of a dynamically sized matrix.
+    #[cfg(feature = "nalgebra")]
+    type DynamicSvd = nalgebralib::linalg::SVD<FLOAT, nalgebralib::Dyn, nalgebralib::Dyn>;
 
-            let mut vid = smartstring::SmartString::new();
-            vid.push_str("v");
-            match svd.v_t {
-                Some(v) => result.insert(vid, Dynamic::from_array(omatrix_to_vec_dynamic(v))),
-                None => {
-                    return Err(EvalAltResult::ErrorArithmetic(
-                        format!("SVD decomposition cannot be computed for this matrix."),
-                        Position::NONE,
-                    )
-                    .into());
-                }
-            };
+    /// Computes the SVD of a matrix and the tolerance below which its singular values are treated
+    /// as zero. If no tolerance is given, the default is
+    /// `max(rows, columns) * epsilon * largest singular value`.
+    #[cfg(feature = "nalgebra")]
+    fn svd_with_tolerance(
+        dm: DMatrix<FLOAT>,
+        tol: Option<FLOAT>,
+    ) -> Result<(DynamicSvd, FLOAT), Box<EvalAltResult>> {
+        let max_dim = dm.nrows().max(dm.ncols()) as FLOAT;
+        let svd = nalgebralib::linalg::SVD::new(finite_or_error(dm)?, true, true);
+        let s_max = svd.singular_values.iter().copied().fold(0.0, FLOAT::max);
+        let tol = tol.unwrap_or(max_dim * FLOAT::EPSILON * s_max);
+        Ok((svd, tol))
+    }
 
-            let mut sid = smartstring::SmartString::new();
-            sid.push_str("s");
-            result.insert(
-                sid,
-                Dynamic::from_array(ovector_to_vec_dynamic(svd.singular_values)),
-            );
+    /// Computes the Moore-Penrose pseudo-inverse of a matrix.
+    #[cfg(feature = "nalgebra")]
+    fn pseudo_inverse(
+        dm: DMatrix<FLOAT>,
+        tol: Option<FLOAT>,
+    ) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        let (svd, tol) = svd_with_tolerance(dm, tol)?;
+        svd.pseudo_inverse(tol)
+            .map_err(|e| EvalAltResult::ErrorArithmetic(e.to_string(), Position::NONE).into())
+    }
 
-            Ok(result)
+    /// Computes an orthonormal basis for the null space of a matrix from the right singular
+    /// vectors whose singular values are at most the default tolerance.
+    #[cfg(feature = "nalgebra")]
+    fn null_space(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        // Pad with rows of zeros so that the SVD yields a full set of right singular vectors
+        let (n_rows, n_cols) = dm.shape();
+        let padded = if n_rows < n_cols {
+            dm.resize_vertically(n_cols, 0.0)
+        } else {
+            dm
+        };
+        let max_dim = n_rows.max(n_cols) as FLOAT;
+        let svd = nalgebralib::linalg::SVD::new(finite_or_error(padded)?, false, true);
+        let s_max = svd.singular_values.iter().copied().fold(0.0, FLOAT::max);
+        let tol = max_dim * FLOAT::EPSILON * s_max;
+        let v_t = svd.v_t.unwrap();
+        let columns = svd
+            .singular_values
+            .iter()
+            .enumerate()
+            .filter(|(_, &s)| s <= tol)
+            .map(|(idx, _)| v_t.row(idx).transpose())
+            .collect::<Vec<_>>();
+        Ok(DMatrix::from_fn(n_cols, columns.len(), |r, c| {
+            columns[c][r]
+        }))
+    }
+
+    /// Computes an orthonormal basis for the range of a matrix from the left singular vectors
+    /// whose singular values are above the default tolerance.
+    #[cfg(feature = "nalgebra")]
+    fn range_space(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        let n_rows = dm.nrows();
+        let (svd, tol) = svd_with_tolerance(dm, None)?;
+        let u = svd.u.unwrap();
+        let columns = svd
+            .singular_values
+            .iter()
+            .enumerate()
+            .filter(|(_, &s)| s > tol)
+            .map(|(idx, _)| u.column(idx).clone_owned())
+            .collect::<Vec<_>>();
+        Ok(DMatrix::from_fn(n_rows, columns.len(), |r, c| {
+            columns[c][r]
+        }))
+    }
+
+    /// Calculates the Moore-Penrose pseudo-inverse of a matrix using its singular value
+    /// decomposition. Singular values at or below `max(rows, columns) * epsilon * largest singular
+    /// value` are treated as zero, so this also works for rank-deficient matrices.
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [2, 4]];
+    /// assert_approx_eq(flatten(pinv(x)), [0.04, 0.08, 0.08, 0.16]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 2, 3],
+    ///          [4, 5, 6]];
+    /// let p = pinv(x);
+    /// assert_eq(size(p), [3, 2]);
+    /// assert_approx_eq(flatten(mtimes(x, p)), [1.0, 0.0, 0.0, 1.0]);
+    /// ```
+    /// ```typescript
+    /// let result = "";
+    /// try { pinv([[0.0 / 0.0, 1.0], [2.0, 3.0]]); } catch { result = "not finite"; }
+    /// assert_eq(result, "not finite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "pinv", return_raw, pure)]
+    pub fn pinv(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            pseudo_inverse(dm, None).map(omatrix_to_vec_dynamic)
+        })
+    }
+
+    /// Calculates the Moore-Penrose pseudo-inverse of a matrix, treating singular values at or
+    /// below `tol` as zero.
+    /// ```typescript
+    /// let x = [[1.0, 0.0],
+    ///          [0.0, 1e-10]];
+    /// assert_eq(pinv(x, 1e-6), [[1.0, 0.0], [0.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "pinv", return_raw, pure)]
+    pub fn pinv_with_tol(matrix: &mut Array, tol: FLOAT) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            pseudo_inverse(dm, Some(tol)).map(omatrix_to_vec_dynamic)
+        })
+    }
+
+    /// Calculates the Moore-Penrose pseudo-inverse of a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 2],
+    ///                    [2, 4]]);
+    /// assert_approx_eq(flatten(pinv(x)), [0.04, 0.08, 0.08, 0.16]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "pinv", return_raw, pure)]
+    pub fn pinv_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        pseudo_inverse(matrix.0.clone(), None).map(Matrix)
+    }
+
+    /// Calculates the Moore-Penrose pseudo-inverse of a `Matrix`, treating singular values at or
+    /// below `tol` as zero.
+    /// ```typescript
+    /// let x = to_matrix([[1.0, 0.0],
+    ///                    [0.0, 1e-10]]);
+    /// assert_eq(to_array(pinv(x, 1e-6)), [[1.0, 0.0], [0.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "pinv", return_raw, pure)]
+    pub fn pinv_with_tol_native(
+        matrix: &mut Matrix,
+        tol: FLOAT,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        pseudo_inverse(matrix.0.clone(), Some(tol)).map(Matrix)
+    }
+
+    /// Calculates an orthonormal basis for the null space of a matrix. The basis vectors are the
+    /// columns of the output, which has no columns if the matrix has full column rank. Because
+    /// `null` is a reserved word in Rhai scripts, this is called as `null_space` from scripts,
+    /// while the name `null` remains available to host code through `Engine::call_fn`.
+    /// ```typescript
+    /// let x = [[1, 1],
+    ///          [1, 1]];
+    /// let n = null_space(x);
+    /// assert_eq(size(n), [2, 1]);
+    /// assert_approx_eq(flatten(mtimes(x, n)), [0.0, 0.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 2, 3]];
+    /// let n = null_space(x);
+    /// assert_eq(size(n), [3, 2]);
+    /// assert_approx_eq(flatten(mtimes(transpose(n), n)), [1.0, 0.0, 0.0, 1.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "null_space", name = "null", return_raw, pure)]
+    pub fn null(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| null_space(dm).map(omatrix_to_vec_dynamic))
+    }
+
+    /// Calculates an orthonormal basis for the null space of a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 1],
+    ///                    [1, 1]]);
+    /// assert_eq(size(null_space(x)), [2, 1]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "null_space", name = "null", return_raw, pure)]
+    pub fn null_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        null_space(matrix.0.clone()).map(Matrix)
+    }
+
+    /// Calculates an orthonormal basis for the range (column space) of a matrix. The basis
+    /// vectors are the columns of the output, and there are as many as the rank of the matrix.
+    /// ```typescript
+    /// let x = [[1, 1],
+    ///          [1, 1]];
+    /// let o = orth(x);
+    /// assert_eq(size(o), [2, 1]);
+    /// assert_approx_eq(flatten(o).map(|v| abs(v)), [sqrt(0.5), sqrt(0.5)]);
+    /// ```
+    /// ```typescript
+    /// let o = orth(eye(3));
+    /// assert_eq(size(o), [3, 3]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "orth", return_raw, pure)]
+    pub fn orth(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            range_space(dm).map(omatrix_to_vec_dynamic)
         })
     }
 
+    /// Calculates an orthonormal basis for the range (column space) of a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 1],
+    ///                    [1, 1]]);
+    /// assert_eq(size(orth(x)), [2, 1]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "orth", return_raw, pure)]
+    pub fn orth_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        range_space(matrix.0.clone()).map(Matrix)
+    }
+
+    /// Computes the QR decomposition of a matrix, wrapping each matrix in the output with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn qr_to_map(dm: DMatrix<FLOAT>, wrap: fn(DMatrix<FLOAT>) -> Dynamic) -> Map {
+        let qr = nalgebralib::linalg::QR::new(dm);
+
+        let mut result = BTreeMap::new();
+        let mut qid = smartstring::SmartString::new();
+        qid.push_str("q");
+        result.insert(qid, wrap(qr.q()));
+
+        let mut rid = smartstring::SmartString::new();
+        rid.push_str("r");
+        result.insert(rid, wrap(qr.r()));
+
+        result
+    }
+
     /// Calculates the QR decomposition of a matrix
     /// ```typescript
     /// let matrix = eye(5);
@@ -216,29 +1835,49 @@ pub mod matrix_functions {
     #[cfg(feature = "nalgebra")]
     #[rhai_fn(name = "qr", return_raw, pure)]
     pub fn qr_decomp(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
-        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
-            let dm = DMatrix::from_fn(matrix_as_vec.len(), matrix_as_vec[0].len(), |i, j| {
-                if matrix_as_vec[0][0].is::<FLOAT>() {
-                    matrix_as_vec[i][j].as_float().unwrap()
-                } else {
-                    matrix_as_vec[i][j].as_int().unwrap() as FLOAT
-                }
-            });
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| Ok(qr_to_map(dm, to_dynamic_array)))
+    }
 
-            // Try ot invert
-            let qr = nalgebralib::linalg::QR::new(dm);
+    /// Calculates the QR decomposition of a `Matrix`, returning `q` and `r` as a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix(eye(5));
+    /// let qr_results = qr(matrix);
+    /// assert_eq(to_array(qr_results.q), eye(5));
+    /// assert_eq(to_array(qr_results.r), eye(5));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "qr", pure)]
+    pub fn qr_decomp_native(matrix: &mut Matrix) -> Map {
+        qr_to_map(matrix.0.clone(), to_dynamic_matrix)
+    }
 
-            let mut result = BTreeMap::new();
-            let mut qid = smartstring::SmartString::new();
-            qid.push_str("q");
-            result.insert(qid, Dynamic::from_array(omatrix_to_vec_dynamic(qr.q())));
+    /// Computes the Hessenberg decomposition of a matrix, wrapping each matrix in the output
+    /// with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn hessenberg_to_map(
+        dm: DMatrix<FLOAT>,
+        wrap: fn(DMatrix<FLOAT>) -> Dynamic,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        if !dm.is_square() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The input must be a square matrix".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
 
-            let mut rid = smartstring::SmartString::new();
-            rid.push_str("r");
-            result.insert(rid, Dynamic::from_array(omatrix_to_vec_dynamic(qr.r())));
+        let h = nalgebralib::linalg::Hessenberg::new(dm);
 
-            Ok(result)
-        })
+        let mut result = BTreeMap::new();
+        let mut hid = smartstring::SmartString::new();
+        hid.push_str("h");
+        result.insert(hid, wrap(h.h()));
+
+        let mut qid = smartstring::SmartString::new();
+        qid.push_str("q");
+        result.insert(qid, wrap(h.q()));
+
+        Ok(result)
     }
 
     /// Calculates the QR decomposition of a matrix
@@ -250,31 +1889,219 @@ pub mod matrix_functions {
     #[cfg(feature = "nalgebra")]
     #[rhai_fn(name = "hessenberg", return_raw, pure)]
     pub fn hessenberg(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
-        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
-            let dm = DMatrix::from_fn(matrix_as_vec.len(), matrix_as_vec[0].len(), |i, j| {
-                if matrix_as_vec[0][0].is::<FLOAT>() {
-                    matrix_as_vec[i][j].as_float().unwrap()
-                } else {
-                    matrix_as_vec[i][j].as_int().unwrap() as FLOAT
-                }
-            });
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| hessenberg_to_map(dm, to_dynamic_array))
+    }
+
+    /// Calculates the Hessenberg decomposition of a `Matrix`, returning `h` and `q` as a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix(eye(5));
+    /// let h_results = hessenberg(matrix);
+    /// assert_eq(to_array(h_results.h), eye(5));
+    /// assert_eq(to_array(h_results.q), eye(5));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "hessenberg", return_raw, pure)]
+    pub fn hessenberg_native(matrix: &mut Matrix) -> Result<Map, Box<EvalAltResult>> {
+        hessenberg_to_map(matrix.0.clone(), to_dynamic_matrix)
+    }
+
+    /// Computes the LU decomposition with partial pivoting of a matrix, wrapping each matrix in
+    /// the output with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn lu_to_map(
+        dm: DMatrix<FLOAT>,
+        wrap: fn(DMatrix<FLOAT>) -> Dynamic,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let n_rows = dm.nrows();
+        let lu = nalgebralib::linalg::LU::new(finite_or_error(dm)?);
+
+        let mut p = DMatrix::<FLOAT>::identity(n_rows, n_rows);
+        lu.p().permute_rows(&mut p);
+
+        let mut result = BTreeMap::new();
+        let mut lid = smartstring::SmartString::new();
+        lid.push_str("l");
+        result.insert(lid, wrap(lu.l()));
+
+        let mut uid = smartstring::SmartString::new();
+        uid.push_str("u");
+        result.insert(uid, wrap(lu.u()));
+
+        let mut pid = smartstring::SmartString::new();
+        pid.push_str("p");
+        result.insert(pid, wrap(p));
+
+        Ok(result)
+    }
+
+    /// Calculates the LU decomposition of a matrix with partial pivoting, such that
+    /// `mtimes(p, matrix) == mtimes(l, u)`. The result is a map with the unit lower triangular
+    /// factor `l`, the upper triangular factor `u`, and the permutation matrix `p`.
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [3, 4]];
+    /// let lu_results = lu(x);
+    /// assert_eq(lu_results.p, [[0.0, 1.0], [1.0, 0.0]]);
+    /// assert_approx_eq(flatten(lu_results.l), [1.0, 0.0, 1.0/3.0, 1.0]);
+    /// assert_approx_eq(flatten(lu_results.u), [3.0, 4.0, 0.0, 2.0/3.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [[2, 1, 1],
+    ///          [4, 3, 3],
+    ///          [8, 7, 9]];
+    /// let lu_results = lu(x);
+    /// assert_approx_eq(flatten(mtimes(lu_results.p, x)), flatten(mtimes(lu_results.l, lu_results.u)));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "lu", return_raw, pure)]
+    pub fn lu_decomp(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| lu_to_map(dm, to_dynamic_array))
+    }
+
+    /// Calculates the LU decomposition of a `Matrix`, returning `l`, `u`, and `p` as a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[1, 2],
+    ///                    [3, 4]]);
+    /// let lu_results = lu(x);
+    /// assert_eq(to_array(lu_results.p), [[0.0, 1.0], [1.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "lu", return_raw, pure)]
+    pub fn lu_decomp_native(matrix: &mut Matrix) -> Result<Map, Box<EvalAltResult>> {
+        lu_to_map(matrix.0.clone(), to_dynamic_matrix)
+    }
+
+    /// Computes the lower triangular Cholesky factor of a symmetric positive-definite matrix.
+    #[cfg(feature = "nalgebra")]
+    fn cholesky_lower(dm: DMatrix<FLOAT>) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        if_square_do(finite_or_error(dm)?, |dm| {
+            if dm != dm.transpose() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The input to the Cholesky decomposition must be symmetric".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            match nalgebralib::linalg::Cholesky::new(dm) {
+                Some(chol) => Ok(chol.l()),
+                None => Err(EvalAltResult::ErrorArithmetic(
+                    "Matrix is not positive definite".to_string(),
+                    Position::NONE,
+                )
+                .into()),
+            }
+        })
+    }
+
+    /// Calculates the Cholesky decomposition of a symmetric positive-definite matrix, returning
+    /// the lower triangular factor `l` such that `mtimes(l, transpose(l)) == matrix`. Throws an
+    /// error if the matrix is not symmetric positive-definite.
+    /// ```typescript
+    /// let x = [[4, 2],
+    ///          [2, 3]];
+    /// let l = chol(x);
+    /// assert_approx_eq(flatten(l), [2.0, 0.0, 1.0, sqrt(2.0)]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [2, 1]];
+    /// let result = "factored";
+    /// try { chol(x); } catch { result = "not positive definite"; }
+    /// assert_eq(result, "not positive definite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "chol", return_raw, pure)]
+    pub fn chol(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| {
+            Ok(omatrix_to_vec_dynamic(cholesky_lower(dm)?))
+        })
+    }
+
+    /// Calculates the Cholesky decomposition of a symmetric positive-definite `Matrix`,
+    /// returning the lower triangular factor as a `Matrix`.
+    /// ```typescript
+    /// let x = to_matrix([[4, 2],
+    ///                    [2, 3]]);
+    /// let l = chol(x);
+    /// assert_approx_eq(flatten(l), [2.0, 0.0, 1.0, sqrt(2.0)]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "chol", return_raw, pure)]
+    pub fn chol_native(matrix: &mut Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        cholesky_lower(matrix.0.clone()).map(Matrix)
+    }
 
-            // Try ot invert
-            let h = nalgebralib::linalg::Hessenberg::new(dm);
+    /// Computes the real Schur decomposition of a matrix, wrapping each matrix in the output
+    /// with `wrap`.
+    #[cfg(feature = "nalgebra")]
+    fn schur_to_map(
+        dm: DMatrix<FLOAT>,
+        wrap: fn(DMatrix<FLOAT>) -> Dynamic,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        if_square_do(finite_or_error(dm)?, |dm| {
+            let max_iterations = crate::schur_iterations(dm.nrows());
+            let (q, t) =
+                match nalgebralib::linalg::Schur::try_new(dm, FLOAT::EPSILON, max_iterations) {
+                    Some(schur) => schur.unpack(),
+                    None => {
+                        return Err(EvalAltResult::ErrorArithmetic(
+                            "The Schur decomposition failed to converge".to_string(),
+                            Position::NONE,
+                        )
+                        .into())
+                    }
+                };
 
             let mut result = BTreeMap::new();
-            let mut hid = smartstring::SmartString::new();
-            hid.push_str("h");
-            result.insert(hid, Dynamic::from_array(omatrix_to_vec_dynamic(h.h())));
+            let mut tid = smartstring::SmartString::new();
+            tid.push_str("t");
+            result.insert(tid, wrap(t));
 
             let mut qid = smartstring::SmartString::new();
             qid.push_str("q");
-            result.insert(qid, Dynamic::from_array(omatrix_to_vec_dynamic(h.q())));
+            result.insert(qid, wrap(q));
 
             Ok(result)
         })
     }
 
+    /// Calculates the real Schur decomposition of a square matrix, such that
+    /// `matrix == mtimes(mtimes(q, t), transpose(q))`. The result is a map with the
+    /// quasi-upper-triangular matrix `t` and the orthogonal matrix `q`.
+    /// ```typescript
+    /// let x = [[1, 2],
+    ///          [3, 4]];
+    /// let s = schur(x);
+    /// assert_approx_eq(flatten(mtimes(mtimes(s.q, s.t), transpose(s.q))), [1.0, 2.0, 3.0, 4.0]);
+    /// assert_approx_eq(s.t[1][0], 0.0);
+    /// ```
+    /// ```typescript
+    /// let s = schur(eye(3));
+    /// assert_eq(s, #{"t": eye(3), "q": eye(3)});
+    /// ```
+    /// ```typescript
+    /// let result = "";
+    /// try { schur([[0.0 / 0.0, 1.0], [2.0, 3.0]]); } catch { result = "not finite"; }
+    /// assert_eq(result, "not finite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "schur", return_raw, pure)]
+    pub fn schur(matrix: &mut Array) -> Result<Map, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(matrix, |dm| schur_to_map(dm, to_dynamic_array))
+    }
+
+    /// Calculates the real Schur decomposition of a `Matrix`, returning `t` and `q` as a `Matrix`.
+    /// ```typescript
+    /// let s = schur(to_matrix(eye(3)));
+    /// assert_eq(to_array(s.t), eye(3));
+    /// assert_eq(to_array(s.q), eye(3));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "schur", return_raw, pure)]
+    pub fn schur_native(matrix: &mut Matrix) -> Result<Map, Box<EvalAltResult>> {
+        schur_to_map(matrix.0.clone(), to_dynamic_matrix)
+    }
+
     /// Transposes a matrix.
     /// ```typescript
     /// let row = [[1, 2, 3, 4]];
@@ -304,6 +2131,18 @@ pub mod matrix_functions {
         })
     }
 
+    /// Transposes a `Matrix`.
+    /// ```typescript
+    /// let row = to_matrix([[1, 2, 3, 4]]);
+    /// let column = transpose(row);
+    /// assert_eq(size(column), [4, 1]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "transpose", pure)]
+    pub fn transpose_native(matrix: &mut Matrix) -> Matrix {
+        Matrix::from(matrix.0.transpose())
+    }
+
     /// Returns an array indicating the size of the matrix along each dimension, passed by reference.
     /// ```typescript
     /// let matrix = ones(3, 5);
@@ -319,7 +2158,7 @@ pub mod matrix_functions {
 
         let mut shape = vec![Dynamic::from_int(new_matrix.len() as INT)];
         loop {
-            if new_matrix[0].is_array() {
+            if !new_matrix.is_empty() && new_matrix[0].is_array() {
                 new_matrix = new_matrix[0].clone().into_array().unwrap();
                 shape.push(Dynamic::from_int(new_matrix.len() as INT));
             } else {
@@ -330,6 +2169,20 @@ pub mod matrix_functions {
         shape
     }
 
+    /// Returns an array indicating the number of rows and columns of a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix(ones(3, 5));
+    /// assert_eq(size(matrix), [3, 5]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "size", pure)]
+    pub fn matrix_size_native(matrix: &mut Matrix) -> Array {
+        vec![
+            Dynamic::from_int(matrix.0.nrows() as INT),
+            Dynamic::from_int(matrix.0.ncols() as INT),
+        ]
+    }
+
     /// Return the number of dimensions in matrix, passed by reference.
     /// ```typescript
     /// let matrix = ones(4, 6);
@@ -341,6 +2194,17 @@ pub mod matrix_functions {
         matrix_size_by_reference(matrix).len() as INT
     }
 
+    /// Return the number of dimensions in a `Matrix`, which is always 2.
+    /// ```typescript
+    /// let matrix = to_matrix(ones(4, 6));
+    /// assert_eq(ndims(matrix), 2);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "ndims", pure)]
+    pub fn ndims_native(_matrix: &mut Matrix) -> INT {
+        2
+    }
+
     /// Returns the number of elements in a matrix, passed by reference.
     /// ```typescript
     /// let matrix = ones(4, 6);
@@ -357,6 +2221,17 @@ pub mod matrix_functions {
         flatten(matrix).len() as INT
     }
 
+    /// Returns the number of elements in a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix(ones(4, 6));
+    /// assert_eq(numel(matrix), 24);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "numel", pure)]
+    pub fn numel_native(matrix: &mut Matrix) -> INT {
+        matrix.0.len() as INT
+    }
+
     /// Returns the number of non-zero elements in a matrix, passed by reference.
     /// ```typescript
     /// let matrix = ones(4, 6);
@@ -376,6 +2251,18 @@ pub mod matrix_functions {
             .count() as INT
     }
 
+    /// Returns the number of non-zero elements in a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 0],
+    ///                         [-2, 3]]);
+    /// assert_eq(nnz(matrix), 3);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "nnz", pure)]
+    pub fn nnz_native(matrix: &mut Matrix) -> INT {
+        matrix.0.iter().filter(|&n| *n != 0.0).count() as INT
+    }
+
     #[cfg(all(feature = "io"))]
     pub mod read_write {
         use polars::prelude::{CsvReadOptions, DataType, SerReader};
@@ -750,58 +2637,912 @@ pub mod matrix_functions {
                         format!(""),
                         Position::NONE,
                     )
-                    .into())
+                    .into())
+                }
+            },
+        )
+    }
+
+    /// Returns the identity matrix, specifying the number of rows and columns separately.
+    /// ```typescript
+    /// let matrix = eye(3, 4);
+    /// assert_eq(matrix, [[1.0, 0.0, 0.0, 0.0],
+    ///                    [0.0, 1.0, 0.0, 0.0],
+    ///                    [0.0, 0.0, 1.0, 0.0]]);
+    /// ```
+    #[rhai_fn(name = "eye")]
+    pub fn eye_double_input(nx: INT, ny: INT) -> Array {
+        let mut output = vec![];
+        for i in 0..nx {
+            let mut row = vec![];
+            for j in 0..ny {
+                if i == j {
+                    row.push(Dynamic::FLOAT_ONE);
+                } else {
+                    row.push(Dynamic::FLOAT_ZERO);
+                }
+            }
+            output.push(Dynamic::from_array(row))
+        }
+        output
+    }
+
+    /// Returns a Toeplitz matrix, which has constant diagonals, with `c` as its first column and
+    /// `r` as its first row. If the first elements of `c` and `r` differ, the one from `c` is used.
+    /// ```typescript
+    /// let t = toeplitz([1, 2, 3], [1, 4, 5, 6]);
+    /// assert_eq(t, [[1, 4, 5, 6],
+    ///               [2, 1, 4, 5],
+    ///               [3, 2, 1, 4]]);
+    /// ```
+    #[rhai_fn(name = "toeplitz", return_raw)]
+    pub fn toeplitz(c: Array, r: Array) -> Result<Array, Box<EvalAltResult>> {
+        if_list_do(&mut c.clone(), |c| {
+            if_list_do(&mut r.clone(), |r| {
+                Ok((0..c.len())
+                    .map(|i| {
+                        Dynamic::from_array(
+                            (0..r.len())
+                                .map(|j| {
+                                    if i >= j {
+                                        c[i - j].clone()
+                                    } else {
+                                        r[j - i].clone()
+                                    }
+                                })
+                                .collect(),
+                        )
+                    })
+                    .collect())
+            })
+        })
+    }
+
+    /// Returns a symmetric Toeplitz matrix with `c` as its first row and column.
+    /// ```typescript
+    /// let t = toeplitz([1, 2, 3]);
+    /// assert_eq(t, [[1, 2, 3],
+    ///               [2, 1, 2],
+    ///               [3, 2, 1]]);
+    /// ```
+    #[rhai_fn(name = "toeplitz", return_raw)]
+    pub fn toeplitz_symmetric(c: Array) -> Result<Array, Box<EvalAltResult>> {
+        toeplitz(c.clone(), c)
+    }
+
+    /// Returns a Hankel matrix, which has constant anti-diagonals, with `c` as its first column
+    /// and `r` as its last row. If the last element of `c` and the first element of `r` differ,
+    /// the one from `c` is used.
+    /// ```typescript
+    /// let h = hankel([1, 2, 3], [3, 4, 5, 6]);
+    /// assert_eq(h, [[1, 2, 3, 4],
+    ///               [2, 3, 4, 5],
+    ///               [3, 4, 5, 6]]);
+    /// ```
+    #[rhai_fn(name = "hankel", return_raw)]
+    pub fn hankel(c: Array, r: Array) -> Result<Array, Box<EvalAltResult>> {
+        if_list_do(&mut c.clone(), |c| {
+            if_list_do(&mut r.clone(), |r| {
+                let m = c.len();
+                Ok((0..m)
+                    .map(|i| {
+                        Dynamic::from_array(
+                            (0..r.len())
+                                .map(|j| {
+                                    if i + j < m {
+                                        c[i + j].clone()
+                                    } else {
+                                        r[i + j + 1 - m].clone()
+                                    }
+                                })
+                                .collect(),
+                        )
+                    })
+                    .collect())
+            })
+        })
+    }
+
+    /// Returns a square Hankel matrix with `c` as its first column and zeros below the
+    /// anti-diagonal.
+    /// ```typescript
+    /// let h = hankel([1, 2, 3]);
+    /// assert_eq(h, [[1, 2, 3],
+    ///               [2, 3, 0],
+    ///               [3, 0, 0]]);
+    /// ```
+    #[rhai_fn(name = "hankel", return_raw)]
+    pub fn hankel_square(c: Array) -> Result<Array, Box<EvalAltResult>> {
+        let zero = if c.iter().all(|x| x.is_int()) {
+            Dynamic::ZERO
+        } else {
+            Dynamic::FLOAT_ZERO
+        };
+        let r = vec![zero; c.len()];
+        hankel(c, r)
+    }
+
+    /// Returns a Vandermonde matrix with `n` columns, whose columns are the powers of `x` from
+    /// `n - 1` down to 0.
+    /// ```typescript
+    /// let v = vander([1, 2, 3], 2);
+    /// assert_eq(v, [[1, 1],
+    ///               [2, 1],
+    ///               [3, 1]]);
+    /// ```
+    #[rhai_fn(name = "vander", return_raw)]
+    pub fn vander(x: Array, n: INT) -> Result<Array, Box<EvalAltResult>> {
+        if_list_do(&mut x.clone(), |x| {
+            x.iter()
+                .map(|xi| {
+                    (0..n)
+                        .rev()
+                        .map(|k| {
+                            crate::arithmetic_functions::power(xi.clone(), Dynamic::from_int(k))
+                        })
+                        .collect::<Result<Array, Box<EvalAltResult>>>()
+                        .map(Dynamic::from_array)
+                })
+                .collect()
+        })
+    }
+
+    /// Returns a square Vandermonde matrix, whose columns are the powers of `x` from
+    /// `len(x) - 1` down to 0.
+    /// ```typescript
+    /// let v = vander([1, 2, 3]);
+    /// assert_eq(v, [[1, 1, 1],
+    ///               [4, 2, 1],
+    ///               [9, 3, 1]]);
+    /// ```
+    /// ```typescript
+    /// let v = vander([0.5, 2.0]);
+    /// assert_eq(v, [[0.5, 1.0],
+    ///               [2.0, 1.0]]);
+    /// ```
+    #[rhai_fn(name = "vander", return_raw)]
+    pub fn vander_square(x: Array) -> Result<Array, Box<EvalAltResult>> {
+        let n = x.len() as INT;
+        vander(x, n)
+    }
+
+    /// Returns an `n x n` magic square of the integers from 1 to `n^2`, whose rows, columns, and
+    /// diagonals all have the same sum. The squares are the same as those produced by MATLAB.
+    /// ```typescript
+    /// let m = magic(4);
+    /// assert_eq(m, [[16,  2,  3, 13],
+    ///               [ 5, 11, 10,  8],
+    ///               [ 9,  7,  6, 12],
+    ///               [ 4, 14, 15,  1]]);
+    /// ```
+    /// ```typescript
+    /// let m = magic(6);
+    /// assert_eq(sum(m[0]), 111);
+    /// assert_eq(sum(diag(m)), 111);
+    /// assert_eq(sum(diag(fliplr(m))), 111);
+    /// ```
+    #[rhai_fn(name = "magic")]
+    pub fn magic(n: INT) -> Array {
+        let n = n.max(0) as usize;
+        let square = |f: &dyn Fn(usize, usize) -> INT| -> Vec<Vec<INT>> {
+            (0..n).map(|i| (0..n).map(|j| f(i, j)).collect()).collect()
+        };
+        let m = match n % 4 {
+            _ if n == 0 => vec![],
+            // Odd order: the de la Loubère method
+            1 | 3 => square(&|i, j| {
+                let a = (i as INT + j as INT + 2 - (n as INT + 3) / 2).rem_euclid(n as INT);
+                let b = (i as INT + 2 * j as INT + 1).rem_euclid(n as INT);
+                n as INT * a + b + 1
+            }),
+            // Doubly even order
+            0 => square(&|i, j| {
+                let value = (i * n + j + 1) as INT;
+                if ((i + 1) % 4) / 2 == ((j + 1) % 4) / 2 {
+                    (n * n + 1) as INT - value
+                } else {
+                    value
+                }
+            }),
+            // Singly even order: the LUX method built from four odd-order squares
+            _ => {
+                let p = n / 2;
+                let sub = magic(p as INT)
+                    .into_iter()
+                    .map(|row| {
+                        row.into_array()
+                            .unwrap()
+                            .into_iter()
+                            .map(|x| x.as_int().unwrap())
+                            .collect::<Vec<INT>>()
+                    })
+                    .collect::<Vec<Vec<INT>>>();
+                let offsets = [[0, 2], [3, 1]];
+                let mut m =
+                    square(&|i, j| sub[i % p][j % p] + offsets[i / p][j / p] * (p * p) as INT);
+                if n > 2 {
+                    let k = (n - 2) / 4;
+                    let (top, bottom) = m.split_at_mut(p);
+                    for (top_row, bottom_row) in top.iter_mut().zip(bottom.iter_mut()) {
+                        for j in (0..k).chain((n - k + 1)..n) {
+                            std::mem::swap(&mut top_row[j], &mut bottom_row[j]);
+                        }
+                    }
+                    for j in [0, k] {
+                        std::mem::swap(&mut top[k][j], &mut bottom[k][j]);
+                    }
+                }
+                m
+            }
+        };
+        m.into_iter()
+            .map(|row| Dynamic::from_array(row.into_iter().map(Dynamic::from_int).collect()))
+            .collect()
+    }
+
+    /// Returns the `n x n` Hilbert matrix, whose elements are `1 / (i + j + 1)` for zero-based
+    /// row and column indices `i` and `j`.
+    /// ```typescript
+    /// let h = hilb(3);
+    /// assert_eq(h, [[1.0,     1.0/2.0, 1.0/3.0],
+    ///               [1.0/2.0, 1.0/3.0, 1.0/4.0],
+    ///               [1.0/3.0, 1.0/4.0, 1.0/5.0]]);
+    /// ```
+    #[rhai_fn(name = "hilb")]
+    pub fn hilb(n: INT) -> Array {
+        (0..n)
+            .map(|i| {
+                Dynamic::from_array(
+                    (0..n)
+                        .map(|j| Dynamic::from_float(1.0 / ((i + j + 1) as FLOAT)))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the contents of a multidimensional array as a 1-D array.
+    /// ```typescript
+    /// let matrix = ones(3, 5);
+    /// let flat = flatten(matrix);
+    /// assert_eq(len(flat), 15);
+    /// ```
+    /// ```typescript
+    /// let matrix = [[1.0, 2.0, 3.0], [1.0]];
+    /// let flat = flatten(matrix);
+    /// assert_eq(len(flat), 4);
+    /// ```
+    #[rhai_fn(name = "flatten", pure)]
+    pub fn flatten(matrix: &mut Array) -> Array {
+        let mut flat: Array = vec![];
+        for el in matrix {
+            if el.is_array() {
+                flat.extend(flatten(&mut el.clone().into_array().unwrap()))
+            } else {
+                flat.push(el.clone());
+            }
+        }
+        flat
+    }
+
+    /// Returns the contents of a `Matrix` as a 1-D array, row by row.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2],
+    ///                         [3, 4]]);
+    /// assert_eq(flatten(matrix), [1.0, 2.0, 3.0, 4.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "flatten", pure)]
+    pub fn flatten_native(matrix: &mut Matrix) -> Array {
+        matrix
+            .0
+            .transpose()
+            .iter()
+            .map(|el| Dynamic::from_float(*el))
+            .collect()
+    }
+
+    /// Rearranges the elements of an array into a new shape, given as an array with the length of
+    /// each dimension. Elements are taken and placed in row-major order, and the number of elements
+    /// must not change.
+    /// ```typescript
+    /// let matrix = reshape([1, 2, 3, 4, 5, 6], [2, 3]);
+    /// assert_eq(matrix, [[1, 2, 3],
+    ///                    [4, 5, 6]]);
+    /// ```
+    /// ```typescript
+    /// let arr = reshape([[1, 2], [3, 4], [5, 6], [7, 8]], [2, 2, 2]);
+    /// assert_eq(arr, [[[1, 2], [3, 4]],
+    ///                 [[5, 6], [7, 8]]]);
+    /// ```
+    #[rhai_fn(name = "reshape", return_raw, pure)]
+    pub fn reshape(matrix: &mut Array, shape: Array) -> Result<Array, Box<EvalAltResult>> {
+        let flat = flatten(matrix);
+        let new_shape = shape
+            .iter()
+            .map(|n| match n.as_int() {
+                Ok(n) if n >= 0 => Ok(n as usize),
+                _ => Err(EvalAltResult::ErrorArithmetic(
+                    "The new shape must only contain nonnegative INT values".to_string(),
+                    Position::NONE,
+                )
+                .into()),
+            })
+            .collect::<Result<Vec<usize>, Box<EvalAltResult>>>()?;
+        if new_shape.is_empty() || new_shape.iter().product::<usize>() != flat.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Cannot reshape an array with {} elements into shape {new_shape:?}",
+                    flat.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        Ok(flat_to_nested_array(&flat, &new_shape))
+    }
+
+    /// Rearranges the dimensions of an array in the order given (counting from 1), so that
+    /// dimension `i` of the result is dimension `order[i]` of the input. The order may include
+    /// trailing singleton dimensions beyond those of the input.
+    /// ```typescript
+    /// let matrix = permute([[1, 2, 3],
+    ///                       [4, 5, 6]], [2, 1]);
+    /// assert_eq(matrix, [[1, 4],
+    ///                    [2, 5],
+    ///                    [3, 6]]);
+    /// ```
+    /// ```typescript
+    /// let arr = permute([[[1, 2], [3, 4]],
+    ///                    [[5, 6], [7, 8]]], [3, 1, 2]);
+    /// assert_eq(arr, [[[1, 3], [5, 7]],
+    ///                 [[2, 4], [6, 8]]]);
+    /// ```
+    /// ```typescript
+    /// let row = permute([1, 2, 3], [2, 1]);
+    /// assert_eq(row, [[1, 2, 3]]);
+    /// ```
+    #[rhai_fn(name = "permute", return_raw, pure)]
+    pub fn permute(matrix: &mut Array, order: Array) -> Result<Array, Box<EvalAltResult>> {
+        let mut shape = if_numeric_array_get_shape(matrix)?;
+        let ndims = shape.len();
+        let k = order.len();
+
+        // The order must be a permutation of 1..=k, covering every dimension of the input
+        let mut perm = vec![];
+        for d in &order {
+            match d.as_int() {
+                Ok(d) if d >= 1 && d as usize <= k && !perm.contains(&(d as usize - 1)) => {
+                    perm.push(d as usize - 1)
+                }
+                _ => perm.clear(),
+            }
+        }
+        if perm.len() != k || k < ndims {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The order must be a permutation of the dimensions 1 to {}",
+                    k.max(ndims)
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        shape.resize(k, 1);
+
+        let flat = flatten(matrix);
+        let mut new_shape = perm.iter().map(|&d| shape[d]).collect::<Vec<usize>>();
+        let mut strides = vec![1; k];
+        for d in (0..k - 1).rev() {
+            strides[d] = strides[d + 1] * shape[d + 1];
+        }
+
+        let mut out = Vec::with_capacity(flat.len());
+        let mut index = vec![0; k];
+        for _ in 0..flat.len() {
+            out.push(flat[(0..k).map(|i| index[i] * strides[perm[i]]).sum::<usize>()].clone());
+            // Step through the output in row-major order
+            for i in (0..k).rev() {
+                index[i] += 1;
+                if index[i] < new_shape[i] {
+                    break;
+                }
+                index[i] = 0;
+            }
+        }
+
+        while new_shape.len() > ndims && new_shape.last() == Some(&1) {
+            new_shape.pop();
+        }
+        Ok(flat_to_nested_array(&out, &new_shape))
+    }
+
+    /// Removes all dimensions of length 1 from an array. An array with a single element becomes a
+    /// 1-D array with one element.
+    /// ```typescript
+    /// let arr = squeeze([[[1], [2], [3]]]);
+    /// assert_eq(arr, [1, 2, 3]);
+    /// ```
+    /// ```typescript
+    /// let matrix = squeeze([[[1, 2]], [[3, 4]]]);
+    /// assert_eq(matrix, [[1, 2], [3, 4]]);
+    /// ```
+    #[rhai_fn(name = "squeeze", return_raw, pure)]
+    pub fn squeeze(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        let mut shape = if_numeric_array_get_shape(matrix)?;
+        shape.retain(|&n| n != 1);
+        if shape.is_empty() {
+            shape.push(1);
+        }
+        Ok(flat_to_nested_array(&flatten(matrix), &shape))
+    }
+
+    /// Concatenates an array of arrays along dimension `dim` (counting from 1). The arrays must
+    /// have the same length along every other dimension.
+    /// ```typescript
+    /// let a = [[1, 2], [3, 4]];
+    /// let b = [[5, 6], [7, 8]];
+    /// assert_eq(cat(1, [a, b]), [[1, 2], [3, 4], [5, 6], [7, 8]]);
+    /// assert_eq(cat(2, [a, b]), [[1, 2, 5, 6], [3, 4, 7, 8]]);
+    /// assert_eq(cat(3, [a, b]), [[[1, 5], [2, 6]], [[3, 7], [4, 8]]]);
+    /// ```
+    #[rhai_fn(name = "cat", return_raw)]
+    pub fn cat(dim: INT, arrays: Array) -> Result<Array, Box<EvalAltResult>> {
+        if arrays.is_empty() || arrays.iter().any(|a| !a.is_array()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The input must be an array of arrays".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let mut pieces = arrays
+            .into_iter()
+            .map(|a| a.into_array().unwrap())
+            .collect::<Vec<Array>>();
+
+        let mut shapes = pieces
+            .iter()
+            .map(if_numeric_array_get_shape)
+            .collect::<Result<Vec<Vec<usize>>, Box<EvalAltResult>>>()?;
+        let ndims = shapes.iter().map(|s| s.len()).max().unwrap();
+        let d = crate::resolve_dim(&shapes[0], Some(dim))?;
+        if d > ndims {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The dimension must be at most one more than the number of dimensions of the arrays"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        for s in shapes.iter_mut() {
+            s.resize(ndims.max(d + 1), 1);
+        }
+
+        let mut new_shape = shapes[0].clone();
+        for s in &shapes[1..] {
+            if (0..new_shape.len()).any(|i| i != d && s[i] != new_shape[i]) {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The arrays must have the same length along every dimension except the one being concatenated".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            new_shape[d] += s[d];
+        }
+
+        // Interleave contiguous blocks of each piece
+        let outer = new_shape[..d].iter().product::<usize>();
+        let inner = new_shape[d + 1..].iter().product::<usize>();
+        let flats = pieces.iter_mut().map(flatten).collect::<Vec<Array>>();
+        let mut out = vec![];
+        for o in 0..outer {
+            for (flat, s) in flats.iter().zip(&shapes) {
+                let block = s[d] * inner;
+                out.extend_from_slice(&flat[o * block..(o + 1) * block]);
+            }
+        }
+
+        while new_shape.len() > ndims && new_shape.last() == Some(&1) {
+            new_shape.pop();
+        }
+        Ok(flat_to_nested_array(&out, &new_shape))
+    }
+
+    /// Concatenates two arrays along dimension `dim` (counting from 1).
+    /// ```typescript
+    /// let c = cat(1, [1, 2], [3, 4, 5]);
+    /// assert_eq(c, [1, 2, 3, 4, 5]);
+    /// ```
+    /// ```typescript
+    /// let c = cat(2, [1, 2], [3, 4]);
+    /// assert_eq(c, [[1, 3], [2, 4]]);
+    /// ```
+    #[rhai_fn(name = "cat", return_raw)]
+    pub fn cat_pair(dim: INT, a: Array, b: Array) -> Result<Array, Box<EvalAltResult>> {
+        cat(dim, vec![Dynamic::from_array(a), Dynamic::from_array(b)])
+    }
+
+    /// Converts an index argument (an INT, an array of INT, or a range) into a list of indices,
+    /// checking that each one is within a dimension of length `n`.
+    fn to_index_list(idx: Dynamic, n: usize) -> Result<Vec<usize>, Box<EvalAltResult>> {
+        let check = |i: INT| {
+            if i >= 0 && (i as usize) < n {
+                Ok(i as usize)
+            } else {
+                Err(EvalAltResult::ErrorArithmetic(
+                    format!("The index {i} is out of bounds for a dimension of length {n}"),
+                    Position::NONE,
+                ))
+            }
+        };
+        // Check the ends of a range before collecting it, so a huge range fails quickly
+        let range = if idx.is::<std::ops::Range<INT>>() {
+            let r = idx.clone().cast::<std::ops::Range<INT>>();
+            Some((r.start, r.end.checked_sub(1)))
+        } else if idx.is::<std::ops::RangeInclusive<INT>>() {
+            let r = idx.clone().cast::<std::ops::RangeInclusive<INT>>();
+            Some((*r.start(), Some(*r.end())))
+        } else {
+            None
+        };
+        if let Some((start, last)) = range {
+            return match last {
+                Some(last) if start <= last => Ok((check(start)?..=check(last)?).collect()),
+                _ => Ok(vec![]),
+            };
+        }
+
+        let indices = if idx.is_int() {
+            vec![idx.as_int().unwrap()]
+        } else if idx.is_array() && idx.clone().into_array().unwrap().iter().all(|i| i.is_int()) {
+            idx.into_array()
+                .unwrap()
+                .iter()
+                .map(|i| i.as_int().unwrap())
+                .collect()
+        } else {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The indices must be an INT, an array of INT, or a range".to_string(),
+                Position::NONE,
+            )
+            .into());
+        };
+        indices
+            .into_iter()
+            .map(|i| check(i).map_err(Into::into))
+            .collect()
+    }
+
+    /// Returns the indices from 0 to `n - 1` that are not in an index argument.
+    fn complement_index_list(idx: Dynamic, n: usize) -> Result<Vec<usize>, Box<EvalAltResult>> {
+        let removed = to_index_list(idx, n)?;
+        Ok((0..n).filter(|i| !removed.contains(i)).collect())
+    }
+
+    /// Returns the rows of a matrix at the given indices, which can be an INT, an array of INT, or
+    /// a range.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(rows(matrix, 0..2), [[1, 2, 3], [4, 5, 6]]);
+    /// assert_eq(rows(matrix, 1..=2), [[4, 5, 6], [7, 8, 9]]);
+    /// assert_eq(rows(matrix, [2, 0]), [[7, 8, 9], [1, 2, 3]]);
+    /// assert_eq(rows(matrix, 1), [[4, 5, 6]]);
+    /// ```
+    /// ```typescript
+    /// let matrix = [[1, 2], [3, 4]];
+    /// let result = "ok";
+    /// try { rows(matrix, 0..9223372036854775807); } catch { result = "out of bounds"; }
+    /// assert_eq(result, "out of bounds");
+    /// assert_eq(rows(matrix, 1..1), []);
+    /// ```
+    #[rhai_fn(name = "rows", return_raw, pure)]
+    pub fn rows(matrix: &mut Array, idx: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
+            Ok(to_index_list(idx.clone(), matrix_as_vec.len())?
+                .into_iter()
+                .map(|i| Dynamic::from_array(matrix_as_vec[i].clone()))
+                .collect())
+        })
+    }
+
+    /// Returns the rows of a `Matrix` at the given indices, which can be an INT, an array of INT,
+    /// or a range.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2],
+    ///                         [3, 4],
+    ///                         [5, 6]]);
+    /// assert_eq(to_array(rows(matrix, 1..3)), [[3.0, 4.0], [5.0, 6.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rows", return_raw, pure)]
+    pub fn rows_native(matrix: &mut Matrix, idx: Dynamic) -> Result<Matrix, Box<EvalAltResult>> {
+        let r = to_index_list(idx, matrix.0.nrows())?;
+        Ok(Matrix::from(matrix.0.select_rows(r.iter())))
+    }
+
+    /// Returns the columns of a matrix at the given indices, which can be an INT, an array of INT,
+    /// or a range.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6]];
+    /// assert_eq(cols(matrix, 1..3), [[2, 3], [5, 6]]);
+    /// assert_eq(cols(matrix, [0, 0]), [[1, 1], [4, 4]]);
+    /// assert_eq(cols(matrix, 2), [[3], [6]]);
+    /// ```
+    #[rhai_fn(name = "cols", return_raw, pure)]
+    pub fn cols(matrix: &mut Array, idx: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
+            let c = to_index_list(idx.clone(), matrix_as_vec[0].len())?;
+            Ok(matrix_as_vec
+                .iter()
+                .map(|row| Dynamic::from_array(c.iter().map(|&j| row[j].clone()).collect()))
+                .collect())
+        })
+    }
+
+    /// Returns the columns of a `Matrix` at the given indices, which can be an INT, an array of
+    /// INT, or a range.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2, 3],
+    ///                         [4, 5, 6]]);
+    /// assert_eq(to_array(cols(matrix, [2, 0])), [[3.0, 1.0], [6.0, 4.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "cols", return_raw, pure)]
+    pub fn cols_native(matrix: &mut Matrix, idx: Dynamic) -> Result<Matrix, Box<EvalAltResult>> {
+        let c = to_index_list(idx, matrix.0.ncols())?;
+        Ok(Matrix::from(matrix.0.select_columns(c.iter())))
+    }
+
+    /// Returns the block of a matrix at the given row and column indices, each of which can be an
+    /// INT, an array of INT, or a range.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(submatrix(matrix, 1..3, 0..2), [[4, 5], [7, 8]]);
+    /// assert_eq(submatrix(matrix, [0, 2], 2), [[3], [9]]);
+    /// ```
+    #[rhai_fn(name = "submatrix", return_raw, pure)]
+    pub fn submatrix(
+        matrix: &mut Array,
+        row_idx: Dynamic,
+        col_idx: Dynamic,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        rows(&mut cols(matrix, col_idx)?, row_idx)
+    }
+
+    /// Returns the block of a matrix from row `r0` to row `r1` and from column `c0` to column `c1`,
+    /// including the end points.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(submatrix(matrix, 0, 1, 1, 2), [[2, 3], [5, 6]]);
+    /// ```
+    /// ```typescript
+    /// let matrix = [[1, 2], [3, 4]];
+    /// let result = "ok";
+    /// try { submatrix(matrix, 0, 9223372036854775807, 0, 1); } catch { result = "out of bounds"; }
+    /// assert_eq(result, "out of bounds");
+    /// ```
+    #[rhai_fn(name = "submatrix", return_raw, pure)]
+    pub fn submatrix_by_bounds(
+        matrix: &mut Array,
+        r0: INT,
+        r1: INT,
+        c0: INT,
+        c1: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        submatrix(matrix, Dynamic::from(r0..=r1), Dynamic::from(c0..=c1))
+    }
+
+    /// Returns the block of a `Matrix` at the given row and column indices, each of which can be
+    /// an INT, an array of INT, or a range.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2, 3],
+    ///                         [4, 5, 6]]);
+    /// assert_eq(to_array(submatrix(matrix, 1, 1..3)), [[5.0, 6.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "submatrix", return_raw, pure)]
+    pub fn submatrix_native(
+        matrix: &mut Matrix,
+        row_idx: Dynamic,
+        col_idx: Dynamic,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        cols_native(&mut rows_native(matrix, row_idx)?, col_idx)
+    }
+
+    /// Returns the block of a `Matrix` from row `r0` to row `r1` and from column `c0` to column
+    /// `c1`, including the end points.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2, 3],
+    ///                         [4, 5, 6]]);
+    /// assert_eq(to_array(submatrix(matrix, 0, 1, 0, 0)), [[1.0], [4.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "submatrix", return_raw, pure)]
+    pub fn submatrix_by_bounds_native(
+        matrix: &mut Matrix,
+        r0: INT,
+        r1: INT,
+        c0: INT,
+        c1: INT,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        submatrix_native(matrix, Dynamic::from(r0..=r1), Dynamic::from(c0..=c1))
+    }
+
+    /// Returns a copy of a matrix with the block starting at row `r0` and column `c0` replaced by
+    /// another matrix. The block must fit inside the matrix.
+    /// ```typescript
+    /// let matrix = zeros(3, 3);
+    /// let x = set_submatrix(matrix, 1, 1, [[1, 2], [3, 4]]);
+    /// assert_eq(x, [[0.0, 0.0, 0.0],
+    ///               [0.0, 1, 2],
+    ///               [0.0, 3, 4]]);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { set_submatrix(zeros(2, 2), 9223372036854775807, 0, [[1]]); } catch { result = "does not fit"; }
+    /// assert_eq(result, "does not fit");
+    /// ```
+    #[rhai_fn(name = "set_submatrix", return_raw, pure)]
+    pub fn set_submatrix(
+        matrix: &mut Array,
+        r0: INT,
+        c0: INT,
+        mut block: Array,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_vec_array_and_do(matrix, |mut matrix_as_vec| {
+            if_matrix_convert_to_vec_array_and_do(&mut block, |block_as_vec| {
+                let (h, w) = (block_as_vec.len() as INT, block_as_vec[0].len() as INT);
+                if r0 < 0
+                    || c0 < 0
+                    || r0
+                        .checked_add(h)
+                        .is_none_or(|r1| r1 > matrix_as_vec.len() as INT)
+                    || c0
+                        .checked_add(w)
+                        .is_none_or(|c1| c1 > matrix_as_vec[0].len() as INT)
+                {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The block does not fit inside the matrix at the given position"
+                            .to_string(),
+                        Position::NONE,
+                    )
+                    .into());
                 }
-            },
-        )
+                for (i, block_row) in block_as_vec.iter().enumerate() {
+                    let row = &mut matrix_as_vec[r0 as usize + i];
+                    row[c0 as usize..c0 as usize + block_row.len()].clone_from_slice(block_row);
+                }
+                Ok(matrix_as_vec
+                    .iter()
+                    .map(|row| Dynamic::from_array(row.clone()))
+                    .collect())
+            })
+        })
     }
 
-    /// Returns the identity matrix, specifying the number of rows and columns separately.
+    /// Returns a copy of a `Matrix` with the block starting at row `r0` and column `c0` replaced
+    /// by another `Matrix`. The block must fit inside the matrix.
     /// ```typescript
-    /// let matrix = eye(3, 4);
-    /// assert_eq(matrix, [[1.0, 0.0, 0.0, 0.0],
-    ///                    [0.0, 1.0, 0.0, 0.0],
-    ///                    [0.0, 0.0, 1.0, 0.0]]);
+    /// let matrix = to_matrix(zeros(2, 3));
+    /// let x = set_submatrix(matrix, 0, 1, to_matrix([[1, 2]]));
+    /// assert_eq(to_array(x), [[0.0, 1.0, 2.0],
+    ///                         [0.0, 0.0, 0.0]]);
     /// ```
-    #[rhai_fn(name = "eye")]
-    pub fn eye_double_input(nx: INT, ny: INT) -> Array {
-        let mut output = vec![];
-        for i in 0..nx {
-            let mut row = vec![];
-            for j in 0..ny {
-                if i == j {
-                    row.push(Dynamic::FLOAT_ONE);
-                } else {
-                    row.push(Dynamic::FLOAT_ZERO);
-                }
-            }
-            output.push(Dynamic::from_array(row))
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "set_submatrix", return_raw, pure)]
+    pub fn set_submatrix_native(
+        matrix: &mut Matrix,
+        r0: INT,
+        c0: INT,
+        block: Matrix,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        let (h, w) = block.0.shape();
+        if r0 < 0
+            || c0 < 0
+            || (r0 as usize)
+                .checked_add(h)
+                .is_none_or(|r1| r1 > matrix.0.nrows())
+            || (c0 as usize)
+                .checked_add(w)
+                .is_none_or(|c1| c1 > matrix.0.ncols())
+        {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The block does not fit inside the matrix at the given position".to_string(),
+                Position::NONE,
+            )
+            .into());
         }
-        output
+        let mut dm = matrix.0.clone();
+        dm.view_mut((r0 as usize, c0 as usize), (h, w))
+            .copy_from(&block.0);
+        Ok(Matrix::from(dm))
     }
 
-    /// Returns the contents of a multidimensional array as a 1-D array.
+    /// Returns a copy of a matrix without the rows at the given indices, which can be an INT, an
+    /// array of INT, or a range.
     /// ```typescript
-    /// let matrix = ones(3, 5);
-    /// let flat = flatten(matrix);
-    /// assert_eq(len(flat), 15);
+    /// let matrix = [[1, 2],
+    ///               [3, 4],
+    ///               [5, 6]];
+    /// assert_eq(delete_rows(matrix, 1), [[1, 2], [5, 6]]);
+    /// assert_eq(delete_rows(matrix, 0..2), [[5, 6]]);
     /// ```
+    #[rhai_fn(name = "delete_rows", return_raw, pure)]
+    pub fn delete_rows(matrix: &mut Array, idx: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        let n = if_matrix_convert_to_vec_array_and_do(matrix, |m| Ok(m.len()))?;
+        let kept = complement_index_list(idx, n)?;
+        rows(
+            matrix,
+            Dynamic::from_array(
+                kept.into_iter()
+                    .map(|i| Dynamic::from_int(i as INT))
+                    .collect(),
+            ),
+        )
+    }
+
+    /// Returns a copy of a `Matrix` without the rows at the given indices, which can be an INT, an
+    /// array of INT, or a range.
     /// ```typescript
-    /// let matrix = [[1.0, 2.0, 3.0], [1.0]];
-    /// let flat = flatten(matrix);
-    /// assert_eq(len(flat), 4);
+    /// let matrix = to_matrix([[1, 2],
+    ///                         [3, 4],
+    ///                         [5, 6]]);
+    /// assert_eq(to_array(delete_rows(matrix, [0, 2])), [[3.0, 4.0]]);
     /// ```
-    #[rhai_fn(name = "flatten", pure)]
-    pub fn flatten(matrix: &mut Array) -> Array {
-        let mut flat: Array = vec![];
-        for el in matrix {
-            if el.is_array() {
-                flat.extend(flatten(&mut el.clone().into_array().unwrap()))
-            } else {
-                flat.push(el.clone());
-            }
-        }
-        flat
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "delete_rows", return_raw, pure)]
+    pub fn delete_rows_native(
+        matrix: &mut Matrix,
+        idx: Dynamic,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        let kept = complement_index_list(idx, matrix.0.nrows())?;
+        Ok(Matrix::from(matrix.0.select_rows(kept.iter())))
+    }
+
+    /// Returns a copy of a matrix without the columns at the given indices, which can be an INT,
+    /// an array of INT, or a range.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6]];
+    /// assert_eq(delete_cols(matrix, 1), [[1, 3], [4, 6]]);
+    /// assert_eq(delete_cols(matrix, [0, 2]), [[2], [5]]);
+    /// ```
+    #[rhai_fn(name = "delete_cols", return_raw, pure)]
+    pub fn delete_cols(matrix: &mut Array, idx: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        let n = if_matrix_convert_to_vec_array_and_do(matrix, |m| Ok(m[0].len()))?;
+        let kept = complement_index_list(idx, n)?;
+        cols(
+            matrix,
+            Dynamic::from_array(
+                kept.into_iter()
+                    .map(|i| Dynamic::from_int(i as INT))
+                    .collect(),
+            ),
+        )
+    }
+
+    /// Returns a copy of a `Matrix` without the columns at the given indices, which can be an INT,
+    /// an array of INT, or a range.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2, 3],
+    ///                         [4, 5, 6]]);
+    /// assert_eq(to_array(delete_cols(matrix, 0..2)), [[3.0], [6.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "delete_cols", return_raw, pure)]
+    pub fn delete_cols_native(
+        matrix: &mut Matrix,
+        idx: Dynamic,
+    ) -> Result<Matrix, Box<EvalAltResult>> {
+        let kept = complement_index_list(idx, matrix.0.ncols())?;
+        Ok(Matrix::from(matrix.0.select_columns(kept.iter())))
     }
 
     /// Flip a matrix left-to-right
@@ -830,6 +3571,20 @@ pub mod matrix_functions {
         })
     }
 
+    /// Flip a `Matrix` left-to-right
+    /// ```typescript
+    /// let matrix = fliplr(to_matrix([[1.0, 0.0],
+    ///                                [0.0, 2.0]]));
+    /// assert_eq(to_array(matrix), [[0.0, 1.0],
+    ///                              [2.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "fliplr", pure)]
+    pub fn fliplr_native(matrix: &mut Matrix) -> Matrix {
+        let (h, w) = matrix.0.shape();
+        Matrix::from(DMatrix::from_fn(h, w, |i, j| matrix.0[(i, w - j - 1)]))
+    }
+
     /// Flip a matrix up-down
     /// ```typescript
     /// let matrix = flipud([[1.0, 0.0],
@@ -856,6 +3611,20 @@ pub mod matrix_functions {
         })
     }
 
+    /// Flip a `Matrix` up-down
+    /// ```typescript
+    /// let matrix = flipud(to_matrix([[1.0, 0.0],
+    ///                                [0.0, 2.0]]));
+    /// assert_eq(to_array(matrix), [[0.0, 2.0],
+    ///                              [1.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "flipud", pure)]
+    pub fn flipud_native(matrix: &mut Matrix) -> Matrix {
+        let (h, w) = matrix.0.shape();
+        Matrix::from(DMatrix::from_fn(h, w, |i, j| matrix.0[(h - i - 1, j)]))
+    }
+
     /// Rotate a matrix counterclockwise once
     /// ```typescript
     /// let matrix = rot90([[1.0, 0.0],
@@ -883,6 +3652,20 @@ pub mod matrix_functions {
         })
     }
 
+    /// Rotate a `Matrix` counterclockwise once
+    /// ```typescript
+    /// let matrix = rot90(to_matrix([[1.0, 0.0],
+    ///                               [0.0, 2.0]]));
+    /// assert_eq(to_array(matrix), [[0.0, 2.0],
+    ///                              [1.0, 0.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rot90", pure)]
+    pub fn rot90_once_native(matrix: &mut Matrix) -> Matrix {
+        let (h, w) = matrix.0.shape();
+        Matrix::from(DMatrix::from_fn(w, h, |i, j| matrix.0[(j, w - i - 1)]))
+    }
+
     /// Rotate a matrix counterclockwise `k` times
     /// ```typescript
     /// let matrix = rot90([[1.0, 0.0],
@@ -907,7 +3690,24 @@ pub mod matrix_functions {
         Ok(result_base)
     }
 
-    /// Perform matrix multiplication.
+    /// Rotate a `Matrix` counterclockwise `k` times
+    /// ```typescript
+    /// let matrix = rot90(to_matrix([[1.0, 0.0],
+    ///                               [0.0, 2.0]]), 2);
+    /// assert_eq(to_array(matrix), [[2.0, 0.0],
+    ///                              [0.0, 1.0]]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "rot90", pure)]
+    pub fn rot90_ktimes_native(matrix: &mut Matrix, k: INT) -> Matrix {
+        let mut result = matrix.clone();
+        for _ in 0..k.rem_euclid(4) {
+            result = rot90_once_native(&mut result);
+        }
+        result
+    }
+
+    /// Perform matrix multiplication. Use `times` for element-wise multiplication.
     /// ```typescript
     /// let a = eye(3);
     /// let b = ones(3);
@@ -957,6 +3757,85 @@ pub mod matrix_functions {
         )
     }
 
+    /// Perform matrix multiplication of two `Matrix` values.
+    /// ```typescript
+    /// let a = to_matrix(eye(3));
+    /// let b = to_matrix(ones(3));
+    /// let c = mtimes(a, b);
+    /// assert_eq(to_array(c), ones(3));
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mtimes", return_raw)]
+    pub fn mtimes_native(matrix1: Matrix, matrix2: Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        if matrix1.0.ncols() == matrix2.0.nrows() {
+            Ok(Matrix::from(matrix1.0 * matrix2.0))
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The input matrices are not compatible for this operation".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Solves `x * b = a` for `x` by solving the transposed system `b' * x' = a'`.
+    #[cfg(feature = "nalgebra")]
+    fn right_divide(
+        a: DMatrix<FLOAT>,
+        b: DMatrix<FLOAT>,
+    ) -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+        left_divide(b.transpose(), a.transpose()).map(|x| x.transpose())
+    }
+
+    /// Performs matrix right division, returning the solution `x` of `x * matrix2 = matrix1`. The
+    /// matrices must have the same number of columns. Use `rdivide` for element-wise division.
+    /// ```typescript
+    /// let a = [[1.0, 2.0],
+    ///          [3.0, 4.0]];
+    /// let x = mrdivide(a, a);
+    /// assert_eq(x, eye(2));
+    /// ```
+    /// ```typescript
+    /// let a = [[4, 2]];
+    /// let b = [[2, 0],
+    ///          [0, 4]];
+    /// let x = mrdivide(a, b);
+    /// assert_approx_eq(flatten(x), [2.0, 0.5]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mrdivide", return_raw)]
+    pub fn mrdivide(matrix1: Array, matrix2: Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrices_and_compatible_convert_to_dmatrix_and_do(
+            FOIL::Last,
+            &mut matrix1.clone(),
+            &mut matrix2.clone(),
+            |a, b| right_divide(a, b).map(omatrix_to_vec_dynamic),
+        )
+    }
+
+    /// Performs matrix right division of two `Matrix` values, returning the solution `x` of
+    /// `x * matrix2 = matrix1`.
+    /// ```typescript
+    /// let a = to_matrix([[4, 2]]);
+    /// let b = to_matrix([[2, 0],
+    ///                    [0, 4]]);
+    /// let x = mrdivide(a, b);
+    /// assert_approx_eq(flatten(x), [2.0, 0.5]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "mrdivide", return_raw)]
+    pub fn mrdivide_native(matrix1: Matrix, matrix2: Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        if matrix1.0.ncols() == matrix2.0.ncols() {
+            right_divide(matrix1.0, matrix2.0).map(Matrix::from)
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The input matrices are not compatible for this operation".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
     /// Concatenate two arrays horizontally.
     /// ```typescript
     /// let arr1 = eye(3);
@@ -1016,6 +3895,37 @@ pub mod matrix_functions {
         )
     }
 
+    /// Concatenate two `Matrix` values horizontally.
+    /// ```typescript
+    /// let m = to_matrix(eye(3));
+    /// let combined = horzcat(m, m);
+    /// assert_eq(size(combined), [3, 6]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "horzcat", return_raw)]
+    pub fn horzcat_native(matrix1: Matrix, matrix2: Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        if matrix1.0.nrows() == matrix2.0.nrows() {
+            let w0 = matrix1.0.ncols();
+            Ok(Matrix::from(DMatrix::from_fn(
+                matrix1.0.nrows(),
+                w0 + matrix2.0.ncols(),
+                |i, j| {
+                    if j >= w0 {
+                        matrix2.0[(i, j - w0)]
+                    } else {
+                        matrix1.0[(i, j)]
+                    }
+                },
+            )))
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The input matrices are not compatible for this operation".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
     /// Concatenates two array vertically.
     /// ```typescript
     /// let arr1 = eye(3);
@@ -1075,6 +3985,37 @@ pub mod matrix_functions {
         )
     }
 
+    /// Concatenate two `Matrix` values vertically.
+    /// ```typescript
+    /// let m = to_matrix(eye(3));
+    /// let combined = vertcat(m, m);
+    /// assert_eq(size(combined), [6, 3]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "vertcat", return_raw)]
+    pub fn vertcat_native(matrix1: Matrix, matrix2: Matrix) -> Result<Matrix, Box<EvalAltResult>> {
+        if matrix1.0.ncols() == matrix2.0.ncols() {
+            let h0 = matrix1.0.nrows();
+            Ok(Matrix::from(DMatrix::from_fn(
+                h0 + matrix2.0.nrows(),
+                matrix1.0.ncols(),
+                |i, j| {
+                    if i >= h0 {
+                        matrix2.0[(i - h0, j)]
+                    } else {
+                        matrix1.0[(i, j)]
+                    }
+                },
+            )))
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The input matrices are not compatible for this operation".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
     /// This function can be used in two distinct ways.
     /// 1. If the argument is an 2-D array, `diag` returns an array containing the diagonal of the array.
     /// 2. If the argument is a 1-D array, `diag` returns a matrix containing the argument along the
@@ -1135,6 +4076,197 @@ pub mod matrix_functions {
         }
     }
 
+    /// Returns an array containing the main diagonal of a `Matrix`.
+    /// ```typescript
+    /// let matrix = to_matrix([[1, 2, 3],
+    ///                         [4, 5, 6],
+    ///                         [7, 8, 9]]);
+    /// assert_eq(diag(matrix), [1.0, 5.0, 9.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "diag", pure)]
+    pub fn diag_native(matrix: &mut Matrix) -> Array {
+        ovector_to_vec_dynamic(matrix.0.diagonal())
+    }
+
+    /// Returns a zero of the same type (INT or FLOAT) as the input.
+    fn zero_like(x: &mut Dynamic) -> Dynamic {
+        if x.is_int() {
+            Dynamic::ZERO
+        } else {
+            Dynamic::FLOAT_ZERO
+        }
+    }
+
+    /// Like `diag`, but for the `k`-th diagonal. Positive values of `k` refer to diagonals above
+    /// the main diagonal, and negative values to diagonals below it.
+    /// 1. If the argument is a 2-D array, `diag` returns an array containing the `k`-th diagonal.
+    /// 2. If the argument is a 1-D array, `diag` returns a square matrix containing the argument
+    /// along the `k`-th diagonal and zeros elsewhere. In this case, `k` must not be larger in
+    /// magnitude than the length of the array.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(diag(matrix, 1), [2, 6]);
+    /// assert_eq(diag(matrix, -2), [7]);
+    /// assert_eq(diag(matrix, 5), []);
+    /// ```
+    /// ```typescript
+    /// let matrix = diag([1, 2], -1);
+    /// assert_eq(matrix, [[0, 0, 0],
+    ///                    [1, 0, 0],
+    ///                    [0, 2, 0]]);
+    /// ```
+    #[rhai_fn(name = "diag", return_raw)]
+    pub fn diag_with_offset(matrix: Array, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        let offset = usize::try_from(k.unsigned_abs()).unwrap_or(usize::MAX);
+        let (row_offset, col_offset) = if k < 0 { (offset, 0) } else { (0, offset) };
+        if ndims_by_reference(&mut matrix.clone()) == 2 {
+            if_matrix_convert_to_vec_array_and_do(&mut matrix.clone(), |matrix_as_vec| {
+                let n_cols = matrix_as_vec[0].len();
+                Ok((row_offset..matrix_as_vec.len())
+                    .zip(col_offset..n_cols)
+                    .map(|(i, j)| matrix_as_vec[i][j].clone())
+                    .collect())
+            })
+        } else if ndims_by_reference(&mut matrix.clone()) == 1 {
+            if offset > matrix.len() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!(
+                        "The diagonal offset ({k}) must not be larger than the length of the array ({})",
+                        matrix.len()
+                    ),
+                    Position::NONE,
+                )
+                .into());
+            }
+            let n = matrix.len() + offset;
+            let zero = if matrix.iter().all(|x| x.is_int()) {
+                Dynamic::ZERO
+            } else {
+                Dynamic::FLOAT_ZERO
+            };
+            let mut out = vec![vec![zero; n]; n];
+            for (idx, el) in matrix.into_iter().enumerate() {
+                out[idx + row_offset][idx + col_offset] = el;
+            }
+            Ok(out.into_iter().map(Dynamic::from_array).collect())
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "Argument must be a 2-D matrix (to extract a diagonal) or a 1-D array (to create a matrix with that diagonal)".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Returns the upper triangular part of a matrix, with the elements below the main diagonal
+    /// set to zero.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(triu(matrix), [[1, 2, 3],
+    ///                          [0, 5, 6],
+    ///                          [0, 0, 9]]);
+    /// ```
+    #[rhai_fn(name = "triu", return_raw)]
+    pub fn triu(matrix: Array) -> Result<Array, Box<EvalAltResult>> {
+        triu_with_offset(matrix, 0)
+    }
+
+    /// Returns the elements on and above the `k`-th diagonal of a matrix, with the rest set to
+    /// zero. Positive values of `k` refer to diagonals above the main diagonal, and negative values
+    /// to diagonals below it.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(triu(matrix, 1), [[0, 2, 3],
+    ///                             [0, 0, 6],
+    ///                             [0, 0, 0]]);
+    /// ```
+    /// ```typescript
+    /// let matrix = ones(3, 3);
+    /// assert_eq(triu(matrix, -1), [[1.0, 1.0, 1.0],
+    ///                              [1.0, 1.0, 1.0],
+    ///                              [0.0, 1.0, 1.0]]);
+    /// ```
+    #[rhai_fn(name = "triu", return_raw)]
+    pub fn triu_with_offset(matrix: Array, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_vec_array_and_do(&mut matrix.clone(), |matrix_as_vec| {
+            Ok(matrix_as_vec
+                .into_iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    Dynamic::from_array(
+                        row.into_iter()
+                            .enumerate()
+                            .map(|(j, mut el)| {
+                                if (j as INT) - (i as INT) >= k {
+                                    el
+                                } else {
+                                    zero_like(&mut el)
+                                }
+                            })
+                            .collect(),
+                    )
+                })
+                .collect())
+        })
+    }
+
+    /// Returns the lower triangular part of a matrix, with the elements above the main diagonal
+    /// set to zero.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(tril(matrix), [[1, 0, 0],
+    ///                          [4, 5, 0],
+    ///                          [7, 8, 9]]);
+    /// ```
+    #[rhai_fn(name = "tril", return_raw)]
+    pub fn tril(matrix: Array) -> Result<Array, Box<EvalAltResult>> {
+        tril_with_offset(matrix, 0)
+    }
+
+    /// Returns the elements on and below the `k`-th diagonal of a matrix, with the rest set to
+    /// zero. Positive values of `k` refer to diagonals above the main diagonal, and negative values
+    /// to diagonals below it.
+    /// ```typescript
+    /// let matrix = [[1, 2, 3],
+    ///               [4, 5, 6],
+    ///               [7, 8, 9]];
+    /// assert_eq(tril(matrix, -1), [[0, 0, 0],
+    ///                              [4, 0, 0],
+    ///                              [7, 8, 0]]);
+    /// ```
+    #[rhai_fn(name = "tril", return_raw)]
+    pub fn tril_with_offset(matrix: Array, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_vec_array_and_do(&mut matrix.clone(), |matrix_as_vec| {
+            Ok(matrix_as_vec
+                .into_iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    Dynamic::from_array(
+                        row.into_iter()
+                            .enumerate()
+                            .map(|(j, mut el)| {
+                                if (j as INT) - (i as INT) <= k {
+                                    el
+                                } else {
+                                    zero_like(&mut el)
+                                }
+                            })
+                            .collect(),
+                    )
+                })
+                .collect())
+        })
+    }
+
     /// Repeats copies of a matrix
     /// ```typescript
     /// let matrix = eye(3);
@@ -1157,6 +4289,137 @@ pub mod matrix_functions {
         })
     }
 
+    /// Repeats copies of a `Matrix`
+    /// ```typescript
+    /// let matrix = to_matrix(eye(3));
+    /// let combined = repmat(matrix, 2, 3);
+    /// assert_eq(size(combined), [6, 9]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "repmat", pure)]
+    pub fn repmat_native(matrix: &mut Matrix, nx: INT, ny: INT) -> Matrix {
+        let (h, w) = matrix.0.shape();
+        Matrix::from(DMatrix::from_fn(
+            h * nx.max(0) as usize,
+            w * ny.max(0) as usize,
+            |i, j| matrix.0[(i % h, j % w)],
+        ))
+    }
+
+    /// Returns the Kronecker product of two matrices, i.e., a block matrix in which each element
+    /// of the first matrix is replaced by that element times the second matrix.
+    /// ```typescript
+    /// let a = [[1, 2],
+    ///          [3, 4]];
+    /// let b = [[0, 1],
+    ///          [1, 0]];
+    /// assert_eq(kron(a, b), [[0, 1, 0, 2],
+    ///                        [1, 0, 2, 0],
+    ///                        [0, 3, 0, 4],
+    ///                        [3, 0, 4, 0]]);
+    /// ```
+    /// ```typescript
+    /// let k = kron(eye(2), ones(2, 3));
+    /// assert_eq(size(k), [4, 6]);
+    /// ```
+    #[rhai_fn(name = "kron", return_raw)]
+    pub fn kron(matrix1: Array, matrix2: Array) -> Result<Array, Box<EvalAltResult>> {
+        if_matrix_convert_to_vec_array_and_do(&mut matrix1.clone(), |a| {
+            if_matrix_convert_to_vec_array_and_do(&mut matrix2.clone(), |b| {
+                let mut out = vec![];
+                for a_row in &a {
+                    for b_row in &b {
+                        let mut row = vec![];
+                        for a_el in a_row {
+                            for b_el in b_row {
+                                row.push(crate::arithmetic_functions::times(
+                                    a_el.clone(),
+                                    b_el.clone(),
+                                )?);
+                            }
+                        }
+                        out.push(Dynamic::from_array(row));
+                    }
+                }
+                Ok(out)
+            })
+        })
+    }
+
+    /// Returns a block diagonal matrix built from an array of blocks, each of which is either a
+    /// matrix or a scalar. A single matrix is returned unchanged.
+    /// ```typescript
+    /// let b = blkdiag([[[1, 2]], 3, [[4], [5]]]);
+    /// assert_eq(b, [[1, 2, 0, 0],
+    ///               [0, 0, 3, 0],
+    ///               [0, 0, 0, 4],
+    ///               [0, 0, 0, 5]]);
+    /// ```
+    /// ```typescript
+    /// let b = blkdiag([[1, 2], [3, 4]]);
+    /// assert_eq(b, [[1, 2], [3, 4]]);
+    /// ```
+    #[rhai_fn(name = "blkdiag", return_raw)]
+    pub fn blkdiag(blocks: Array) -> Result<Array, Box<EvalAltResult>> {
+        if crate::numeric_shape(&Dynamic::from_array(blocks.clone())).map(|s| s.len()) == Some(2) {
+            return Ok(blocks);
+        }
+
+        let mut matrices = vec![];
+        for block in blocks {
+            if block.is_int() || block.is_float() {
+                matrices.push(vec![vec![block]]);
+            } else if block.is_array() {
+                matrices.push(if_matrix_convert_to_vec_array_and_do(
+                    &mut block.into_array().unwrap(),
+                    Ok,
+                )?);
+            } else {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "Each block must be a matrix or a scalar".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+        }
+
+        let zero = if matrices.iter().flatten().flatten().all(|x| x.is_int()) {
+            Dynamic::ZERO
+        } else {
+            Dynamic::FLOAT_ZERO
+        };
+        let n_cols = matrices.iter().map(|m| m[0].len()).sum::<usize>();
+        let mut out = vec![];
+        let mut col_offset = 0;
+        for matrix in matrices {
+            let width = matrix[0].len();
+            for matrix_row in matrix {
+                let mut row = vec![zero.clone(); n_cols];
+                for (j, el) in matrix_row.into_iter().enumerate() {
+                    row[col_offset + j] = el;
+                }
+                out.push(Dynamic::from_array(row));
+            }
+            col_offset += width;
+        }
+        Ok(out)
+    }
+
+    /// Returns a block diagonal matrix with two matrices along the diagonal.
+    /// ```typescript
+    /// let b = blkdiag(eye(2), [[5]]);
+    /// assert_eq(b, [[1.0, 0.0, 0.0],
+    ///               [0.0, 1.0, 0.0],
+    ///               [0.0, 0.0, 5  ]]);
+    /// ```
+    #[rhai_fn(name = "blkdiag", return_raw)]
+    pub fn blkdiag_pair(matrix1: Array, matrix2: Array) -> Result<Array, Box<EvalAltResult>> {
+        blkdiag(vec![
+            Dynamic::from_array(matrix1),
+            Dynamic::from_array(matrix2),
+        ])
+    }
+
     /// Returns an object map containing 2-D grid coordinates based on the uni-axial coordinates
     /// contained in arguments x and y.
     /// ```typescript
@@ -9,6 +9,10 @@ pub mod misc_functions {
     #[allow(non_upper_case_globals)]
     pub const inf: FLOAT = FLOAT::INFINITY;
 
+    /// Not a number
+    #[allow(non_upper_case_globals)]
+    pub const nan: FLOAT = FLOAT::NAN;
+
     /// Returns a random number between zero and one.
     /// ```typescript
     /// let r = rand();
@@ -38,8 +42,8 @@ pub mod misc_functions {
             },
             |arr| {
                 let mut x = crate::array_to_vec_float(arr);
-                x.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                x.dedup();
+                x.sort_by(crate::compare_floats_nan_last);
+                x.dedup_by(|a, b| a == b || (a.is_nan() && b.is_nan()));
                 Ok(x.iter().map(|el| Dynamic::from_float(*el)).collect())
             },
         )
@@ -0,0 +1,379 @@
+use rhai::plugin::*;
+
+#[export_module]
+pub mod optimization_functions {
+    use rhai::{
+        Array, Dynamic, EvalAltResult, FnPtr, ImmutableString, Map, NativeCallContext, Position,
+        FLOAT, INT,
+    };
+    use std::collections::BTreeMap;
+
+    /// Converts the output of a model into a vector of FLOAT predictions, checking that there is
+    /// one for each y value.
+    fn to_predictions(
+        output: Dynamic,
+        expected_len: INT,
+    ) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        let mut output = match output.into_array() {
+            Ok(mut arr) => crate::matrix_functions::flatten(&mut arr),
+            Err(_) => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The model must return an array of predictions".to_string(),
+                    Position::NONE,
+                )
+                .into())
+            }
+        };
+        let predictions = crate::if_list_convert_to_vec_float_and_do(&mut output, Ok)?;
+        if predictions.len() as INT != expected_len {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The model returned {} predictions, but there are {expected_len} y values",
+                    predictions.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        Ok(predictions)
+    }
+
+    /// Reads a FLOAT option from a map of options, falling back to a default.
+    fn float_option(
+        options: Map,
+        key: ImmutableString,
+        default: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        match options.get(key.as_str()) {
+            Some(value) => crate::if_int_convert_to_float_and_do(value.clone(), Ok),
+            None => Ok(default),
+        }
+    }
+
+    /// Reads the lower or upper bounds of the parameters from a map of options, falling back to
+    /// `default` for every parameter.
+    fn bounds_option(
+        options: Map,
+        key: ImmutableString,
+        n: INT,
+        default: FLOAT,
+    ) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        match options.get(key.as_str()) {
+            Some(value) => {
+                let mut value = value.clone().into_array().map_err(|_| {
+                    EvalAltResult::ErrorArithmetic(
+                        format!("The {key} bounds must be an array"),
+                        Position::NONE,
+                    )
+                })?;
+                let bounds = crate::if_list_convert_to_vec_float_and_do(&mut value, Ok)?;
+                if bounds.len() as INT != n {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        format!("There must be one {key} bound for each parameter"),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                if bounds.iter().any(|b| b.is_nan()) {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        format!("The {key} bounds must not be NaN"),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                Ok(bounds)
+            }
+            None => Ok(vec![default; n as usize]),
+        }
+    }
+
+    /// Fits the parameters of a nonlinear model to data in the least squares sense, with default
+    /// options. See `lsqcurvefit(model, p0, xdata, ydata, options)`.
+    /// ```typescript
+    /// fn decay(p, x) {
+    ///     let y = [];
+    ///     for v in x {
+    ///         y.push(p[0] * exp(-p[1] * v));
+    ///     }
+    ///     y
+    /// }
+    /// let x = [0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0];
+    /// let y = [3.0, 2.336402349214, 1.819591979138, 1.417099658223, 1.103638323514, 0.669390480445,
+    ///          0.406005849710];
+    /// let fit = lsqcurvefit(Fn("decay"), [1.0, 1.0], x, y);
+    /// assert_approx_eq(fit.params, [3.0, 0.5], 1e-6);
+    /// assert_eq(fit.exitflag > 0, true);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "lsqcurvefit", return_raw)]
+    pub fn lsqcurvefit(
+        context: NativeCallContext,
+        model: FnPtr,
+        p0: Array,
+        xdata: Dynamic,
+        ydata: Array,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        lsqcurvefit_with_options(context, model, p0, xdata, ydata, Map::new())
+    }
+
+    /// Fits the parameters of a nonlinear model to data in the least squares sense, with the
+    /// Levenberg–Marquardt algorithm and a forward-difference Jacobian. The model is a function
+    /// pointer that is called as `model(p, xdata)` and returns an array with a prediction for
+    /// each element of `ydata`, starting from the parameters `p0`. The options are
+    /// - `lower` and `upper`, arrays of bounds on the parameters (unbounded by default),
+    /// - `max_iterations` (default 400), the maximum number of steps tried, counting steps that
+    ///   are rejected because they do not reduce the residuals,
+    /// - `function_tolerance` (default 1e-10), the relative decrease of the sum of squared
+    ///   residuals below which the fit stops,
+    /// - `step_tolerance` (default 1e-10), the relative size of a step below which the fit stops,
+    /// - `gradient_tolerance` (default 1e-10), the size of the gradient below which the fit stops.
+    ///
+    /// The result is a map with the keys
+    /// - `params`, the fitted parameters,
+    /// - `residual_norm`, the sum of squared residuals at the fitted parameters,
+    /// - `residual`, the residual `model(params, xdata) - ydata` of each observation,
+    /// - `jacobian`, the Jacobian matrix of the model at the fitted parameters, with a row for each
+    ///   observation and a column for each parameter,
+    /// - `iterations`, the number of steps tried,
+    /// - `exitflag`, which is 1 if the gradient became small enough, 2 if the step became small
+    ///   enough, 3 if the decrease of the residuals became small enough, 0 if the fit reached
+    ///   the maximum number of iterations, and -1 if the residuals, the gradient or a step were
+    ///   not finite (for example, because the model returned NaN).
+    /// ```typescript
+    /// fn gaussian(p, x) {
+    ///     let y = [];
+    ///     for v in x {
+    ///         let z = (v - p[1]) / p[2];
+    ///         y.push(p[0] * exp(-0.5 * z * z));
+    ///     }
+    ///     y
+    /// }
+    /// let x = [-2.0, -1.0, 0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0];
+    /// let y = [0.004419131535, 0.219684668117, 2.289166808858, 4.112887811993, 5.0, 4.112887811993,
+    ///          2.289166808858, 0.219684668117, 0.004419131535];
+    /// let fit = lsqcurvefit(Fn("gaussian"), [3, 0.5, 2], x, y, #{lower: [0, -5, 0.1]});
+    /// assert_approx_eq(fit.params, [5.0, 1.0, 0.8], 1e-4);
+    /// assert_eq(size(fit.jacobian), [9, 3]);
+    /// ```
+    /// ```typescript
+    /// let line = |p, x| x.map(|v| p[0] * v + p[1]);
+    /// let fit = lsqcurvefit(line, [1, 0], [0, 1, 2, 3], [1, 3, 5, 7], #{upper: [1.5, 10]});
+    /// assert_approx_eq(fit.params, [1.5, 1.75], 1e-8);
+    /// assert_approx_eq(fit.residual_norm, 1.25, 1e-8);
+    /// ```
+    /// ```typescript
+    /// let fit = lsqcurvefit(|p, x| x.map(|v| p[0] * v / 0.0 * 0.0), [1], [1, 2], [2, 4]);
+    /// assert_eq(fit.exitflag, -1);
+    /// ```
+    /// ```typescript
+    /// let line = |p, x| x.map(|v| p[0] * v);
+    /// let result = "ok";
+    /// try { lsqcurvefit(line, [1], [1, 2], [2, 4], #{lower: [nan]}); } catch { result = "NaN bound"; }
+    /// assert_eq(result, "NaN bound");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "lsqcurvefit", return_raw)]
+    pub fn lsqcurvefit_with_options(
+        context: NativeCallContext,
+        model: FnPtr,
+        mut p0: Array,
+        xdata: Dynamic,
+        ydata: Array,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        use nalgebralib::{DMatrix, DVector};
+        let allowed = [
+            "lower",
+            "upper",
+            "max_iterations",
+            "function_tolerance",
+            "step_tolerance",
+            "gradient_tolerance",
+        ];
+        if let Some(key) = options.keys().find(|k| !allowed.contains(&k.as_str())) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown option '{key}', expected {}", allowed.join(", ")),
+                Position::NONE,
+            )
+            .into());
+        }
+        let p0 = crate::if_list_convert_to_vec_float_and_do(&mut p0, Ok)?;
+        let y = crate::if_list_convert_to_vec_float_and_do(
+            &mut crate::matrix_functions::flatten(&mut ydata.clone()),
+            Ok,
+        )?;
+        let (n, m) = (p0.len(), y.len());
+        if n == 0 || m == 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "There must be at least one parameter and one y value".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let lower = bounds_option(
+            options.clone(),
+            "lower".into(),
+            n as INT,
+            FLOAT::NEG_INFINITY,
+        )?;
+        let upper = bounds_option(options.clone(), "upper".into(), n as INT, FLOAT::INFINITY)?;
+        if lower.iter().zip(&upper).any(|(l, u)| l > u) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The lower bounds must not exceed the upper bounds".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let max_iterations = match options.get("max_iterations") {
+            Some(value) => value.as_int().map_err(|_| {
+                EvalAltResult::ErrorArithmetic(
+                    "The maximum number of iterations must be an INT".to_string(),
+                    Position::NONE,
+                )
+            })?,
+            None => 400,
+        };
+        let function_tolerance = float_option(options.clone(), "function_tolerance".into(), 1e-10)?;
+        let step_tolerance = float_option(options.clone(), "step_tolerance".into(), 1e-10)?;
+        let gradient_tolerance = float_option(options, "gradient_tolerance".into(), 1e-10)?;
+
+        let project = |p: DVector<FLOAT>| {
+            DVector::from_iterator(
+                n,
+                p.iter()
+                    .enumerate()
+                    .map(|(j, v)| v.clamp(lower[j], upper[j])),
+            )
+        };
+        let residual = |p: &DVector<FLOAT>| -> Result<DVector<FLOAT>, Box<EvalAltResult>> {
+            let p = p.iter().map(|&v| Dynamic::from_float(v)).collect::<Array>();
+            let output = model.call_raw(&context, None, [Dynamic::from_array(p), xdata.clone()])?;
+            let predictions = to_predictions(output, m as INT)?;
+            Ok(DVector::from_iterator(
+                m,
+                predictions.iter().zip(&y).map(|(f, v)| f - v),
+            ))
+        };
+        // Forward differences, stepping backwards instead where a step would leave the bounds
+        let jacobian = |p: &DVector<FLOAT>,
+                        r: &DVector<FLOAT>|
+         -> Result<DMatrix<FLOAT>, Box<EvalAltResult>> {
+            let mut jac = DMatrix::zeros(m, n);
+            for j in 0..n {
+                let mut h = FLOAT::EPSILON.sqrt() * p[j].abs().max(1.0);
+                if p[j] + h > upper[j] {
+                    h = -h;
+                }
+                let mut shifted = p.clone();
+                shifted[j] += h;
+                let column = (residual(&shifted)? - r) / h;
+                jac.set_column(j, &column);
+            }
+            Ok(jac)
+        };
+
+        let mut p = project(DVector::from_vec(p0));
+        let mut r = residual(&p)?;
+        let mut cost = r.norm_squared();
+        let mut lambda = 1e-3;
+        let mut iterations = 0;
+        let mut exitflag = if cost.is_finite() { 0 } else { -1 };
+        while exitflag == 0 && iterations < max_iterations {
+            let jac = jacobian(&p, &r)?;
+            let gradient = jac.transpose() * &r;
+            if gradient.iter().any(|g| !g.is_finite()) {
+                exitflag = -1;
+                break;
+            }
+
+            // Hold the parameters whose gradient points out of the bounds where they are
+            let active = (0..n)
+                .map(|j| {
+                    (p[j] <= lower[j] && gradient[j] > 0.0)
+                        || (p[j] >= upper[j] && gradient[j] < 0.0)
+                })
+                .collect::<Vec<bool>>();
+            let projected_gradient = (0..n)
+                .filter(|&j| !active[j])
+                .fold(0.0 as FLOAT, |acc, j| acc.max(gradient[j].abs()));
+            if projected_gradient <= gradient_tolerance {
+                exitflag = 1;
+                break;
+            }
+
+            let jtj = jac.transpose() * &jac;
+            let scaling = jtj.diagonal().map(|d| d.max(FLOAT::EPSILON));
+            let mut rhs = -&gradient;
+            for j in (0..n).filter(|&j| active[j]) {
+                rhs[j] = 0.0;
+            }
+            // Each rejected step increases the damping, and counts toward the maximum number of
+            // iterations so that a model that never improves cannot keep the fit going forever
+            loop {
+                iterations += 1;
+                let mut damped = jtj.clone();
+                for j in 0..n {
+                    if active[j] {
+                        damped.row_mut(j).fill(0.0);
+                        damped.column_mut(j).fill(0.0);
+                        damped[(j, j)] = 1.0;
+                    } else {
+                        damped[(j, j)] += lambda * scaling[j];
+                    }
+                }
+                let step = damped.lu().solve(&rhs).ok_or_else(|| {
+                    EvalAltResult::ErrorArithmetic(
+                        "The Levenberg-Marquardt step could not be computed".to_string(),
+                        Position::NONE,
+                    )
+                })?;
+                if step.iter().any(|s| !s.is_finite()) {
+                    exitflag = -1;
+                    break;
+                }
+                let p_new = project(&p + step);
+                let step_size = (&p_new - &p).norm();
+                let small_step = step_size <= step_tolerance * (step_tolerance + p.norm());
+                let r_new = residual(&p_new)?;
+                let cost_new = r_new.norm_squared();
+                if cost_new < cost {
+                    let small_decrease = cost - cost_new <= function_tolerance * cost;
+                    p = p_new;
+                    r = r_new;
+                    cost = cost_new;
+                    lambda = (lambda / 10.0).max(1e-12);
+                    if small_step {
+                        exitflag = 2;
+                    } else if small_decrease {
+                        exitflag = 3;
+                    }
+                    break;
+                }
+                if small_step {
+                    exitflag = 2;
+                    break;
+                }
+                if iterations >= max_iterations {
+                    break;
+                }
+                lambda *= 10.0;
+            }
+        }
+
+        let jac = jacobian(&p, &r)?;
+        let to_array = |v: &DVector<FLOAT>| {
+            Dynamic::from_array(v.iter().map(|&x| Dynamic::from_float(x)).collect())
+        };
+        let mut result = BTreeMap::new();
+        result.insert("params".into(), to_array(&p));
+        result.insert("residual_norm".into(), Dynamic::from_float(cost));
+        result.insert("residual".into(), to_array(&r));
+        result.insert(
+            "jacobian".into(),
+            Dynamic::from_array(crate::omatrix_to_vec_dynamic(jac)),
+        );
+        result.insert("iterations".into(), Dynamic::from_int(iterations));
+        result.insert("exitflag".into(), Dynamic::from_int(exitflag));
+        Ok(result)
+    }
+}
@@ -36,6 +36,13 @@ where
     FA: FnMut(&mut Array) -> Result<T, Box<EvalAltResult>>,
     FB: FnMut(&mut Array) -> Result<T, Box<EvalAltResult>>,
 {
+    if arr.iter().any(|el| el.is_array()) {
+        return Err(EvalAltResult::ErrorArithmetic(
+            "The input must be a 1-D array, not a matrix or N-D array".to_string(),
+            Position::NONE,
+        )
+        .into());
+    }
     let (int, float, total) = int_and_float_totals(arr);
     if int == total {
         f_int(arr)
@@ -64,6 +71,12 @@ where
     }
 }
 
+/// Tests whether every element of an array is an INT or FLOAT. Unlike `is_numeric_list`, the two
+/// may be mixed.
+pub fn is_int_or_float_list(arr: &Array) -> bool {
+    arr.iter().all(|el| el.is_int() || el.is_float())
+}
+
 /// Does a function if the input is a list, otherwise throws an error.
 pub fn if_list_do<F, T>(arr: &mut Array, mut f: F) -> Result<T, Box<EvalAltResult>>
 where
@@ -181,6 +194,33 @@ where
     }
 }
 
+/// If both inputs are matrices of INT and/or FLOAT values that satisfy the compatibility condition,
+/// convert them to `nalgebra` matrices of FLOAT values and do the function. Otherwise, throw an
+/// error.
+#[cfg(feature = "nalgebra")]
+pub fn if_matrices_and_compatible_convert_to_dmatrix_and_do<T, F>(
+    compatibility_condition: FOIL,
+    matrix1: &mut Array,
+    matrix2: &mut Array,
+    mut f: F,
+) -> Result<T, Box<EvalAltResult>>
+where
+    F: FnMut(
+        nalgebralib::DMatrix<FLOAT>,
+        nalgebralib::DMatrix<FLOAT>,
+    ) -> Result<T, Box<EvalAltResult>>,
+{
+    if_matrices_and_compatible_convert_to_vec_array_and_do(
+        compatibility_condition,
+        matrix1,
+        matrix2,
+        |_, _| Ok(()),
+    )?;
+    if_matrix_convert_to_dmatrix_and_do(matrix1, |dm1| {
+        if_matrix_convert_to_dmatrix_and_do(matrix2, |dm2| f(dm1.clone(), dm2))
+    })
+}
+
 /// If the input is a
 pub fn if_matrix_convert_to_vec_array_and_do<F, T>(
     matrix: &mut Array,
@@ -189,11 +229,11 @@ pub fn if_matrix_convert_to_vec_array_and_do<F, T>(
 where
     F: FnMut(Vec<Array>) -> Result<T, Box<EvalAltResult>>,
 {
-    let matrix_as_vec = matrix
-        .into_iter()
-        .map(|x| x.clone().into_array().unwrap())
-        .collect::<Vec<Array>>();
     if crate::validation_functions::is_matrix(matrix) {
+        let matrix_as_vec = matrix
+            .iter()
+            .map(|x| x.clone().into_array().unwrap())
+            .collect::<Vec<Array>>();
         f(matrix_as_vec)
     } else {
         Err(EvalAltResult::ErrorArithmetic(
@@ -204,6 +244,49 @@ where
     }
 }
 
+/// If the input is a matrix of INT and/or FLOAT values, convert it to an `nalgebra` matrix of
+/// FLOAT values and do the function. Otherwise, throw an error.
+#[cfg(feature = "nalgebra")]
+pub fn if_matrix_convert_to_dmatrix_and_do<F, T>(
+    matrix: &mut Array,
+    mut f: F,
+) -> Result<T, Box<EvalAltResult>>
+where
+    F: FnMut(nalgebralib::DMatrix<FLOAT>) -> Result<T, Box<EvalAltResult>>,
+{
+    if !crate::validation_functions::is_matrix(matrix) {
+        return Err(EvalAltResult::ErrorArithmetic(
+            "The input must be a matrix".to_string(),
+            Position::NONE,
+        )
+        .into());
+    }
+    if !crate::validation_functions::is_numeric_array(matrix) {
+        return Err(EvalAltResult::ErrorArithmetic(
+            "The elements of the input matrix must either be INT or FLOAT".to_string(),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    let matrix_as_vec = matrix
+        .iter()
+        .map(|x| x.clone().into_array().unwrap())
+        .collect::<Vec<Array>>();
+    f(nalgebralib::DMatrix::from_fn(
+        matrix_as_vec.len(),
+        matrix_as_vec[0].len(),
+        |i, j| {
+            let el = &matrix_as_vec[i][j];
+            if el.is_float() {
+                el.as_float().unwrap()
+            } else {
+                el.as_int().unwrap() as FLOAT
+            }
+        },
+    ))
+}
+
 pub fn if_int_do_else_if_array_do<FA, FB, T>(
     d: Dynamic,
     mut f_int: FA,
@@ -261,3 +344,567 @@ pub fn ovector_to_vec_dynamic(mat: nalgebralib::OVector<FLOAT, nalgebralib::Dyn>
     }
     out
 }
+
+/// The maximum number of QR iterations allowed for the Schur decomposition of an `n` by `n`
+/// matrix.
+#[cfg(feature = "nalgebra")]
+pub fn schur_iterations(n: usize) -> usize {
+    100 * n.max(1)
+}
+
+/// Compares two FLOAT values, treating NaN as larger than any other value so that it is placed last
+/// when sorting in ascending order.
+pub fn compare_floats_nan_last(a: &FLOAT, b: &FLOAT) -> std::cmp::Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => std::cmp::Ordering::Equal,
+        (true, false) => std::cmp::Ordering::Greater,
+        (false, true) => std::cmp::Ordering::Less,
+        (false, false) => a.partial_cmp(b).unwrap(),
+    }
+}
+
+/// Compares two INT or FLOAT values in ascending or descending order, placing NaN last in either
+/// case. Two INT values are compared exactly.
+pub fn compare_numbers_nan_last(a: &Dynamic, b: &Dynamic, descending: bool) -> std::cmp::Ordering {
+    if a.is_int() && b.is_int() {
+        let ordering = a.as_int().unwrap().cmp(&b.as_int().unwrap());
+        return if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+    }
+    let as_float = |d: &Dynamic| {
+        if d.is_float() {
+            d.as_float().unwrap()
+        } else {
+            d.as_int().unwrap() as FLOAT
+        }
+    };
+    let (x, y) = (as_float(a), as_float(b));
+    if descending && !x.is_nan() && !y.is_nan() {
+        compare_floats_nan_last(&y, &x)
+    } else {
+        compare_floats_nan_last(&x, &y)
+    }
+}
+
+/// Returns the shape of a numeric array along each dimension, or `None` if the array is ragged or
+/// contains anything other than INT or FLOAT. An INT or FLOAT scalar has an empty shape, and an
+/// empty array has the shape `[0]`.
+pub fn numeric_shape(x: &Dynamic) -> Option<Vec<usize>> {
+    if x.is_int() || x.is_float() {
+        Some(vec![])
+    } else if x.is_array() {
+        let arr = x.read_lock::<Array>().unwrap();
+        if arr.is_empty() {
+            return Some(vec![0]);
+        }
+        let inner = numeric_shape(&arr[0])?;
+        for el in arr.iter().skip(1) {
+            if numeric_shape(el)? != inner {
+                return None;
+            }
+        }
+        let mut shape = vec![arr.len()];
+        shape.extend(inner);
+        Some(shape)
+    } else {
+        None
+    }
+}
+
+/// Determines the shape that results from broadcasting two shapes against each other. Shapes are
+/// aligned on their trailing dimensions, and each pair of dimensions must either match or have one
+/// of them equal to 1.
+pub fn broadcast_shapes(s1: &[usize], s2: &[usize]) -> Result<Vec<usize>, Box<EvalAltResult>> {
+    let n = s1.len().max(s2.len());
+    let pad = |s: &[usize]| [vec![1; n - s.len()], s.to_vec()].concat();
+    pad(s1)
+        .iter()
+        .zip(pad(s2).iter())
+        .map(|(&a, &b)| {
+            if a == b || b == 1 {
+                Ok(a)
+            } else if a == 1 {
+                Ok(b)
+            } else {
+                Err(EvalAltResult::ErrorArithmetic(
+                    format!("The shapes {s1:?} and {s2:?} are not compatible for element-wise operations"),
+                    Position::NONE,
+                )
+                .into())
+            }
+        })
+        .collect()
+}
+
+/// Converts a flat list of elements into a nested array with the given shape. The elements are
+/// taken in row-major order.
+pub fn flat_to_nested_array(flat: &[Dynamic], shape: &[usize]) -> Array {
+    if shape.len() <= 1 {
+        return flat.to_vec();
+    }
+    let stride = flat.len() / shape[0].max(1);
+    (0..shape[0])
+        .map(|i| {
+            Dynamic::from_array(flat_to_nested_array(
+                &flat[i * stride..(i + 1) * stride],
+                &shape[1..],
+            ))
+        })
+        .collect()
+}
+
+/// Returns the shape of a numeric array, throwing an error if it is ragged or contains anything
+/// other than INT or FLOAT.
+pub fn if_numeric_array_get_shape(arr: &Array) -> Result<Vec<usize>, Box<EvalAltResult>> {
+    numeric_shape(&Dynamic::from_array(arr.clone())).ok_or_else(|| {
+        EvalAltResult::ErrorArithmetic(
+            "The input must be an array with consistent dimensions containing only INT or FLOAT"
+                .to_string(),
+            Position::NONE,
+        )
+        .into()
+    })
+}
+
+/// Converts a one-based dimension argument into a zero-based index, defaulting to the first
+/// non-singleton dimension of the shape. The index may be beyond the last dimension of the shape,
+/// in which case the array has length 1 along it.
+pub fn resolve_dim(shape: &[usize], dim: Option<INT>) -> Result<usize, Box<EvalAltResult>> {
+    match dim {
+        Some(dim) if dim < 1 => Err(EvalAltResult::ErrorArithmetic(
+            "The dimension must be a positive integer".to_string(),
+            Position::NONE,
+        )
+        .into()),
+        Some(dim) => Ok(dim as usize - 1),
+        None => Ok(shape.iter().position(|&n| n != 1).unwrap_or(0)),
+    }
+}
+
+/// Applies a function to every 1-D slice of a numeric array taken along the (one-based) dimension
+/// `dim`, or along the first non-singleton dimension if `dim` is `None`. The results are assembled
+/// into an array with the same shape as the input, except along `dim`, where the length is that of
+/// the function outputs.
+pub fn if_numeric_array_do_along_dim<F>(
+    arr: &mut Array,
+    dim: Option<INT>,
+    mut f: F,
+) -> Result<Array, Box<EvalAltResult>>
+where
+    F: FnMut(&mut Array) -> Result<Array, Box<EvalAltResult>>,
+{
+    let mut shape = if_numeric_array_get_shape(arr)?;
+    let ndims = shape.len();
+    let d = resolve_dim(&shape, dim)?;
+
+    // A dimension beyond the last one is a singleton, so each element is a slice of its own
+    let flat = crate::matrix_functions::flatten(arr);
+    let outer = shape[..d.min(ndims)].iter().product::<usize>();
+    let inner = shape
+        .get(d + 1..)
+        .map_or(1, |s| s.iter().product::<usize>());
+    let n = shape.get(d).copied().unwrap_or(1);
+
+    // Collect the output of each slice, and check that they all have the same length
+    let mut results = Vec::with_capacity(outer * inner);
+    for o in 0..outer {
+        for i in 0..inner {
+            let mut slice = (0..n)
+                .map(|k| flat[(o * n + k) * inner + i].clone())
+                .collect::<Array>();
+            results.push(f(&mut slice)?);
+        }
+    }
+    let m = results.first().map(|r| r.len()).unwrap_or(0);
+    if results.iter().any(|r| r.len() != m) {
+        return Err(EvalAltResult::ErrorArithmetic(
+            "The function must return the same number of elements for each slice".to_string(),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    let mut out = vec![Dynamic::UNIT; outer * m * inner];
+    for o in 0..outer {
+        for i in 0..inner {
+            for (k, el) in results[o * inner + i].iter().enumerate() {
+                out[(o * m + k) * inner + i] = el.clone();
+            }
+        }
+    }
+
+    if d < ndims {
+        shape[d] = m;
+    } else if m != 1 {
+        if d > ndims {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The dimension must be at most one more than the number of dimensions of the array"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        shape.push(m);
+    }
+    Ok(flat_to_nested_array(&out, &shape))
+}
+
+/// Reduces every 1-D slice of a numeric array along the (one-based) dimension `dim` to a single
+/// value, or along the first non-singleton dimension if `dim` is `None`. The reduced dimension is
+/// kept with length 1, and a result with only one element is returned as a scalar.
+pub fn if_numeric_array_reduce_along_dim<F>(
+    arr: &mut Array,
+    dim: Option<INT>,
+    mut f: F,
+) -> Result<Dynamic, Box<EvalAltResult>>
+where
+    F: FnMut(&mut Array) -> Result<Dynamic, Box<EvalAltResult>>,
+{
+    let out = if_numeric_array_do_along_dim(arr, dim, |x| Ok(vec![f(x)?]))?;
+    let flat = crate::matrix_functions::flatten(&mut out.clone());
+    if flat.len() == 1 {
+        Ok(flat[0].clone())
+    } else {
+        Ok(Dynamic::from_array(out))
+    }
+}
+
+/// Does a function on a list, or reduces a matrix or N-D array along its first non-singleton
+/// dimension by applying the function to each 1-D slice.
+pub fn if_list_do_else_reduce_along_dim<F>(
+    arr: &mut Array,
+    mut f: F,
+) -> Result<Dynamic, Box<EvalAltResult>>
+where
+    F: FnMut(&mut Array) -> Result<Dynamic, Box<EvalAltResult>>,
+{
+    if arr.iter().any(|el| el.is_array()) {
+        if_numeric_array_reduce_along_dim(arr, None, f)
+    } else {
+        f(arr)
+    }
+}
+
+/// Does a function on a list, or applies it to each 1-D slice of a matrix or N-D array taken along
+/// its first non-singleton dimension.
+pub fn if_list_do_else_map_along_dim<F>(
+    arr: &mut Array,
+    mut f: F,
+) -> Result<Array, Box<EvalAltResult>>
+where
+    F: FnMut(&mut Array) -> Result<Array, Box<EvalAltResult>>,
+{
+    if arr.iter().any(|el| el.is_array()) {
+        if_numeric_array_do_along_dim(arr, None, f)
+    } else {
+        f(arr)
+    }
+}
+
+/// Applies a binary function element-by-element to two numeric arrays (or scalars), broadcasting
+/// them against each other as needed. Throws an error if the inputs are not numeric or if their
+/// shapes are not compatible.
+pub fn if_broadcastable_do_elementwise<F>(
+    x: Dynamic,
+    y: Dynamic,
+    mut f: F,
+) -> Result<Dynamic, Box<EvalAltResult>>
+where
+    F: FnMut(&Dynamic, &Dynamic) -> Result<Dynamic, Box<EvalAltResult>>,
+{
+    let (s1, s2) = match (numeric_shape(&x), numeric_shape(&y)) {
+        (Some(s1), Some(s2)) => (s1, s2),
+        _ => {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The inputs must be INT, FLOAT, or arrays with consistent dimensions containing only INT or FLOAT".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    };
+    let shape = broadcast_shapes(&s1, &s2)?;
+
+    let flatten = |d: Dynamic| {
+        if d.is_array() {
+            crate::matrix_functions::flatten(&mut d.into_array().unwrap())
+        } else {
+            vec![d]
+        }
+    };
+    let (flat1, flat2) = (flatten(x), flatten(y));
+
+    // Map a linear index into the broadcast result onto a linear index into an input
+    let source_index = |mut idx: usize, s: &[usize]| {
+        let offset = shape.len() - s.len();
+        let (mut source, mut stride) = (0, 1);
+        for d in (0..shape.len()).rev() {
+            let i = idx % shape[d];
+            idx /= shape[d];
+            if d >= offset {
+                let n = s[d - offset];
+                if n != 1 {
+                    source += i * stride;
+                }
+                stride *= n;
+            }
+        }
+        source
+    };
+
+    let n = shape.iter().product::<usize>();
+    let mut out = Vec::with_capacity(n);
+    for idx in 0..n {
+        out.push(f(
+            &flat1[source_index(idx, &s1)],
+            &flat2[source_index(idx, &s2)],
+        )?);
+    }
+
+    if shape.is_empty() {
+        Ok(out.remove(0))
+    } else {
+        Ok(Dynamic::from_array(flat_to_nested_array(&out, &shape)))
+    }
+}
+
+/// Ranks the values of a slice (counting from 1), giving tied values their average rank.
+pub fn tied_ranks(x: &[FLOAT]) -> Vec<FLOAT> {
+    let mut order = (0..x.len()).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| compare_floats_nan_last(&x[a], &x[b]));
+    let mut ranks = vec![0.0; x.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && x[order[end]] == x[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as FLOAT / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Natural logarithm of the gamma function, computed with the Lanczos approximation.
+pub fn ln_gamma(x: FLOAT) -> FLOAT {
+    const COEFFICIENTS: [FLOAT; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula
+        (std::f64::consts::PI as FLOAT / (std::f64::consts::PI as FLOAT * x).sin().abs()).ln()
+            - ln_gamma(1.0 - x)
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+        let series = COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFFICIENTS[0], |acc, (i, c)| {
+                acc + c / (x + i as FLOAT + 1.0)
+            });
+        0.5 * (2.0 * std::f64::consts::PI as FLOAT).ln() + (x + 0.5) * t.ln() - t + series.ln()
+    }
+}
+
+/// Regularized lower incomplete gamma function P(a, x).
+pub fn regularized_gamma_p(a: FLOAT, x: FLOAT) -> FLOAT {
+    incomplete_gamma(a, x).0
+}
+
+/// Regularized upper incomplete gamma function Q(a, x) = 1 - P(a, x), computed without the loss
+/// of precision of subtracting from 1 when Q is small.
+pub fn regularized_gamma_q(a: FLOAT, x: FLOAT) -> FLOAT {
+    incomplete_gamma(a, x).1
+}
+
+/// Returns the pair (P(a, x), Q(a, x)) of regularized incomplete gamma functions, computing P by
+/// series expansion for small `x` and Q by continued fraction otherwise.
+fn incomplete_gamma(a: FLOAT, x: FLOAT) -> (FLOAT, FLOAT) {
+    if x.is_nan() || a.is_nan() {
+        return (FLOAT::NAN, FLOAT::NAN);
+    }
+    if x <= 0.0 {
+        return (0.0, 1.0);
+    }
+    if x.is_infinite() {
+        return (1.0, 0.0);
+    }
+    let prefactor = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let (mut term, mut total, mut denominator) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..1000 {
+            denominator += 1.0;
+            term *= x / denominator;
+            total += term;
+            if term.abs() < total.abs() * 1e-15 {
+                break;
+            }
+        }
+        (total * prefactor, 1.0 - total * prefactor)
+    } else {
+        let q =
+            prefactor / continued_fraction(|i| (-i * (i - a), x + 2.0 * i + 1.0 - a), x + 1.0 - a);
+        (1.0 - q, q)
+    }
+}
+
+/// Regularized incomplete beta function I_x(a, b), computed by continued fraction.
+pub fn regularized_beta(a: FLOAT, b: FLOAT, x: FLOAT) -> FLOAT {
+    if x.is_nan() || a.is_nan() || b.is_nan() {
+        return FLOAT::NAN;
+    }
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    // The continued fraction converges quickly only on one side of the mean, so use the symmetry
+    // I_x(a, b) = 1 - I_{1-x}(b, a) on the other.
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - regularized_beta(b, a, 1.0 - x);
+    }
+    let prefactor =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    let fraction = continued_fraction(
+        |i| {
+            let m = ((i + 1.0) / 2.0).floor();
+            let numerator = if i as INT % 2 == 1 {
+                -(a + m - 1.0) * (a + b + m - 1.0) * x / ((a + 2.0 * m - 2.0) * (a + 2.0 * m - 1.0))
+            } else {
+                m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m))
+            };
+            (numerator, 1.0)
+        },
+        1.0,
+    );
+    prefactor / (a * fraction)
+}
+
+/// Evaluates the continued fraction b0 + a1 / (b1 + a2 / (b2 + ...)) with the modified Lentz
+/// method, where `terms(i)` returns the pair (a_i, b_i).
+fn continued_fraction<F>(terms: F, b0: FLOAT) -> FLOAT
+where
+    F: Fn(FLOAT) -> (FLOAT, FLOAT),
+{
+    let tiny = 1e-300;
+    let mut f = if b0 == 0.0 { tiny } else { b0 };
+    let (mut c, mut d) = (f, 0.0);
+    for i in 1..1000 {
+        let (a, b) = terms(i as FLOAT);
+        d = b + a * d;
+        d = if d == 0.0 { tiny } else { d };
+        c = b + a / c;
+        c = if c == 0.0 { tiny } else { c };
+        d = 1.0 / d;
+        let delta = c * d;
+        f *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    f
+}
+
+/// The error function.
+pub fn erf(x: FLOAT) -> FLOAT {
+    x.signum() * regularized_gamma_p(0.5, x * x)
+}
+
+/// Cumulative distribution function of the standard normal distribution. Each tail is computed
+/// from the complementary error function, so that small probabilities keep their precision.
+pub fn normal_cdf(z: FLOAT) -> FLOAT {
+    let tail = 0.5 * regularized_gamma_q(0.5, z * z / 2.0);
+    if z > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Inverse of the cumulative distribution function of the standard normal distribution, computed
+/// with Acklam's rational approximation followed by one step of Halley's method.
+pub fn normal_inv(p: FLOAT) -> FLOAT {
+    const A: [FLOAT; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [FLOAT; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [FLOAT; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [FLOAT; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    if p.is_nan() || !(0.0..=1.0).contains(&p) {
+        return FLOAT::NAN;
+    }
+    if p == 0.0 {
+        return FLOAT::NEG_INFINITY;
+    }
+    if p == 1.0 {
+        return FLOAT::INFINITY;
+    }
+
+    let polynomial =
+        |coefficients: &[FLOAT], x: FLOAT| coefficients.iter().fold(0.0, |acc, c| acc * x + c);
+    let tail = |q: FLOAT| {
+        let r = (-2.0 * q.ln()).sqrt();
+        polynomial(&C, r) / (polynomial(&D, r) * r + 1.0)
+    };
+    let x = if p < 0.02425 {
+        tail(p)
+    } else if p > 1.0 - 0.02425 {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.0)
+    };
+
+    let e = normal_cdf(x) - p;
+    let u = e * (2.0 * std::f64::consts::PI as FLOAT).sqrt() * (x * x / 2.0).exp();
+    x - u / (1.0 + x * u / 2.0)
+}
+
+/// Cumulative distribution function of Student's t distribution with `df` degrees of freedom.
+pub fn student_t_cdf(t: FLOAT, df: FLOAT) -> FLOAT {
+    let tail = 0.5 * regularized_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
@@ -0,0 +1,716 @@
+use rhai::plugin::*;
+
+#[export_module]
+pub mod polynomial_functions {
+    use crate::if_list_convert_to_vec_float_and_do;
+    use rhai::{Array, Dynamic, EvalAltResult, Map, Position, FLOAT, INT};
+    use std::collections::BTreeMap;
+
+    /// Converts the coefficients of a polynomial, in descending powers, into a vector of FLOAT
+    /// values, checking that there is at least one.
+    fn to_coefficients(mut p: Array) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        let p = if_list_convert_to_vec_float_and_do(&mut p, Ok)?;
+        if p.is_empty() {
+            Err(EvalAltResult::ErrorArithmetic(
+                "A polynomial must have at least one coefficient".to_string(),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok(p)
+        }
+    }
+
+    /// Wraps a vector of FLOAT values as an array.
+    fn to_array(values: Vec<FLOAT>) -> Array {
+        values.into_iter().map(Dynamic::from_float).collect()
+    }
+
+    /// Removes the leading zero coefficients of a polynomial, keeping at least one coefficient.
+    fn strip_leading_zeros(p: Vec<FLOAT>) -> Vec<FLOAT> {
+        match p.iter().position(|&c| c != 0.0) {
+            Some(idx) => p[idx..].to_vec(),
+            None => vec![0.0],
+        }
+    }
+
+    /// Evaluates a polynomial at a point with Horner's method.
+    fn horner(p: Vec<FLOAT>, x: FLOAT) -> FLOAT {
+        p.iter().fold(0.0, |acc, &c| acc * x + c)
+    }
+
+    /// Returns the full convolution of two vectors, which is the product of the polynomials that
+    /// they represent.
+    fn convolve(a: Vec<FLOAT>, b: Vec<FLOAT>) -> Vec<FLOAT> {
+        let mut c = vec![0.0; a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                c[i + j] += x * y;
+            }
+        }
+        c
+    }
+
+    /// Reads the real and imaginary parts of an INT, FLOAT, or Complex value.
+    fn to_complex_parts(x: Dynamic) -> Result<(FLOAT, FLOAT), Box<EvalAltResult>> {
+        if let Some(z) = x.clone().try_cast::<crate::Complex>() {
+            Ok((z.re, z.im))
+        } else {
+            crate::if_int_convert_to_float_and_do(x, |re| Ok((re, 0.0))).map_err(|_| {
+                EvalAltResult::ErrorArithmetic(
+                    "The elements of the input must be INT, FLOAT, or Complex".to_string(),
+                    Position::NONE,
+                )
+                .into()
+            })
+        }
+    }
+
+    /// Wraps complex values as an array of FLOAT values if they are all real, and as an array of
+    /// Complex values otherwise.
+    #[cfg(feature = "nalgebra")]
+    fn complex_or_real(values: Vec<nalgebralib::Complex<FLOAT>>) -> Array {
+        if values.iter().all(|z| z.im == 0.0) {
+            values
+                .into_iter()
+                .map(|z| Dynamic::from_float(z.re))
+                .collect()
+        } else {
+            values
+                .into_iter()
+                .map(|z| Dynamic::from(crate::Complex::from(z)))
+                .collect()
+        }
+    }
+
+    /// Fits a polynomial of degree `n` to the points `(x, y)` in the least squares sense, and
+    /// returns its coefficients in descending powers. See `polyfit(x, y, n, options)` to center
+    /// and scale `x` for better conditioning.
+    /// ```typescript
+    /// let x = [0, 1, 2, 3, 4];
+    /// let y = [1, 3, 7, 13, 21];
+    /// let p = polyfit(x, y, 2);
+    /// assert_approx_eq(p, [1.0, 1.0, 1.0]);
+    /// ```
+    /// ```typescript
+    /// let x = [0.0, 1.0, 2.0, 3.0];
+    /// let y = [0.1, 0.9, 2.1, 2.9];
+    /// let p = polyfit(x, y, 1);
+    /// assert_approx_eq(p, [0.96, 0.06]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "polyfit", return_raw)]
+    pub fn polyfit(x: Array, y: Array, n: INT) -> Result<Array, Box<EvalAltResult>> {
+        let x = if_list_convert_to_vec_float_and_do(&mut x.clone(), Ok)?;
+        let y = if_list_convert_to_vec_float_and_do(&mut y.clone(), Ok)?;
+        let (p, _, _) = polyfit_core(x, y, n)?;
+        Ok(to_array(p))
+    }
+
+    /// Fits a polynomial of degree `n` to the points `(x, y)` in the least squares sense, with
+    /// options: `center` (default true), which centers and scales `x` to zero mean and unit
+    /// standard deviation before fitting. Returns a map with the keys
+    /// - `p`, the coefficients in descending powers of `(x - mu[0]) / mu[1]`,
+    /// - `S`, a map with the triangular factor `R` of the QR decomposition of the Vandermonde
+    ///   matrix, the degrees of freedom `df` and the norm of the residuals `normr`,
+    /// - `mu`, the mean and standard deviation of `x`, or `[0.0, 1.0]` if `center` is false.
+    ///
+    /// Evaluate the result with `polyval(p, x, mu)`.
+    /// ```typescript
+    /// let x = [1000, 1001, 1002, 1003, 1004];
+    /// let y = [2.0, 2.5, 3.1, 3.4, 4.0];
+    /// let fit = polyfit(x, y, 1, #{});
+    /// assert_approx_eq(fit.mu, [1002.0, sqrt(2.5)]);
+    /// assert_eq(fit.S.df, 3);
+    /// assert_approx_eq(polyval(fit.p, 1002, fit.mu), 3.0);
+    /// ```
+    /// ```typescript
+    /// let result = "";
+    /// try { polyfit([2, 2, 2], [1, 2, 3], 1, #{}); } catch { result = "badly conditioned"; }
+    /// assert_eq(result, "badly conditioned");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "polyfit", return_raw)]
+    pub fn polyfit_with_options(
+        x: Array,
+        y: Array,
+        n: INT,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        if let Some(key) = options.keys().find(|k| k.as_str() != "center") {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown option '{key}', expected center"),
+                Position::NONE,
+            )
+            .into());
+        }
+        let center = match options.get("center") {
+            Some(center) => center.as_bool().map_err(|_| {
+                EvalAltResult::ErrorArithmetic(
+                    "The center option must be a bool".to_string(),
+                    Position::NONE,
+                )
+            })?,
+            None => true,
+        };
+        let x = if_list_convert_to_vec_float_and_do(&mut x.clone(), Ok)?;
+        let y = if_list_convert_to_vec_float_and_do(&mut y.clone(), Ok)?;
+        let mu = if center && x.len() > 1 {
+            let mean = x.iter().sum::<FLOAT>() / x.len() as FLOAT;
+            let variance =
+                x.iter().map(|v| (v - mean).powi(2)).sum::<FLOAT>() / (x.len() - 1) as FLOAT;
+            [mean, variance.sqrt()]
+        } else {
+            [0.0, 1.0]
+        };
+        if mu[1] == 0.0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The polynomial is badly conditioned; use more distinct x values or a lower degree"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let x_scaled = x.iter().map(|v| (v - mu[0]) / mu[1]).collect();
+        let (p, r, normr) = polyfit_core(x_scaled, y, n)?;
+
+        let mut s = BTreeMap::new();
+        s.insert(
+            "R".into(),
+            Dynamic::from_array(crate::omatrix_to_vec_dynamic(r)),
+        );
+        s.insert("df".into(), Dynamic::from_int(x.len() as INT - n - 1));
+        s.insert("normr".into(), Dynamic::from_float(normr));
+        let mut result = BTreeMap::new();
+        result.insert("p".into(), Dynamic::from_array(to_array(p)));
+        result.insert("S".into(), Dynamic::from_map(s));
+        result.insert("mu".into(), Dynamic::from_array(to_array(mu.to_vec())));
+        Ok(result)
+    }
+
+    /// Fits a polynomial of degree `n` with a QR decomposition of the Vandermonde matrix, and
+    /// returns its coefficients, the triangular factor and the norm of the residuals.
+    #[cfg(feature = "nalgebra")]
+    fn polyfit_core(
+        x: Vec<FLOAT>,
+        y: Vec<FLOAT>,
+        n: INT,
+    ) -> Result<(Vec<FLOAT>, nalgebralib::DMatrix<FLOAT>, FLOAT), Box<EvalAltResult>> {
+        if x.len() != y.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The x and y values must have the same length".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let cols = match n.checked_add(1) {
+            Some(cols) if n >= 0 && x.len() >= cols as usize => cols as usize,
+            _ => {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The degree must be nonnegative and less than the number of points".to_string(),
+                    Position::NONE,
+                )
+                .into())
+            }
+        };
+        let vandermonde =
+            nalgebralib::DMatrix::from_fn(x.len(), cols, |i, j| x[i].powi((cols - 1 - j) as i32));
+        let y = nalgebralib::DVector::from_vec(y);
+        let qr = vandermonde.clone().qr();
+        let r = qr.r();
+        let scale = r.diagonal().amax();
+        if r.diagonal()
+            .iter()
+            .any(|d| d.abs() <= scale * FLOAT::EPSILON * x.len() as FLOAT)
+        {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The polynomial is badly conditioned; use more distinct x values or a lower degree"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let p = r
+            .solve_upper_triangular(&(qr.q().transpose() * &y))
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "The least squares system could not be solved".to_string(),
+                    Position::NONE,
+                )
+            })?;
+        let normr = (y - vandermonde * &p).norm();
+        Ok((p.data.as_vec().clone(), r, normr))
+    }
+
+    /// Evaluates a polynomial with coefficients `p`, in descending powers, at a number or at each
+    /// element of an array.
+    /// ```typescript
+    /// let p = [3, 2, 1];
+    /// assert_eq(polyval(p, 2), 17.0);
+    /// assert_eq(polyval(p, [0, 1, 2]), [1.0, 6.0, 17.0]);
+    /// ```
+    /// ```typescript
+    /// let p = [1.0, 0.0, -1.0];
+    /// assert_eq(polyval(p, [[1, 2], [3, 4]]), [[0.0, 3.0], [8.0, 15.0]]);
+    /// ```
+    #[rhai_fn(name = "polyval", return_raw)]
+    pub fn polyval(p: Array, x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        let p = to_coefficients(p)?;
+        crate::if_broadcastable_do_elementwise(x, Dynamic::from_int(0), |el, _| {
+            crate::if_int_convert_to_float_and_do(el.clone(), |v| {
+                Ok(Dynamic::from_float(horner(p.clone(), v)))
+            })
+        })
+    }
+
+    /// Evaluates a polynomial with coefficients `p`, in descending powers, at a Complex number.
+    /// ```typescript
+    /// let z = polyval([1, 0, 1], complex(0, 1));
+    /// assert_eq(z, complex(0, 0));
+    /// ```
+    #[rhai_fn(name = "polyval", return_raw)]
+    pub fn polyval_complex(
+        p: Array,
+        z: crate::Complex,
+    ) -> Result<crate::Complex, Box<EvalAltResult>> {
+        let p = to_coefficients(p)?;
+        let (mut re, mut im) = (0.0, 0.0);
+        for c in p {
+            (re, im) = (re * z.re - im * z.im + c, re * z.im + im * z.re);
+        }
+        Ok(crate::Complex::new(re, im))
+    }
+
+    /// Evaluates a polynomial fitted with centering and scaling at a number or at each element of
+    /// an array, where `mu` holds the mean and standard deviation used to transform `x`, as
+    /// returned by `polyfit(x, y, n, options)`.
+    /// ```typescript
+    /// let p = [2.0, 1.0];
+    /// assert_eq(polyval(p, [10, 14], [12, 2]), [-1.0, 3.0]);
+    /// ```
+    #[rhai_fn(name = "polyval", return_raw)]
+    pub fn polyval_centered(
+        p: Array,
+        x: Dynamic,
+        mut mu: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let p = to_coefficients(p)?;
+        let mu = if_list_convert_to_vec_float_and_do(&mut mu, Ok)?;
+        if mu.len() != 2 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The centering and scaling values must be [mean, standard deviation]".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        crate::if_broadcastable_do_elementwise(x, Dynamic::from_int(0), |el, _| {
+            crate::if_int_convert_to_float_and_do(el.clone(), |v| {
+                Ok(Dynamic::from_float(horner(p.clone(), (v - mu[0]) / mu[1])))
+            })
+        })
+    }
+
+    /// Returns the roots of a polynomial with coefficients `p`, in descending powers, computed as
+    /// the eigenvalues of its companion matrix. The roots are sorted by decreasing magnitude, then
+    /// by decreasing real and imaginary parts. If any root is complex, all of the roots are
+    /// returned as Complex values.
+    /// ```typescript
+    /// let r = roots([1, -3, 2]);
+    /// assert_approx_eq(r, [2.0, 1.0]);
+    /// ```
+    /// ```typescript
+    /// let r = roots([1, 0, 4]);
+    /// assert_eq(type_of(r[0]), "Complex");
+    /// assert_approx_eq([r[0].re, r[0].im, r[1].re, r[1].im], [0.0, 2.0, 0.0, -2.0]);
+    /// ```
+    /// ```typescript
+    /// let r = roots([0, 2, -2, 0]);
+    /// assert_approx_eq(r, [1.0, 0.0]);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { roots([1, nan, 1]); } catch { result = "not finite"; }
+    /// assert_eq(result, "not finite");
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "roots", return_raw)]
+    pub fn roots(p: Array) -> Result<Array, Box<EvalAltResult>> {
+        Ok(complex_or_real(roots_core(to_coefficients(p)?)?))
+    }
+
+    /// Computes the roots of a polynomial, sorted as described for `roots`.
+    #[cfg(feature = "nalgebra")]
+    fn roots_core(p: Vec<FLOAT>) -> Result<Vec<nalgebralib::Complex<FLOAT>>, Box<EvalAltResult>> {
+        if p.iter().any(|c| !c.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The coefficients of the polynomial must be finite".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let p = strip_leading_zeros(p);
+        let trailing_zeros = p.iter().rev().take_while(|&&c| c == 0.0).count();
+        if trailing_zeros == p.len() {
+            return Ok(vec![]);
+        }
+        let p = &p[..p.len() - trailing_zeros];
+        let degree = p.len() - 1;
+
+        let mut values = if degree == 0 {
+            vec![]
+        } else {
+            let companion = nalgebralib::DMatrix::from_fn(degree, degree, |i, j| {
+                if i == 0 {
+                    -p[j + 1] / p[0]
+                } else if i == j + 1 {
+                    1.0
+                } else {
+                    0.0
+                }
+            });
+            match nalgebralib::linalg::Schur::try_new(
+                companion,
+                FLOAT::EPSILON,
+                crate::schur_iterations(degree),
+            ) {
+                Some(schur) => schur.complex_eigenvalues().iter().copied().collect(),
+                None => {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The eigenvalue computation failed to converge".to_string(),
+                        Position::NONE,
+                    )
+                    .into())
+                }
+            }
+        };
+        values.sort_by(|a: &nalgebralib::Complex<FLOAT>, b| {
+            b.norm()
+                .partial_cmp(&a.norm())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.re.partial_cmp(&a.re).unwrap_or(std::cmp::Ordering::Equal))
+                .then(b.im.partial_cmp(&a.im).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        values.extend(vec![nalgebralib::Complex::new(0.0, 0.0); trailing_zeros]);
+        Ok(values)
+    }
+
+    /// Returns the coefficients, in descending powers, of the monic polynomial with the given
+    /// roots, which may be INT, FLOAT, or Complex values. The coefficients are FLOAT values when
+    /// the complex roots come in conjugate pairs, and Complex values otherwise.
+    /// ```typescript
+    /// assert_eq(poly([2, 1]), [1.0, -3.0, 2.0]);
+    /// ```
+    /// ```typescript
+    /// let p = poly([complex(0, 2), complex(0, -2)]);
+    /// assert_eq(p, [1.0, 0.0, 4.0]);
+    /// ```
+    /// ```typescript
+    /// let p = poly([complex(0, 1)]);
+    /// assert_eq(p, [complex(1, 0), complex(0, -1)]);
+    /// ```
+    #[rhai_fn(name = "poly", return_raw)]
+    pub fn poly(r: Array) -> Result<Array, Box<EvalAltResult>> {
+        let r = r
+            .into_iter()
+            .map(to_complex_parts)
+            .collect::<Result<Vec<(FLOAT, FLOAT)>, Box<EvalAltResult>>>()?;
+        let mut c = vec![(1.0, 0.0)];
+        for &(re, im) in &r {
+            let mut next = c.clone();
+            next.push((0.0, 0.0));
+            for (i, &(a, b)) in c.iter().enumerate() {
+                next[i + 1].0 -= re * a - im * b;
+                next[i + 1].1 -= re * b + im * a;
+            }
+            c = next;
+        }
+
+        let conjugate_pairs = r.iter().all(|&(re, im)| {
+            im == 0.0
+                || r.iter().filter(|&&w| w == (re, -im)).count()
+                    == r.iter().filter(|&&w| w == (re, im)).count()
+        });
+        Ok(if conjugate_pairs {
+            c.into_iter()
+                .map(|(re, _)| Dynamic::from_float(re))
+                .collect()
+        } else {
+            c.into_iter()
+                .map(|(re, im)| Dynamic::from(crate::Complex::new(re, im)))
+                .collect()
+        })
+    }
+
+    /// Returns the convolution of two vectors, which is the product of the polynomials that they
+    /// represent.
+    /// ```typescript
+    /// assert_eq(conv([1, 2], [1, 3]), [1.0, 5.0, 6.0]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(conv([1, 0, -1], [2]), [2.0, 0.0, -2.0]);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { conv([[1, 2]], [1]); } catch { result = "not a list"; }
+    /// assert_eq(result, "not a list");
+    /// ```
+    #[rhai_fn(name = "conv", return_raw)]
+    pub fn conv(a: Array, b: Array) -> Result<Array, Box<EvalAltResult>> {
+        Ok(to_array(convolve(to_coefficients(a)?, to_coefficients(b)?)))
+    }
+
+    /// Divides the polynomial `b` by the polynomial `a`, returning a map with the quotient `q`
+    /// and the remainder `r`, such that `b` is `conv(a, q)` plus `r`. The remainder has the same
+    /// length as `b`. The first coefficient of `a` must be nonzero.
+    /// ```typescript
+    /// let result = deconv([1, 5, 6], [1, 2]);
+    /// assert_eq(result.q, [1.0, 3.0]);
+    /// assert_eq(result.r, [0.0, 0.0, 0.0]);
+    /// ```
+    /// ```typescript
+    /// let result = deconv([2, 3, 4], [1, 1]);
+    /// assert_eq(result.q, [2.0, 1.0]);
+    /// assert_eq(result.r, [0.0, 0.0, 3.0]);
+    /// ```
+    #[rhai_fn(name = "deconv", return_raw)]
+    pub fn deconv(b: Array, a: Array) -> Result<Map, Box<EvalAltResult>> {
+        let b = to_coefficients(b)?;
+        let a = to_coefficients(a)?;
+        if a[0] == 0.0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The first coefficient of the divisor must be nonzero".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let (q, r) = divide(b, a);
+        let mut result = BTreeMap::new();
+        result.insert("q".into(), Dynamic::from_array(to_array(q)));
+        result.insert("r".into(), Dynamic::from_array(to_array(r)));
+        Ok(result)
+    }
+
+    /// Divides the polynomial `b` by the polynomial `a`, whose first coefficient is nonzero, with
+    /// long division. Returns the quotient and the remainder, which has the same length as `b`.
+    fn divide(b: Vec<FLOAT>, a: Vec<FLOAT>) -> (Vec<FLOAT>, Vec<FLOAT>) {
+        if b.len() < a.len() {
+            return (vec![0.0], b);
+        }
+        let mut r = b;
+        let mut q = vec![0.0; r.len() - a.len() + 1];
+        for i in 0..q.len() {
+            q[i] = r[i] / a[0];
+            for (j, c) in a.iter().enumerate() {
+                r[i + j] -= q[i] * c;
+            }
+            r[i] = 0.0;
+        }
+        (q, r)
+    }
+
+    /// Returns the derivative of a polynomial with coefficients `p`, in descending powers.
+    /// ```typescript
+    /// assert_eq(polyder([3, 2, 1]), [6.0, 2.0]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(polyder([5]), [0.0]);
+    /// ```
+    #[rhai_fn(name = "polyder", return_raw)]
+    pub fn polyder(p: Array) -> Result<Array, Box<EvalAltResult>> {
+        Ok(to_array(derivative(to_coefficients(p)?)))
+    }
+
+    /// Returns the derivative of the product of two polynomials `a` and `b`.
+    /// ```typescript
+    /// assert_eq(polyder([1, 1], [1, -1]), [2.0, 0.0]);
+    /// ```
+    #[rhai_fn(name = "polyder", return_raw)]
+    pub fn polyder_product(a: Array, b: Array) -> Result<Array, Box<EvalAltResult>> {
+        Ok(to_array(derivative(convolve(
+            to_coefficients(a)?,
+            to_coefficients(b)?,
+        ))))
+    }
+
+    /// Differentiates a polynomial, removing leading zeros from the result.
+    fn derivative(p: Vec<FLOAT>) -> Vec<FLOAT> {
+        let degree = p.len() - 1;
+        strip_leading_zeros(
+            p[..degree]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| c * (degree - i) as FLOAT)
+                .collect(),
+        )
+    }
+
+    /// Returns the integral of a polynomial with coefficients `p`, in descending powers, with a
+    /// constant of integration of zero.
+    /// ```typescript
+    /// assert_eq(polyint([3, 2, 1]), [1.0, 1.0, 1.0, 0.0]);
+    /// ```
+    #[rhai_fn(name = "polyint", return_raw)]
+    pub fn polyint(p: Array) -> Result<Array, Box<EvalAltResult>> {
+        polyint_with_constant(p, Dynamic::from_float(0.0))
+    }
+
+    /// Returns the integral of a polynomial with coefficients `p`, in descending powers, with the
+    /// constant of integration `k`.
+    /// ```typescript
+    /// assert_eq(polyint([4, 0], 3), [2.0, 0.0, 3.0]);
+    /// ```
+    #[rhai_fn(name = "polyint", return_raw)]
+    pub fn polyint_with_constant(p: Array, k: Dynamic) -> Result<Array, Box<EvalAltResult>> {
+        let p = to_coefficients(p)?;
+        let k = crate::if_int_convert_to_float_and_do(k, Ok)?;
+        let degree = p.len();
+        let mut integral = p
+            .iter()
+            .enumerate()
+            .map(|(i, c)| c / (degree - i) as FLOAT)
+            .collect::<Vec<FLOAT>>();
+        integral.push(k);
+        Ok(to_array(integral))
+    }
+
+    /// Computes the partial fraction expansion of the ratio of two polynomials `b(s) / a(s)`,
+    /// returning a map with the residues `r`, the poles `p` and the direct term `k`, such that
+    /// `b(s) / a(s) = r[0] / (s - p[0]) + ... + r[n - 1] / (s - p[n - 1]) + k(s)`. A pole of
+    /// multiplicity `m` is repeated `m` times, and its residues are for increasing powers of
+    /// `1 / (s - p)`. Poles within a relative distance of 0.001 of each other are treated as
+    /// repeated. The poles are sorted as for `roots`, and the residues and poles are Complex
+    /// values if any pole is complex. The direct term is empty if the degree of `b` is less than
+    /// the degree of `a`.
+    /// ```typescript
+    /// let result = residue([-4, 8], [1, 6, 8]);
+    /// assert_approx_eq(result.r, [-12.0, 8.0]);
+    /// assert_approx_eq(result.p, [-4.0, -2.0]);
+    /// assert_eq(result.k, []);
+    /// ```
+    /// ```typescript
+    /// let result = residue([1, 0, 1], [1, 5, 8, 4]);
+    /// assert_approx_eq(result.r, [-1.0, -5.0, 2.0], 1e-6);
+    /// assert_approx_eq(result.p, [-2.0, -2.0, -1.0], 1e-6);
+    /// ```
+    /// ```typescript
+    /// let result = residue([2, 0, 0, 1, 0], [1, 0, 1]);
+    /// assert_approx_eq(result.k, [2.0, 0.0, -2.0]);
+    /// assert_approx_eq([result.r[0].re, result.r[0].im], [0.5, -1.0]);
+    /// assert_approx_eq([result.p[0].re, result.p[0].im], [0.0, 1.0]);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "residue", return_raw)]
+    pub fn residue(b: Array, a: Array) -> Result<Map, Box<EvalAltResult>> {
+        use nalgebralib::Complex;
+        let b = strip_leading_zeros(to_coefficients(b)?);
+        let a = strip_leading_zeros(to_coefficients(a)?);
+        if a.len() < 2 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The denominator must have a degree of at least one".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let (k, remainder) = if b.len() >= a.len() {
+            let (q, r) = divide(b, a.clone());
+            (q, strip_leading_zeros(r))
+        } else {
+            (vec![], b)
+        };
+
+        // Group repeated poles, replacing each group with its mean
+        let mut groups: Vec<(Complex<FLOAT>, usize)> = vec![];
+        for pole in roots_core(a.clone())? {
+            match groups
+                .iter_mut()
+                .find(|(p, _)| (*p - pole).norm() < 0.001 * p.norm().max(1.0))
+            {
+                Some((p, m)) => {
+                    *p = (*p * *m as FLOAT + pole) / (*m + 1) as FLOAT;
+                    *m += 1;
+                }
+                None => groups.push((pole, 1)),
+            }
+        }
+
+        let remainder = remainder
+            .iter()
+            .map(|&c| Complex::new(c, 0.0))
+            .collect::<Vec<Complex<FLOAT>>>();
+        let (mut residues, mut poles) = (vec![], vec![]);
+        for (idx, &(pole, multiplicity)) in groups.iter().enumerate() {
+            // The denominator without the factors of this pole
+            let mut deflated = vec![Complex::new(a[0], 0.0)];
+            for (other, &(q, m)) in groups.iter().enumerate() {
+                if other != idx {
+                    for _ in 0..m {
+                        deflated.push(Complex::new(0.0, 0.0));
+                        for i in (1..deflated.len()).rev() {
+                            deflated[i] = deflated[i] - q * deflated[i - 1];
+                        }
+                    }
+                }
+            }
+
+            // Expand both polynomials in powers of (s - pole) and divide the series
+            let numerator = taylor_coefficients(remainder.clone(), pole, multiplicity);
+            let denominator = taylor_coefficients(deflated, pole, multiplicity);
+            let mut series: Vec<Complex<FLOAT>> = vec![];
+            for k in 0..multiplicity {
+                let mut d = numerator[k];
+                for j in 1..=k {
+                    d -= denominator[j] * series[k - j];
+                }
+                series.push(d / denominator[0]);
+            }
+            for j in 1..=multiplicity {
+                residues.push(series[multiplicity - j]);
+                poles.push(pole);
+            }
+        }
+
+        let real = poles.iter().all(|z| z.im == 0.0);
+        let wrap = |values: Vec<Complex<FLOAT>>| {
+            if real {
+                to_array(values.into_iter().map(|z| z.re).collect())
+            } else {
+                values
+                    .into_iter()
+                    .map(|z| Dynamic::from(crate::Complex::from(z)))
+                    .collect()
+            }
+        };
+        let mut result = BTreeMap::new();
+        result.insert("r".into(), Dynamic::from_array(wrap(residues)));
+        result.insert("p".into(), Dynamic::from_array(wrap(poles)));
+        result.insert("k".into(), Dynamic::from_array(to_array(k)));
+        Ok(result)
+    }
+
+    /// Returns the first `count` coefficients of a polynomial expanded in powers of `(s - at)`,
+    /// in ascending powers, by repeated synthetic division.
+    #[cfg(feature = "nalgebra")]
+    fn taylor_coefficients(
+        p: Vec<nalgebralib::Complex<FLOAT>>,
+        at: nalgebralib::Complex<FLOAT>,
+        count: usize,
+    ) -> Vec<nalgebralib::Complex<FLOAT>> {
+        let mut current = p;
+        let mut coefficients = vec![];
+        for _ in 0..count {
+            if current.is_empty() {
+                coefficients.push(nalgebralib::Complex::new(0.0, 0.0));
+                continue;
+            }
+            let mut quotient = Vec::with_capacity(current.len() - 1);
+            let mut acc = nalgebralib::Complex::new(0.0, 0.0);
+            for &c in &current {
+                acc = acc * at + c;
+                quotient.push(acc);
+            }
+            coefficients.push(quotient.pop().unwrap());
+            current = quotient;
+        }
+        coefficients
+    }
+}
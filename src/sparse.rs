@@ -0,0 +1,452 @@
+use rhai::plugin::*;
+
+/// A sparse, two-dimensional matrix of FLOAT values, stored in compressed sparse row (CSR) format.
+#[cfg(feature = "nalgebra")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseMatrix(pub nalgebra_sparse::CsrMatrix<rhai::FLOAT>);
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra_sparse::CsrMatrix<rhai::FLOAT>> for SparseMatrix {
+    fn from(csr: nalgebra_sparse::CsrMatrix<rhai::FLOAT>) -> Self {
+        SparseMatrix(csr)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl std::fmt::Display for SparseMatrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} SparseMatrix with {} stored entries",
+            self.0.nrows(),
+            self.0.ncols(),
+            self.0.nnz()
+        )
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+#[export_module]
+pub mod sparse_functions {
+    use crate::{
+        if_list_convert_to_vec_float_and_do, if_matrix_convert_to_dmatrix_and_do,
+        omatrix_to_vec_dynamic,
+    };
+    use nalgebra_sparse::{CooMatrix, CscMatrix, CsrMatrix};
+    use nalgebralib::{DMatrix, DVector};
+    use rhai::{Array, Dynamic, EvalAltResult, Position, FLOAT, INT};
+
+    /// A sparse, two-dimensional matrix of FLOAT values. Create one with `sparse` or `speye`, and
+    /// turn it into a nested array with `full`.
+    pub type SparseMatrix = super::SparseMatrix;
+
+    /// Converts an array of INT values into zero-based indices.
+    fn to_indices(arr: Array) -> Result<Vec<usize>, Box<EvalAltResult>> {
+        arr.into_iter()
+            .map(|i| match i.as_int() {
+                Ok(i) if i >= 0 => Ok(i as usize),
+                _ => Err(EvalAltResult::ErrorArithmetic(
+                    "Indices must be nonnegative INT values".to_string(),
+                    Position::NONE,
+                )
+                .into()),
+            })
+            .collect()
+    }
+
+    /// Creates an `m x n` sparse matrix from arrays of zero-based row indices `i`, column indices
+    /// `j`, and values `v`, so that `s[i[k]][j[k]] = v[k]`. Values with the same indices are
+    /// added together.
+    /// ```typescript
+    /// let s = sparse([0, 1, 2, 2], [0, 1, 2, 2], [1, 2, 3, 4], 3, 4);
+    /// assert_eq(size(s), [3, 4]);
+    /// assert_eq(full(s), [[1.0, 0.0, 0.0, 0.0],
+    ///                     [0.0, 2.0, 0.0, 0.0],
+    ///                     [0.0, 0.0, 7.0, 0.0]]);
+    /// ```
+    #[rhai_fn(name = "sparse", return_raw)]
+    pub fn sparse(
+        i: Array,
+        j: Array,
+        mut v: Array,
+        m: INT,
+        n: INT,
+    ) -> Result<SparseMatrix, Box<EvalAltResult>> {
+        let rows = to_indices(i)?;
+        let cols = to_indices(j)?;
+        if_list_convert_to_vec_float_and_do(&mut v, |values| {
+            CooMatrix::try_from_triplets(
+                m.max(0) as usize,
+                n.max(0) as usize,
+                rows.clone(),
+                cols.clone(),
+                values,
+            )
+            .map(|coo| SparseMatrix(CsrMatrix::from(&coo)))
+            .map_err(|e| EvalAltResult::ErrorArithmetic(e.to_string(), Position::NONE).into())
+        })
+    }
+
+    /// Creates a sparse matrix from arrays of zero-based row indices `i`, column indices `j`, and
+    /// values `v`, with just enough rows and columns to hold the largest indices.
+    /// ```typescript
+    /// let s = sparse([0, 2], [1, 0], [5, 6]);
+    /// assert_eq(size(s), [3, 2]);
+    /// assert_eq(nnz(s), 2);
+    /// ```
+    #[rhai_fn(name = "sparse", return_raw)]
+    pub fn sparse_from_triplets(
+        i: Array,
+        j: Array,
+        v: Array,
+    ) -> Result<SparseMatrix, Box<EvalAltResult>> {
+        let m = to_indices(i.clone())?
+            .into_iter()
+            .max()
+            .map_or(0, |x| x + 1);
+        let n = to_indices(j.clone())?
+            .into_iter()
+            .max()
+            .map_or(0, |x| x + 1);
+        sparse(i, j, v, m as INT, n as INT)
+    }
+
+    /// Converts a dense matrix into a sparse matrix, keeping only the nonzero elements.
+    /// ```typescript
+    /// let s = sparse([[1, 0],
+    ///                 [0, 2]]);
+    /// assert_eq(nnz(s), 2);
+    /// ```
+    #[rhai_fn(name = "sparse", return_raw)]
+    pub fn sparse_from_dense(mut matrix: Array) -> Result<SparseMatrix, Box<EvalAltResult>> {
+        if_matrix_convert_to_dmatrix_and_do(&mut matrix, |dm| {
+            Ok(SparseMatrix(CsrMatrix::from(&dm)))
+        })
+    }
+
+    /// Converts a `Matrix` into a sparse matrix, keeping only the nonzero elements.
+    /// ```typescript
+    /// let s = sparse(to_matrix(eye(3)));
+    /// assert_eq(nnz(s), 3);
+    /// ```
+    #[rhai_fn(name = "sparse")]
+    pub fn sparse_from_native(matrix: crate::Matrix) -> SparseMatrix {
+        SparseMatrix(CsrMatrix::from(&matrix.0))
+    }
+
+    /// Returns an `n x n` sparse identity matrix.
+    /// ```typescript
+    /// let s = speye(50000);
+    /// assert_eq(size(s), [50000, 50000]);
+    /// assert_eq(nnz(s), 50000);
+    /// ```
+    #[rhai_fn(name = "speye")]
+    pub fn speye(n: INT) -> SparseMatrix {
+        SparseMatrix(CsrMatrix::identity(n.max(0) as usize))
+    }
+
+    /// Returns an `m x n` sparse matrix with ones on the main diagonal.
+    /// ```typescript
+    /// let s = speye(2, 3);
+    /// assert_eq(full(s), [[1.0, 0.0, 0.0],
+    ///                     [0.0, 1.0, 0.0]]);
+    /// ```
+    #[rhai_fn(name = "speye", return_raw)]
+    pub fn speye_rectangular(m: INT, n: INT) -> Result<SparseMatrix, Box<EvalAltResult>> {
+        let k = m.min(n).max(0);
+        let diagonal = (0..k).map(Dynamic::from_int).collect::<Array>();
+        sparse(
+            diagonal.clone(),
+            diagonal,
+            vec![Dynamic::FLOAT_ONE; k as usize],
+            m,
+            n,
+        )
+    }
+
+    /// Converts a sparse matrix into a dense nested array.
+    /// ```typescript
+    /// let s = speye(2);
+    /// assert_eq(full(s), eye(2));
+    /// ```
+    #[rhai_fn(name = "full", pure)]
+    pub fn full(s: &mut SparseMatrix) -> Array {
+        omatrix_to_vec_dynamic(DMatrix::from(&s.0))
+    }
+
+    /// Converts a sparse matrix into a dense `Matrix`.
+    /// ```typescript
+    /// let m = to_matrix(speye(2));
+    /// assert_eq(to_array(m), eye(2));
+    /// ```
+    #[rhai_fn(name = "to_matrix", pure)]
+    pub fn sparse_to_matrix(s: &mut SparseMatrix) -> crate::Matrix {
+        crate::Matrix(DMatrix::from(&s.0))
+    }
+
+    /// Returns the number of nonzero elements in a sparse matrix.
+    /// ```typescript
+    /// let s = sparse([0, 0, 1], [0, 1, 1], [1, -1, 3], 2, 2);
+    /// assert_eq(nnz(s), 3);
+    /// ```
+    /// ```typescript
+    /// let s = sparse([0, 0], [0, 0], [1, -1], 2, 2);
+    /// assert_eq(nnz(s), 0);
+    /// ```
+    #[rhai_fn(name = "nnz", pure)]
+    pub fn nnz_sparse(s: &mut SparseMatrix) -> INT {
+        s.0.values().iter().filter(|&&x| x != 0.0).count() as INT
+    }
+
+    /// Returns the number of rows and columns of a sparse matrix.
+    /// ```typescript
+    /// assert_eq(size(speye(2, 5)), [2, 5]);
+    /// ```
+    #[rhai_fn(name = "size", pure)]
+    pub fn size_sparse(s: &mut SparseMatrix) -> Array {
+        vec![
+            Dynamic::from_int(s.0.nrows() as INT),
+            Dynamic::from_int(s.0.ncols() as INT),
+        ]
+    }
+
+    /// Transposes a sparse matrix.
+    /// ```typescript
+    /// let s = transpose(sparse([0], [2], [5]));
+    /// assert_eq(full(s), [[0.0], [0.0], [5.0]]);
+    /// ```
+    #[rhai_fn(name = "transpose", pure)]
+    pub fn transpose_sparse(s: &mut SparseMatrix) -> SparseMatrix {
+        SparseMatrix(s.0.transpose())
+    }
+
+    /// Multiplies a sparse matrix by a vector (a 1-D array), returning a 1-D array.
+    /// ```typescript
+    /// let s = sparse([0, 1, 1], [0, 0, 1], [2, 1, 3]);
+    /// assert_eq(mtimes(s, [1, 2]), [2.0, 7.0]);
+    /// ```
+    /// ```typescript
+    /// let s = sparse([0, 1, 1], [0, 0, 1], [2, 1, 3]);
+    /// assert_eq(mtimes(s, [1, 2.0]), [2.0, 7.0]);
+    /// ```
+    #[rhai_fn(name = "mtimes", return_raw)]
+    pub fn mtimes_sparse_vector(
+        s: SparseMatrix,
+        mut x: Array,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if crate::is_int_or_float_list(&x) {
+            if_list_convert_to_vec_float_and_do(&mut x, |x| {
+                if x.len() != s.0.ncols() {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The length of the vector must match the number of columns".to_string(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                Ok((&s.0 * DVector::from_vec(x))
+                    .iter()
+                    .map(|&y| Dynamic::from_float(y))
+                    .collect())
+            })
+        } else {
+            if_matrix_convert_to_dmatrix_and_do(&mut x, |dm| {
+                if dm.nrows() != s.0.ncols() {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The number of rows of the dense matrix must match the number of columns of the sparse matrix".to_string(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                Ok(omatrix_to_vec_dynamic(&s.0 * dm))
+            })
+        }
+    }
+
+    /// Multiplies two sparse matrices, returning a sparse matrix.
+    /// ```typescript
+    /// let s = sparse([0, 1], [1, 0], [1, 1]);
+    /// assert_eq(full(mtimes(s, s)), eye(2));
+    /// ```
+    #[rhai_fn(name = "mtimes", return_raw)]
+    pub fn mtimes_sparse_sparse(
+        s1: SparseMatrix,
+        s2: SparseMatrix,
+    ) -> Result<SparseMatrix, Box<EvalAltResult>> {
+        if s1.0.ncols() != s2.0.nrows() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The number of columns of the first matrix must match the number of rows of the second".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        Ok(SparseMatrix(&s1.0 * &s2.0))
+    }
+
+    /// Tests whether a square sparse matrix is symmetric, allowing for rounding error relative to
+    /// its largest entry.
+    fn is_symmetric(s: &mut SparseMatrix) -> bool {
+        let scale =
+            s.0.values()
+                .iter()
+                .fold(0.0, |acc: FLOAT, v| acc.max(v.abs()));
+        let asymmetry = &s.0 - &s.0.transpose();
+        asymmetry.values().iter().all(|v| v.abs() <= 1e-10 * scale)
+    }
+
+    /// Solves `s * x = b` for a symmetric positive-definite sparse matrix `s` using a sparse
+    /// Cholesky decomposition. If `b` is a 1-D array, the result is too. Throws an error if the
+    /// matrix is not symmetric positive-definite.
+    /// ```typescript
+    /// let s = sparse([0, 0, 1, 1], [0, 1, 0, 1], [4, 1, 1, 3]);
+    /// let x = solve(s, [1, 2]);
+    /// assert_approx_eq(x, [1.0/11.0, 7.0/11.0]);
+    /// ```
+    /// ```typescript
+    /// let s = sparse([0, 0, 1, 1], [0, 1, 0, 1], [4, 1, 1 + 1e-14, 3]);
+    /// let x = solve(s, [1, 2.0]);
+    /// assert_approx_eq(x, [1.0/11.0, 7.0/11.0]);
+    /// ```
+    /// ```typescript
+    /// let s = sparse([0, 1], [1, 0], [1, 1]);
+    /// let result = "solved";
+    /// try { solve(s, [1, 2]); } catch { result = "not positive definite"; }
+    /// assert_eq(result, "not positive definite");
+    /// ```
+    #[rhai_fn(name = "solve", name = "mldivide", return_raw)]
+    pub fn solve_sparse(s: SparseMatrix, mut b: Array) -> Result<Array, Box<EvalAltResult>> {
+        let not_spd = || -> Box<EvalAltResult> {
+            EvalAltResult::ErrorArithmetic(
+                "Matrix is not symmetric positive definite".to_string(),
+                Position::NONE,
+            )
+            .into()
+        };
+        if s.0.nrows() != s.0.ncols() || !is_symmetric(&mut s.clone()) {
+            return Err(not_spd());
+        }
+        let cholesky = nalgebra_sparse::factorization::CscCholesky::factor(&CscMatrix::from(&s.0))
+            .map_err(|_| not_spd())?;
+
+        if crate::is_int_or_float_list(&b) {
+            if_list_convert_to_vec_float_and_do(&mut b, |b| {
+                if b.len() != s.0.nrows() {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The right-hand side must have as many rows as the matrix".to_string(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                Ok(cholesky
+                    .solve(&DVector::from_vec(b))
+                    .iter()
+                    .map(|&x| Dynamic::from_float(x))
+                    .collect())
+            })
+        } else {
+            if_matrix_convert_to_dmatrix_and_do(&mut b, |dm| {
+                if dm.nrows() != s.0.nrows() {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        "The right-hand side must have as many rows as the matrix".to_string(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+                Ok(omatrix_to_vec_dynamic(cholesky.solve(&dm)))
+            })
+        }
+    }
+
+    /// Solves `s * x = b` for a symmetric positive-definite sparse matrix `s` with the conjugate
+    /// gradient method, stopping when the relative residual `norm(b - s * x) / norm(b)` falls
+    /// below `1e-10` or after twice as many iterations as `s` has rows.
+    /// ```typescript
+    /// let s = sparse([0, 0, 1, 1], [0, 1, 0, 1], [4, 1, 1, 3]);
+    /// let x = pcg(s, [1, 2]);
+    /// assert_approx_eq(x, [1.0/11.0, 7.0/11.0]);
+    /// ```
+    #[rhai_fn(name = "pcg", return_raw)]
+    pub fn pcg(s: SparseMatrix, b: Array) -> Result<Array, Box<EvalAltResult>> {
+        let max_iterations = 2 * s.0.nrows() as INT;
+        pcg_with_options(s, b, 1e-10, max_iterations)
+    }
+
+    /// Solves `s * x = b` for a symmetric positive-definite sparse matrix `s` with the conjugate
+    /// gradient method, stopping when the relative residual falls below `tol`. Throws an error if
+    /// that does not happen within `max_iterations` iterations.
+    /// ```typescript
+    /// let n = 100;
+    /// let i = [];
+    /// let j = [];
+    /// let v = [];
+    /// for k in 0..n {
+    ///     i += k; j += k; v += 2.0;
+    ///     if k > 0 { i += k; j += k - 1; v += -1.0; }
+    ///     if k < n - 1 { i += k; j += k + 1; v += -1.0; }
+    /// }
+    /// let s = sparse(i, j, v, n, n);
+    /// let x = pcg(s, ones([n]), 1e-8, 200);
+    /// assert_approx_eq(mtimes(s, x), ones([n]), 1e-6);
+    /// ```
+    #[rhai_fn(name = "pcg", return_raw)]
+    pub fn pcg_with_options(
+        s: SparseMatrix,
+        mut b: Array,
+        tol: FLOAT,
+        max_iterations: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if s.0.nrows() != s.0.ncols() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The input must be a square matrix".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if_list_convert_to_vec_float_and_do(&mut b, |b| {
+            if b.len() != s.0.nrows() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The right-hand side must have as many rows as the matrix".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+            let b = DVector::from_vec(b);
+            let b_norm = b.norm();
+            let mut x = DVector::<FLOAT>::zeros(b.len());
+            let mut r = b.clone();
+            let mut p = r.clone();
+            let mut rr = r.dot(&r);
+            for _ in 0..max_iterations.max(0) {
+                if rr.sqrt() <= tol * b_norm {
+                    break;
+                }
+                let sp = &s.0 * &p;
+                let alpha = rr / p.dot(&sp);
+                x += alpha * &p;
+                r -= alpha * sp;
+                let rr_next = r.dot(&r);
+                p = &r + (rr_next / rr) * p;
+                rr = rr_next;
+            }
+
+            let relative_residual = rr.sqrt() / b_norm;
+            if relative_residual <= tol || b_norm == 0.0 {
+                Ok(x.iter().map(|&x| Dynamic::from_float(x)).collect())
+            } else {
+                Err(EvalAltResult::ErrorArithmetic(
+                    format!("The conjugate gradient method did not converge (relative residual {relative_residual:e})"),
+                    Position::NONE,
+                )
+                .into())
+            }
+        })
+    }
+
+    /// Returns a string describing the size and number of stored entries of a sparse matrix.
+    /// ```typescript
+    /// assert_eq(to_string(speye(3)), "3x3 SparseMatrix with 3 stored entries");
+    /// ```
+    #[rhai_fn(name = "to_string", name = "to_debug", pure)]
+    pub fn sparse_to_string(s: &mut SparseMatrix) -> String {
+        s.to_string()
+    }
+}
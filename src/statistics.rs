@@ -1,21 +1,94 @@
 use rhai::plugin::*;
 
+/// Returns a given percentile value for a 1-D array of data as a FLOAT. Scripts reach this
+/// through `prctile`, which also accepts matrices and N-D arrays.
+pub fn prctile(arr: &mut rhai::Array, p: Dynamic) -> Result<rhai::FLOAT, Box<EvalAltResult>> {
+    if arr.is_empty() {
+        return Err(EvalAltResult::ErrorArithmetic(
+            "Array must not be empty".to_string(),
+            Position::NONE,
+        )
+        .into());
+    }
+    if !p.is_float() && !p.is_int() {
+        return Err(EvalAltResult::ErrorArithmetic(
+            "Percentile value must either be INT or FLOAT".to_string(),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    crate::if_list_convert_to_vec_float_and_do(arr, move |mut float_array| {
+        match float_array.len() {
+            0 => unreachable!(),
+            1 => return Ok(float_array[0]),
+            _ => (),
+        }
+
+        // Sort, with any NaN last
+        float_array.sort_by(crate::compare_floats_nan_last);
+        if float_array[float_array.len() - 1].is_nan() {
+            return Ok(rhai::FLOAT::NAN);
+        }
+
+        let sorted_array = float_array
+            .iter()
+            .map(|el| Dynamic::from_float(*el))
+            .collect::<rhai::Array>();
+
+        let mut x = crate::matrix_functions::linspace(
+            Dynamic::from_int(0),
+            Dynamic::from_int(100),
+            float_array.len() as rhai::INT,
+        )?;
+        crate::misc_functions::interp1(&mut x, sorted_array, p.clone())
+    })
+}
+
+/// Returns the inter-quartile range for a 1-D array as a FLOAT. Scripts reach this through
+/// `iqr`, which also accepts matrices and N-D arrays.
+pub fn iqr(arr: &mut rhai::Array) -> Result<rhai::FLOAT, Box<EvalAltResult>> {
+    match (
+        prctile(arr, Dynamic::from_int(25)),
+        prctile(arr, Dynamic::from_int(75)),
+    ) {
+        (Ok(low), Ok(high)) => Ok(high - low),
+        (Ok(_), Err(high)) => Err(high),
+        (Err(low), Ok(_)) => Err(low),
+        (Err(low), Err(_)) => Err(low),
+    }
+}
+
 #[export_module]
 pub mod stats {
     use crate::{
         array_to_vec_float, array_to_vec_int, if_list_convert_to_vec_float_and_do, if_list_do,
-        if_list_do_int_or_do_float,
+        if_list_do_else_reduce_along_dim, if_list_do_int_or_do_float,
+        if_numeric_array_reduce_along_dim,
     };
-    #[cfg(feature = "nalgebra")]
-    use rhai::Map;
-    use rhai::{Array, Dynamic, EvalAltResult, Position, FLOAT, INT};
+    use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, Map, Position, FLOAT, INT};
 
-    #[cfg(feature = "nalgebra")]
     use std::collections::BTreeMap;
     use std::collections::HashMap;
 
+    pub use super::{iqr, prctile};
+
+    /// Throws an error if an array is empty.
+    fn check_not_empty(arr: &mut Array) -> Result<(), Box<EvalAltResult>> {
+        if arr.is_empty() {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The input array must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
     /// Return the highest value from a pair of numbers. Fails if the numbers are anything other
-    /// than INT or FLOAT.
+    /// than INT or FLOAT. If either input is an array, the highest values are taken element by
+    /// element, broadcasting the inputs against each other.
     /// ```typescript
     /// let the_higher_number = max(2, 3);
     /// assert_eq(the_higher_number, 3);
@@ -24,13 +97,25 @@ pub mod stats {
     /// let the_higher_number = max(2.0, 3.0);
     /// assert_eq(the_higher_number, 3.0);
     /// ```
+    /// ```typescript
+    /// let the_highest_numbers = max([1, 5, 2], [3, 2, 4]);
+    /// assert_eq(the_highest_numbers, [3, 5, 4]);
+    /// ```
     #[rhai_fn(name = "max", return_raw)]
     pub fn gen_max(a: Dynamic, b: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
-        array_max(&mut vec![a, b])
+        if a.is_array() || b.is_array() {
+            crate::if_broadcastable_do_elementwise(a, b, |x, y| {
+                array_max(&mut vec![x.clone(), y.clone()])
+            })
+        } else {
+            array_max(&mut vec![a, b])
+        }
     }
 
     /// Return the highest value from an array. Fails if the input is not an array, or if
-    /// it is an array with elements other than INT or FLOAT.
+    /// it is an array with elements other than INT or FLOAT. For a matrix or N-D array, the
+    /// highest values are taken along the first non-singleton dimension. The result is NaN if the
+    /// input contains NaN; use `nanmax` to ignore NaN values.
     /// ```typescript
     /// let the_highest_number = max([2, 3, 4, 5]);
     /// assert_eq(the_highest_number, 5);
@@ -39,25 +124,70 @@ pub mod stats {
     /// let the_highest_number = max([2, 3.0, 4.12, 5]);
     /// assert_eq(the_highest_number, 5.0);
     /// ```
+    /// ```typescript
+    /// let the_highest_numbers = max([[1, 5, 3],
+    ///                                [4, 2, 6]]);
+    /// assert_eq(the_highest_numbers, [[4, 5, 6]]);
+    /// ```
     #[rhai_fn(name = "max", return_raw)]
     pub fn array_max(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do_int_or_do_float(
-            arr,
-            |arr: &mut Array| {
-                let mut y = array_to_vec_int(arr);
-                y.sort();
-                Ok(Dynamic::from(y[y.len() - 1]))
-            },
-            |arr: &mut Array| {
-                let mut y = array_to_vec_float(arr);
-                y.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                Ok(Dynamic::from(y[y.len() - 1]))
-            },
-        )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            check_not_empty(arr)?;
+            if_list_do_int_or_do_float(
+                arr,
+                |arr: &mut Array| {
+                    let mut y = array_to_vec_int(arr);
+                    y.sort();
+                    Ok(Dynamic::from(y[y.len() - 1]))
+                },
+                |arr: &mut Array| {
+                    let mut y = array_to_vec_float(arr);
+                    y.sort_by(crate::compare_floats_nan_last);
+                    Ok(Dynamic::from(y[y.len() - 1]))
+                },
+            )
+        })
+    }
+
+    /// Return the highest values along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// The result has the same shape as the input, except that dimension `dim` has length 1. As
+    /// in MATLAB, the second argument must be an empty array, since `max(arr, 2)` compares each
+    /// element with 2.
+    /// ```typescript
+    /// let data = [[1, 5, 3],
+    ///             [4, 2, 6]];
+    /// assert_eq(max(data, [], 1), [[4, 5, 6]]);
+    /// assert_eq(max(data, [], 2), [[5], [6]]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(max([1, 5, 2], 3), [3, 5, 3]);
+    /// ```
+    #[rhai_fn(name = "max", return_raw)]
+    pub fn array_max_along_dim(
+        arr: &mut Array,
+        placeholder: Array,
+        dim: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        check_dim_placeholder(placeholder)?;
+        if_numeric_array_reduce_along_dim(arr, Some(dim), array_max)
+    }
+
+    /// Throws an error unless the placeholder before a dimension argument is an empty array.
+    fn check_dim_placeholder(placeholder: Array) -> Result<(), Box<EvalAltResult>> {
+        if placeholder.is_empty() {
+            Ok(())
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The second argument must be [] when a dimension is given".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
     }
 
     /// Return the lowest value from a pair of numbers. Fails if the numbers are anything other
-    /// than INT or FLOAT.
+    /// than INT or FLOAT. If either input is an array, the lowest values are taken element by
+    /// element, broadcasting the inputs against each other.
     ///
     /// ```typescript
     /// let the_lower_number = min(2, 3);
@@ -67,13 +197,24 @@ pub mod stats {
     /// let the_lower_number = min(2.0, 3.0);
     /// assert_eq(the_lower_number, 2.0);
     /// ```
+    /// ```typescript
+    /// let the_lowest_numbers = min([1, 5, 2], [3, 2, 4]);
+    /// assert_eq(the_lowest_numbers, [1, 2, 2]);
+    /// ```
     #[rhai_fn(name = "min", return_raw)]
     pub fn gen_min(a: Dynamic, b: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
-        array_min(&mut vec![a, b])
+        if a.is_array() || b.is_array() {
+            crate::if_broadcastable_do_elementwise(a, b, |x, y| {
+                array_min(&mut vec![x.clone(), y.clone()])
+            })
+        } else {
+            array_min(&mut vec![a, b])
+        }
     }
 
     /// Return the lowest value from an array. Fails if the input is not an array, or if
-    /// it is an array with elements other than INT or FLOAT.
+    /// it is an array with elements other than INT or FLOAT. The result is NaN if the input
+    /// contains NaN; use `nanmin` to ignore NaN values.
     ///
     /// ```typescript
     /// let the_lowest_number = min([2, 3, 4, 5]);
@@ -85,19 +226,50 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "min", return_raw, pure)]
     pub fn array_min(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do_int_or_do_float(
-            arr,
-            |arr: &mut Array| {
-                let mut y = array_to_vec_int(arr);
-                y.sort();
-                Ok(Dynamic::from(y[0]))
-            },
-            |arr: &mut Array| {
-                let mut y = array_to_vec_float(arr);
-                y.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                Ok(Dynamic::from(y[0]))
-            },
-        )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            check_not_empty(arr)?;
+            if_list_do_int_or_do_float(
+                arr,
+                |arr: &mut Array| {
+                    let mut y = array_to_vec_int(arr);
+                    y.sort();
+                    Ok(Dynamic::from(y[0]))
+                },
+                |arr: &mut Array| {
+                    let mut y = array_to_vec_float(arr);
+                    y.sort_by(crate::compare_floats_nan_last);
+                    // NaN is sorted last, so it is present if the last element is NaN
+                    if y[y.len() - 1].is_nan() {
+                        Ok(Dynamic::from_float(FLOAT::NAN))
+                    } else {
+                        Ok(Dynamic::from(y[0]))
+                    }
+                },
+            )
+        })
+    }
+
+    /// Return the lowest values along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// The result has the same shape as the input, except that dimension `dim` has length 1. As
+    /// in MATLAB, the second argument must be an empty array, since `min(arr, 0)` compares each
+    /// element with 0.
+    /// ```typescript
+    /// let data = [[1, 5, 3],
+    ///             [4, 2, 6]];
+    /// assert_eq(min(data, [], 1), [[1, 2, 3]]);
+    /// assert_eq(min(data, [], 2), [[1], [2]]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(min([1, 5, 2], 0), [0, 0, 0]);
+    /// ```
+    #[rhai_fn(name = "min", return_raw, pure)]
+    pub fn array_min_along_dim(
+        arr: &mut Array,
+        placeholder: Array,
+        dim: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        check_dim_placeholder(placeholder)?;
+        if_numeric_array_reduce_along_dim(arr, Some(dim), array_min)
     }
 
     /// Return the highest value from an array. Fails if the input is not an array, or if
@@ -116,8 +288,9 @@ pub mod stats {
         }
     }
 
-    /// Returns the `k` highest values from an array. Fails if the input is not an array, or if
-    /// it is an array with elements other than INT or FLOAT.
+    /// Returns the `k` highest values from an array, in ascending order. Fails if the input is not
+    /// an array, or if it is an array with elements other than INT or FLOAT. NaN is treated as
+    /// larger than any other value.
     /// ```typescript
     /// let data = [32, 15, -7, 10, 1000, 41, 42];
     /// let mk = maxk(data, 3);
@@ -130,6 +303,16 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "maxk", return_raw, pure)]
     pub fn maxk(arr: &mut Array, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        if k < 0 || k as usize > arr.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "k must be between 0 and the length of the array ({})",
+                    arr.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
         if_list_do_int_or_do_float(
             arr,
             |arr: &mut Array| {
@@ -144,7 +327,7 @@ pub mod stats {
             },
             |arr: &mut Array| {
                 let mut y = array_to_vec_float(arr);
-                y.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                y.sort_by(crate::compare_floats_nan_last);
                 let r = (y.len() - (k as usize))..(y.len());
                 let mut v = Array::new();
                 for idx in r {
@@ -155,8 +338,9 @@ pub mod stats {
         )
     }
 
-    /// Return the `k` lowest values in an array. Fails if the input is not an array, or if
-    /// it is an array with elements other than INT or FLOAT.
+    /// Return the `k` lowest values in an array, in ascending order. Fails if the input is not an
+    /// array, or if it is an array with elements other than INT or FLOAT. NaN is treated as larger
+    /// than any other value.
     /// ```typescript
     /// let data = [32, 15, -7, 10, 1000, 41, 42];
     /// let mk = mink(data, 3);
@@ -169,6 +353,16 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "mink", return_raw, pure)]
     pub fn mink(arr: &mut Array, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        if k < 0 || k as usize > arr.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "k must be between 0 and the length of the array ({})",
+                    arr.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
         if_list_do_int_or_do_float(
             arr,
             |arr| {
@@ -183,7 +377,7 @@ pub mod stats {
             },
             |arr| {
                 let mut y = array_to_vec_float(arr);
-                y.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                y.sort_by(crate::compare_floats_nan_last);
                 let r = (0 as usize)..(k as usize);
                 let mut v = Array::new();
                 for idx in r {
@@ -195,7 +389,8 @@ pub mod stats {
     }
 
     /// Sum an array. Fails if the input is not an array, or if
-    /// it is an array with elements other than INT or FLOAT.
+    /// it is an array with elements other than INT or FLOAT. A matrix or N-D array is summed along
+    /// its first non-singleton dimension.
     /// ```typescript
     /// let data = [1, 2, 3];
     /// let m = sum(data);
@@ -206,23 +401,49 @@ pub mod stats {
     /// let m = sum(data);
     /// assert_eq(m, 6.0);
     /// ```
+    /// ```typescript
+    /// let data = [[1, 2, 3],
+    ///             [4, 5, 6]];
+    /// assert_eq(sum(data), [[5, 7, 9]]);
+    /// assert_eq(sum([[1, 2, 3]]), 6);
+    /// ```
     #[rhai_fn(name = "sum", return_raw, pure)]
     pub fn sum(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do_int_or_do_float(
-            arr,
-            |arr| {
-                let y = array_to_vec_int(arr);
-                Ok(Dynamic::from_int(y.iter().sum()))
-            },
-            |arr| {
-                let y = array_to_vec_float(arr);
-                Ok(Dynamic::from_float(y.iter().sum()))
-            },
-        )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_do_int_or_do_float(
+                arr,
+                |arr| {
+                    let y = array_to_vec_int(arr);
+                    Ok(Dynamic::from_int(y.iter().sum()))
+                },
+                |arr| {
+                    let y = array_to_vec_float(arr);
+                    Ok(Dynamic::from_float(y.iter().sum()))
+                },
+            )
+        })
+    }
+
+    /// Sum a matrix or N-D array along dimension `dim` (counting from 1). The result has the same
+    /// shape as the input, except that dimension `dim` has length 1.
+    /// ```typescript
+    /// let data = [[[1, 2], [3, 4]],
+    ///             [[5, 6], [7, 8]]];
+    /// assert_eq(sum(data, 1), [[[6, 8], [10, 12]]]);
+    /// assert_eq(sum(data, 3), [[[3], [7]], [[11], [15]]]);
+    /// ```
+    /// ```typescript
+    /// let data = [[1, 2], [3, 4]];
+    /// assert_eq(sum(data, 9223372036854775807), data);
+    /// ```
+    #[rhai_fn(name = "sum", return_raw, pure)]
+    pub fn sum_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), sum)
     }
 
     /// Return the average of an array. Fails if the input is not an array, or if
-    /// it is an array with elements other than INT or FLOAT.
+    /// it is an array with elements other than INT or FLOAT. For a matrix or N-D array, the
+    /// average is taken along the first non-singleton dimension.
     /// ```typescript
     /// let data = [1, 2, 3];
     /// let m = mean(data);
@@ -230,14 +451,69 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "mean", return_raw, pure)]
     pub fn mean(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        let l = arr.len() as FLOAT;
-        if_list_do_int_or_do_float(
-            arr,
-            |arr: &mut Array| {
-                sum(arr).map(|s| Dynamic::from_float(s.as_int().unwrap() as FLOAT / l))
-            },
-            |arr: &mut Array| sum(arr).map(|s| Dynamic::from_float(s.as_float().unwrap() / l)),
-        )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let l = arr.len() as FLOAT;
+            if_list_do_int_or_do_float(
+                arr,
+                |arr: &mut Array| {
+                    sum(arr).map(|s| Dynamic::from_float(s.as_int().unwrap() as FLOAT / l))
+                },
+                |arr: &mut Array| sum(arr).map(|s| Dynamic::from_float(s.as_float().unwrap() / l)),
+            )
+        })
+    }
+
+    /// Return the average along dimension `dim` (counting from 1) of a matrix or N-D array. The
+    /// result has the same shape as the input, except that dimension `dim` has length 1.
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [3, 4]];
+    /// assert_eq(mean(data, 2), [[1.5], [3.5]]);
+    /// ```
+    #[rhai_fn(name = "mean", return_raw, pure)]
+    pub fn mean_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), mean)
+    }
+
+    /// Checks that a list of weights is numeric, non-negative and as long as the data it weights,
+    /// and returns it as FLOAT values.
+    fn check_weights(mut weights: Array, n: INT) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+        let w = if_list_convert_to_vec_float_and_do(&mut weights, Ok)?;
+        if w.len() as INT != n {
+            Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The weights must have the same length as the data ({} and {n})",
+                    w.len()
+                ),
+                Position::NONE,
+            )
+            .into())
+        } else if w.iter().any(|&v| v < 0.0) {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The weights must not be negative".to_string(),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok(w)
+        }
+    }
+
+    /// Return the weighted average of an array, where `weights` is a non-negative array of the
+    /// same length. For a matrix, the weighted average of each column is returned.
+    /// ```typescript
+    /// let data = [1, 2, 3];
+    /// assert_eq(mean(data, [3, 1, 0]), 1.25);
+    /// ```
+    #[rhai_fn(name = "mean", return_raw, pure)]
+    pub fn weighted_mean(arr: &mut Array, weights: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let w = check_weights(weights.clone(), arr.len() as INT)?;
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let total = x.iter().zip(w.iter()).map(|(a, b)| a * b).sum::<FLOAT>();
+                Ok(Dynamic::from_float(total / w.iter().sum::<FLOAT>()))
+            })
+        })
     }
 
     /// Return the index of the largest array element. Fails if the input is not an array, or if
@@ -249,17 +525,31 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "argmax", return_raw, pure)]
     pub fn argmax(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do(arr, |arr| {
-            array_max(arr).map(|m| {
-                Dynamic::from_int(
-                    arr.iter()
-                        .position(|r| format!("{r}") == format!("{m}"))
-                        .unwrap() as INT,
-                )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_do(arr, |arr| {
+                array_max(arr).map(|m| {
+                    Dynamic::from_int(
+                        arr.iter()
+                            .position(|r| format!("{r}") == format!("{m}"))
+                            .unwrap() as INT,
+                    )
+                })
             })
         })
     }
 
+    /// Return the indices of the largest elements along dimension `dim` (counting from 1) of a matrix
+    /// or N-D array.
+    /// ```typescript
+    /// let data = [[1, 5, 3],
+    ///             [4, 2, 6]];
+    /// assert_eq(argmax(data, 1), [[1, 0, 1]]);
+    /// ```
+    #[rhai_fn(name = "argmax", return_raw, pure)]
+    pub fn argmax_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), argmax)
+    }
+
     /// Return the index of the smallest array element. Fails if the input is not an array, or if
     /// it is an array with elements other than INT or FLOAT.
     /// ```typescript
@@ -269,17 +559,31 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "argmin", return_raw, pure)]
     pub fn argmin(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do(arr, |arr| {
-            array_min(arr).map(|m| {
-                Dynamic::from_int(
-                    arr.iter()
-                        .position(|r| format!("{r}") == format!("{m}"))
-                        .unwrap() as INT,
-                )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_do(arr, |arr| {
+                array_min(arr).map(|m| {
+                    Dynamic::from_int(
+                        arr.iter()
+                            .position(|r| format!("{r}") == format!("{m}"))
+                            .unwrap() as INT,
+                    )
+                })
             })
         })
     }
 
+    /// Return the indices of the smallest elements along dimension `dim` (counting from 1) of a
+    /// matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 5, 3],
+    ///             [4, 2, 6]];
+    /// assert_eq(argmin(data, 2), [[0], [1]]);
+    /// ```
+    #[rhai_fn(name = "argmin", return_raw, pure)]
+    pub fn argmin_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), argmin)
+    }
+
     /// Compute the product of an array. Fails if the input is not an array, or if
     /// it is an array with elements other than INT or FLOAT.
     /// ```typescript
@@ -294,23 +598,36 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "prod", return_raw, pure)]
     pub fn prod(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do_int_or_do_float(
-            arr,
-            |arr| {
-                let mut p = 1 as INT;
-                for el in arr {
-                    p *= el.as_int().unwrap()
-                }
-                Ok(Dynamic::from_int(p))
-            },
-            |arr| {
-                let mut p = 1.0 as FLOAT;
-                for el in arr {
-                    p *= el.as_float().unwrap()
-                }
-                Ok(Dynamic::from_float(p))
-            },
-        )
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_do_int_or_do_float(
+                arr,
+                |arr| {
+                    let mut p = 1 as INT;
+                    for el in arr {
+                        p *= el.as_int().unwrap()
+                    }
+                    Ok(Dynamic::from_int(p))
+                },
+                |arr| {
+                    let mut p = 1.0 as FLOAT;
+                    for el in arr {
+                        p *= el.as_float().unwrap()
+                    }
+                    Ok(Dynamic::from_float(p))
+                },
+            )
+        })
+    }
+
+    /// Compute the product along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [3, 4]];
+    /// assert_eq(prod(data, 1), [[3, 8]]);
+    /// ```
+    #[rhai_fn(name = "prod", return_raw, pure)]
+    pub fn prod_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), prod)
     }
 
     /// Returns the variance of a 1-D array.
@@ -321,16 +638,56 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "variance", return_raw, pure)]
     pub fn variance(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        let m = mean(arr).map(|med| med.as_float().unwrap())?;
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let m = mean(arr).map(|med| med.as_float().unwrap())?;
 
-        if_list_convert_to_vec_float_and_do(arr, |x| {
-            let mut sum = 0.0 as FLOAT;
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let mut sum = 0.0 as FLOAT;
 
-            for v in &x {
-                sum += (v - m).powi(2)
-            }
-            let d = sum / (x.len() as FLOAT - 1.0);
-            Ok(Dynamic::from_float(d))
+                for v in &x {
+                    sum += (v - m).powi(2)
+                }
+                let d = sum / (x.len() as FLOAT - 1.0);
+                Ok(Dynamic::from_float(d))
+            })
+        })
+    }
+
+    /// Returns the variance along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [3, 6]];
+    /// assert_eq(variance(data, 1), [[2.0, 8.0]]);
+    /// ```
+    #[rhai_fn(name = "variance", return_raw, pure)]
+    pub fn variance_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), variance)
+    }
+
+    /// Returns the weighted variance of an array, where `weights` is a non-negative array of the
+    /// same length. The weighted variance is normalized by the sum of the weights. For a matrix,
+    /// the weighted variance of each column is returned.
+    /// ```typescript
+    /// let data = [1, 2, 3, 4];
+    /// assert_eq(variance(data, [1, 1, 1, 1]), 1.25);
+    /// assert_eq(variance(data, [1, 0, 0, 1]), 2.25);
+    /// ```
+    #[rhai_fn(name = "variance", return_raw, pure)]
+    pub fn weighted_variance(
+        arr: &mut Array,
+        weights: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let m = weighted_mean(arr, weights.clone())?.as_float().unwrap();
+            let w = check_weights(weights.clone(), arr.len() as INT)?;
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let total = x
+                    .iter()
+                    .zip(w.iter())
+                    .map(|(a, b)| b * (a - m).powi(2))
+                    .sum::<FLOAT>();
+                Ok(Dynamic::from_float(total / w.iter().sum::<FLOAT>()))
+            })
         })
     }
 
@@ -342,198 +699,1643 @@ pub mod stats {
     /// ```
     #[rhai_fn(name = "std", return_raw, pure)]
     pub fn std(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        variance(arr).map(|v| Dynamic::from_float(v.as_float().unwrap().sqrt()))
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            variance(arr).map(|v| Dynamic::from_float(v.as_float().unwrap().sqrt()))
+        })
     }
 
-    /// Returns the variance of a 1-D array.
+    /// Returns the standard deviation along dimension `dim` (counting from 1) of a matrix or N-D
+    /// array.
     /// ```typescript
-    /// let data = [1, 2, 3, 4, 5];
-    /// let r = rms(data);
-    /// assert_eq(r, 3.3166247903554);
+    /// let data = [[1, 3, 5],
+    ///             [2, 2, 2]];
+    /// assert_eq(std(data, 2), [[2.0], [0.0]]);
     /// ```
-    #[rhai_fn(name = "rms", return_raw, pure)]
-    pub fn rms(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_convert_to_vec_float_and_do(arr, |arr| {
-            let mut sum = 0.0 as FLOAT;
-            for v in &arr {
-                sum += v.powi(2)
-            }
-            let d = sum / (arr.len() as FLOAT);
-            Ok(Dynamic::from_float(d.sqrt()))
+    #[rhai_fn(name = "std", return_raw, pure)]
+    pub fn std_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), std)
+    }
+
+    /// Returns the central moment of order `k` of an array, that is, the average of the `k`th power
+    /// of the deviations from the mean. For a matrix, the moment of each column is returned.
+    /// ```typescript
+    /// let data = [1, 2, 3, 4];
+    /// assert_eq(moment(data, 1), 0.0);
+    /// assert_eq(moment(data, 2), 1.25);
+    /// assert_eq(moment(data, 3), 0.0);
+    /// ```
+    #[rhai_fn(name = "moment", return_raw, pure)]
+    pub fn moment(arr: &mut Array, k: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if k < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The order of the moment must not be negative".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let m = mean(arr)?.as_float().unwrap();
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let total = x.iter().map(|v| (v - m).powi(k as i32)).sum::<FLOAT>();
+                Ok(Dynamic::from_float(total / x.len() as FLOAT))
+            })
         })
     }
 
-    /// Returns the variance of a 1-D array.
+    /// Returns the sample skewness of an array, which is biased. For a matrix, the skewness of
+    /// each column is returned.
     /// ```typescript
-    /// let data = [1, 1, 1, 1, 2, 5, 6, 7, 8];
-    /// let m = median(data);
-    /// assert_eq(m, 2.0);
+    /// let data = [1, 2, 3, 10];
+    /// assert_approx_eq(skewness(data), 1.0182337649086284);
     /// ```
-    #[rhai_fn(name = "median", return_raw, pure)]
-    pub fn median(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_convert_to_vec_float_and_do(arr, |mut x| {
-            x.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    #[rhai_fn(name = "skewness", return_raw, pure)]
+    pub fn skewness(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        skewness_with_bias(arr, true)
+    }
 
-            let med = if x.len() % 2 == 1 {
-                x[(x.len() - 1) / 2]
+    /// Returns the skewness of an array. If `bias` is false, the skewness is corrected for
+    /// statistical bias, which requires at least 3 values.
+    /// ```typescript
+    /// let data = [1, 2, 3, 10];
+    /// assert_approx_eq(skewness(data, false), 1.763632614803888);
+    /// ```
+    #[rhai_fn(name = "skewness", return_raw, pure)]
+    pub fn skewness_with_bias(arr: &mut Array, bias: bool) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let n = arr.len() as FLOAT;
+            let m2 = moment(arr, 2)?.as_float().unwrap();
+            let m3 = moment(arr, 3)?.as_float().unwrap();
+            let g1 = m3 / m2.powf(1.5);
+            Ok(Dynamic::from_float(if bias {
+                g1
+            } else if n < 3.0 {
+                FLOAT::NAN
             } else {
-                (x[x.len() / 2] + x[x.len() / 2 - 1]) / 2.0
-            };
-
-            Ok(Dynamic::from_float(med))
+                g1 * (n * (n - 1.0)).sqrt() / (n - 2.0)
+            }))
         })
     }
 
-    /// Returns the median absolute deviation of a 1-D array.
+    /// Returns the sample kurtosis of an array, which is biased. This is not the excess kurtosis,
+    /// so the kurtosis of the normal distribution is 3. For a matrix, the kurtosis of each column
+    /// is returned.
     /// ```typescript
-    /// let data = [1.0, 2.0, 3.0, 3.0, 4.0, 4.0, 4.0, 5.0, 5.5, 6.0, 6.0, 6.5, 7.0, 7.0, 7.5, 8.0, 9.0, 12.0, 52.0, 90.0];
-    /// let m = mad(data);
-    /// assert_eq(m, 2.0);
+    /// let data = [1, 2, 3, 10];
+    /// assert_approx_eq(kurtosis(data), 2.2304);
     /// ```
-    #[rhai_fn(name = "mad", return_raw, pure)]
-    pub fn mad(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        let m = median(arr).map(|med| med.as_float().unwrap())?;
+    #[rhai_fn(name = "kurtosis", return_raw, pure)]
+    pub fn kurtosis(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        kurtosis_with_bias(arr, true)
+    }
 
-        if_list_convert_to_vec_float_and_do(arr, |x| {
-            let mut dev = vec![];
-            for v in x {
-                dev.push(Dynamic::from_float((v - m).abs()));
-            }
-            median(&mut dev)
+    /// Returns the kurtosis of an array. If `bias` is false, the kurtosis is corrected for
+    /// statistical bias, which requires at least 4 values.
+    /// ```typescript
+    /// let data = [1, 2, 3, 10];
+    /// assert_approx_eq(kurtosis(data, false), 6.228);
+    /// ```
+    #[rhai_fn(name = "kurtosis", return_raw, pure)]
+    pub fn kurtosis_with_bias(arr: &mut Array, bias: bool) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let n = arr.len() as FLOAT;
+            let m2 = moment(arr, 2)?.as_float().unwrap();
+            let m4 = moment(arr, 4)?.as_float().unwrap();
+            let k = m4 / m2.powi(2);
+            Ok(Dynamic::from_float(if bias {
+                k
+            } else if n < 4.0 {
+                FLOAT::NAN
+            } else {
+                3.0 + (n - 1.0) / ((n - 2.0) * (n - 3.0)) * ((n + 1.0) * k - 3.0 * (n - 1.0))
+            }))
         })
     }
 
-    /// Returns a given percentile value for a 1-D array of data.
-    ///
-    /// The array must not be empty.
-    ///
-    /// If the percentile value is <= 0 or >= 100, returns the minimum and maximum values of the array respectively.
+    /// Returns the standard scores of an array, that is, the deviations from the mean divided by
+    /// the standard deviation. For a matrix, each column is standardized separately.
     /// ```typescript
-    /// let data = [1, 2, 0, 3, 4];
-    /// let p = prctile(data, 0);
-    /// assert_eq(p, 0.0);
+    /// let data = [1, 2, 3];
+    /// assert_eq(zscore(data), [-1.0, 0.0, 1.0]);
     /// ```
     /// ```typescript
-    /// let data = [1, 2, 0, 3, 4];
-    /// let p = prctile(data, 50);
-    /// assert_eq(p, 2.0);
+    /// let data = [[1, 10],
+    ///             [3, 30]];
+    /// assert_approx_eq(flatten(zscore(data)), [-sqrt(0.5), -sqrt(0.5), sqrt(0.5), sqrt(0.5)]);
     /// ```
+    #[rhai_fn(name = "zscore", return_raw, pure)]
+    pub fn zscore(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        normalize_with_method(arr, "zscore".into())
+    }
+
+    /// Normalizes an array using the z-score, as with `zscore`.
     /// ```typescript
-    /// let data = [1, 2, 0, 3, 4];
-    /// let p = prctile(data, 100);
-    /// assert_eq(p, 4.0);
+    /// let data = [1, 2, 3];
+    /// assert_eq(normalize(data), [-1.0, 0.0, 1.0]);
     /// ```
-    #[rhai_fn(name = "prctile", return_raw, pure)]
-    pub fn prctile(arr: &mut Array, p: Dynamic) -> Result<FLOAT, Box<EvalAltResult>> {
-        if arr.is_empty() {
-            return Err(EvalAltResult::ErrorArithmetic(
-                "Array must not be empty".to_string(),
-                Position::NONE,
-            )
-            .into());
-        }
-        if !p.is_float() && !p.is_int() {
-            return Err(EvalAltResult::ErrorArithmetic(
-                "Percentile value must either be INT or FLOAT".to_string(),
-                Position::NONE,
-            )
-            .into());
-        }
-
-        if_list_convert_to_vec_float_and_do(arr, move |mut float_array| {
-            match float_array.len() {
-                0 => unreachable!(),
-                1 => return Ok(float_array[0]),
-                _ => (),
-            }
-
-            // Sort
-            float_array.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    #[rhai_fn(name = "normalize", return_raw, pure)]
+    pub fn normalize(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        normalize_with_method(arr, "zscore".into())
+    }
 
-            let sorted_array = float_array
-                .iter()
-                .map(|el| Dynamic::from_float(*el))
-                .collect::<Array>();
+    /// Normalizes an array using the method "zscore" (center to mean 0 and scale to standard
+    /// deviation 1), "range" (rescale to the interval [0, 1]), "norm" (scale to a Euclidean norm
+    /// of 1), "center" (center to mean 0) or "scale" (scale to standard deviation 1). For a matrix,
+    /// each column is normalized separately.
+    /// ```typescript
+    /// let data = [1, 2, 5];
+    /// assert_eq(normalize(data, "range"), [0.0, 0.25, 1.0]);
+    /// assert_eq(normalize(data, "center"), [-1.6666666666666665, -0.6666666666666665, 2.3333333333333335]);
+    /// ```
+    /// ```typescript
+    /// let data = [3, 4];
+    /// assert_eq(normalize(data, "norm"), [0.6, 0.8]);
+    /// assert_eq(normalize(data, "scale"), [3.0 / sqrt(0.5), 4.0 / sqrt(0.5)]);
+    /// ```
+    #[rhai_fn(name = "normalize", return_raw, pure)]
+    pub fn normalize_with_method(
+        arr: &mut Array,
+        method: ImmutableString,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        crate::if_list_do_else_map_along_dim(arr, |arr| {
+            let (shift, scale) = match method.as_str() {
+                "zscore" => (
+                    mean(arr)?.as_float().unwrap(),
+                    std(arr)?.as_float().unwrap(),
+                ),
+                "range" => {
+                    let x = if_list_convert_to_vec_float_and_do(arr, Ok)?;
+                    let low = x.iter().copied().fold(FLOAT::INFINITY, FLOAT::min);
+                    let high = x.iter().copied().fold(FLOAT::NEG_INFINITY, FLOAT::max);
+                    (low, high - low)
+                }
+                "norm" => {
+                    let x = if_list_convert_to_vec_float_and_do(arr, Ok)?;
+                    (0.0, x.iter().map(|v| v * v).sum::<FLOAT>().sqrt())
+                }
+                "center" => (mean(arr)?.as_float().unwrap(), 1.0),
+                "scale" => (0.0, std(arr)?.as_float().unwrap()),
+                _ => {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        format!("Unknown normalization method '{method}', expected 'zscore', 'range', 'norm', 'center' or 'scale'"),
+                        Position::NONE,
+                    )
+                    .into())
+                }
+            };
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                Ok(x.iter()
+                    .map(|v| Dynamic::from_float((v - shift) / scale))
+                    .collect())
+            })
+        })
+    }
+
+    /// Returns the geometric mean of an array of positive values. For a matrix, the geometric mean
+    /// of each column is returned.
+    /// ```typescript
+    /// let data = [1, 2, 4];
+    /// assert_approx_eq(geomean(data), 2.0);
+    /// ```
+    #[rhai_fn(name = "geomean", return_raw, pure)]
+    pub fn geomean(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let total = x.iter().map(|v| v.ln()).sum::<FLOAT>();
+                Ok(Dynamic::from_float((total / x.len() as FLOAT).exp()))
+            })
+        })
+    }
+
+    /// Returns the harmonic mean of an array. For a matrix, the harmonic mean of each column is
+    /// returned.
+    /// ```typescript
+    /// let data = [1, 2, 4];
+    /// assert_approx_eq(harmean(data), 12.0 / 7.0);
+    /// ```
+    #[rhai_fn(name = "harmean", return_raw, pure)]
+    pub fn harmean(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let total = x.iter().map(|v| 1.0 / v).sum::<FLOAT>();
+                Ok(Dynamic::from_float(x.len() as FLOAT / total))
+            })
+        })
+    }
+
+    /// Returns the trimmed mean of an array, excluding the highest and lowest `pct / 2` percent of
+    /// the values (the number excluded from each end is rounded to the nearest integer, but never
+    /// so large that nothing is left). For a matrix, the trimmed mean of each column is returned.
+    /// ```typescript
+    /// let data = [1, 2, 3, 4, 5, 6, 7, 8, 9, 100];
+    /// assert_eq(trimmean(data, 20), 5.5);
+    /// ```
+    /// ```typescript
+    /// assert_eq(trimmean([1, 2, 3, 4], 75), 2.5);
+    /// assert_eq(trimmean([1, 2, 3, 40, 50], 99), 3.0);
+    /// ```
+    #[rhai_fn(name = "trimmean", return_raw, pure)]
+    pub fn trimmean(arr: &mut Array, pct: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        let pct = crate::if_int_convert_to_float_and_do(pct, Ok)?;
+        if !(0.0..100.0).contains(&pct) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The percentage must be at least 0 and less than 100".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_convert_to_vec_float_and_do(arr, |mut x| {
+                x.sort_by(crate::compare_floats_nan_last);
+                // Always keep at least one value (two for an even number of values)
+                let k = ((x.len() as FLOAT * pct / 200.0).round() as usize)
+                    .min(x.len().saturating_sub(1) / 2);
+                let kept = &x[k..x.len() - k];
+                Ok(Dynamic::from_float(
+                    kept.iter().sum::<FLOAT>() / kept.len() as FLOAT,
+                ))
+            })
+        })
+    }
+
+    /// Returns the variance of a 1-D array.
+    /// ```typescript
+    /// let data = [1, 2, 3, 4, 5];
+    /// let r = rms(data);
+    /// assert_eq(r, 3.3166247903554);
+    /// ```
+    #[rhai_fn(name = "rms", return_raw, pure)]
+    pub fn rms(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_convert_to_vec_float_and_do(arr, |arr| {
+                let mut sum = 0.0 as FLOAT;
+                for v in &arr {
+                    sum += v.powi(2)
+                }
+                let d = sum / (arr.len() as FLOAT);
+                Ok(Dynamic::from_float(d.sqrt()))
+            })
+        })
+    }
+
+    /// Returns the root mean square along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[3, 4],
+    ///             [4, 3]];
+    /// assert_eq(rms(data, 1), [[3.5355339059327378, 3.5355339059327378]]);
+    /// ```
+    #[rhai_fn(name = "rms", return_raw, pure)]
+    pub fn rms_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), rms)
+    }
+
+    /// Returns the median of a 1-D array. The result is NaN if the array is empty or contains NaN;
+    /// use `nanmedian` to ignore NaN values.
+    /// ```typescript
+    /// let data = [1, 1, 1, 1, 2, 5, 6, 7, 8];
+    /// let m = median(data);
+    /// assert_eq(m, 2.0);
+    /// ```
+    #[rhai_fn(name = "median", return_raw, pure)]
+    pub fn median(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            if_list_convert_to_vec_float_and_do(arr, |mut x| {
+                x.sort_by(crate::compare_floats_nan_last);
+
+                let med = if x.is_empty() || x[x.len() - 1].is_nan() {
+                    FLOAT::NAN
+                } else if x.len() % 2 == 1 {
+                    x[(x.len() - 1) / 2]
+                } else {
+                    (x[x.len() / 2] + x[x.len() / 2 - 1]) / 2.0
+                };
+
+                Ok(Dynamic::from_float(med))
+            })
+        })
+    }
+
+    /// Returns the median along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 9, 2],
+    ///             [4, 3, 8]];
+    /// assert_eq(median(data, 2), [[2.0], [4.0]]);
+    /// ```
+    #[rhai_fn(name = "median", return_raw, pure)]
+    pub fn median_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), median)
+    }
+
+    /// Returns the median absolute deviation of a 1-D array.
+    /// ```typescript
+    /// let data = [1.0, 2.0, 3.0, 3.0, 4.0, 4.0, 4.0, 5.0, 5.5, 6.0, 6.0, 6.5, 7.0, 7.0, 7.5, 8.0, 9.0, 12.0, 52.0, 90.0];
+    /// let m = mad(data);
+    /// assert_eq(m, 2.0);
+    /// ```
+    #[rhai_fn(name = "mad", return_raw, pure)]
+    pub fn mad(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            let m = median(arr).map(|med| med.as_float().unwrap())?;
+
+            if_list_convert_to_vec_float_and_do(arr, |x| {
+                let mut dev = vec![];
+                for v in x {
+                    dev.push(Dynamic::from_float((v - m).abs()));
+                }
+                median(&mut dev)
+            })
+        })
+    }
+
+    /// Returns the median absolute deviation along dimension `dim` (counting from 1) of a matrix or
+    /// N-D array.
+    /// ```typescript
+    /// let data = [[1, 2, 4],
+    ///             [1, 1, 1]];
+    /// assert_eq(mad(data, 2), [[1.0], [0.0]]);
+    /// ```
+    #[rhai_fn(name = "mad", return_raw, pure)]
+    pub fn mad_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), mad)
+    }
+
+    /// Returns a given percentile value for a 1-D array of data. For a matrix or N-D array, the
+    /// percentile is taken along the first non-singleton dimension.
+    ///
+    /// The array must not be empty.
+    ///
+    /// If the percentile value is <= 0 or >= 100, returns the minimum and maximum values of the array respectively.
+    /// The result is NaN if the array contains NaN; use `nanprctile` to ignore NaN values.
+    /// ```typescript
+    /// let data = [1, 2, 0, 3, 4];
+    /// let p = prctile(data, 0);
+    /// assert_eq(p, 0.0);
+    /// ```
+    /// ```typescript
+    /// let data = [1, 2, 0, 3, 4];
+    /// let p = prctile(data, 50);
+    /// assert_eq(p, 2.0);
+    /// ```
+    /// ```typescript
+    /// let data = [1, 2, 0, 3, 4];
+    /// let p = prctile(data, 100);
+    /// assert_eq(p, 4.0);
+    /// ```
+    /// ```typescript
+    /// let data = [[1.0, 2.0],
+    ///             [3.0, 4.0]];
+    /// assert_eq(prctile(data, 50), [[2.0, 3.0]]);
+    /// ```
+    #[rhai_fn(name = "prctile", return_raw, pure)]
+    pub fn array_prctile(arr: &mut Array, p: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| prctile(x, p.clone()).map(Dynamic::from_float))
+    }
+
+    /// Returns a given percentile value along dimension `dim` (counting from 1) of a matrix or N-D
+    /// array.
+    /// ```typescript
+    /// let data = [[1, 2, 0, 3, 4],
+    ///             [5, 6, 7, 8, 9]];
+    /// let p = prctile(data, 50, 2);
+    /// assert_eq(p, [[2.0], [7.0]]);
+    /// ```
+    #[rhai_fn(name = "prctile", return_raw, pure)]
+    pub fn prctile_along_dim(
+        arr: &mut Array,
+        p: Dynamic,
+        dim: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), |x| {
+            prctile(x, p.clone()).map(Dynamic::from_float)
+        })
+    }
+
+    /// Returns the inter-quartile range for a 1-D array. For a matrix or N-D array, the range is
+    /// taken along the first non-singleton dimension.
+    /// ```typescript
+    /// let data = [1, 1, 1, 1, 1, 1, 1, 5, 6, 9, 9, 9, 9, 9, 9, 9, 9];
+    /// let inter_quartile_range = iqr(data);
+    /// assert_eq(inter_quartile_range, 8.0);
+    /// ```
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [3, 6],
+    ///             [5, 10]];
+    /// assert_eq(iqr(data), [[2.0, 4.0]]);
+    /// ```
+    #[rhai_fn(name = "iqr", return_raw, pure)]
+    pub fn array_iqr(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| iqr(x).map(Dynamic::from_float))
+    }
+
+    /// Returns the inter-quartile range along dimension `dim` (counting from 1) of a matrix or N-D
+    /// array.
+    /// ```typescript
+    /// let data = [[1, 2, 3, 4, 5],
+    ///             [2, 4, 6, 8, 10]];
+    /// assert_eq(iqr(data, 2), [[2.0], [4.0]]);
+    /// ```
+    #[rhai_fn(name = "iqr", return_raw, pure)]
+    pub fn iqr_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), |x| iqr(x).map(Dynamic::from_float))
+    }
+
+    /// Returns the mode of a 1-D array. All NaN values count as the same value.
+    /// ```typescript
+    /// let data = [1, 2, 2, 2, 2, 3];
+    /// let m = mode(data);
+    /// assert_eq(m, 2);
+    /// ```
+    /// ```typescript
+    /// let data = [1.0, 2.0, 2.0, 2.0, 2.0, 3.0];
+    /// let m = mode(data);
+    /// assert_eq(m, 2.0);
+    /// ```
+    #[rhai_fn(name = "mode", return_raw, pure)]
+    pub fn mode(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |arr| {
+            check_not_empty(arr)?;
+            if_list_do_int_or_do_float(
+                arr,
+                |arr| {
+                    let v = array_to_vec_int(arr);
+
+                    let mut counts: HashMap<INT, usize> = HashMap::new();
+
+                    Ok(Dynamic::from_int(
+                        v.iter()
+                            .copied()
+                            .max_by_key(|&n| {
+                                let count = counts.entry(n).or_insert(0);
+                                *count += 1;
+                                *count
+                            })
+                            .unwrap(),
+                    ))
+                },
+                |arr| {
+                    let v = array_to_vec_float(arr);
+
+                    let mut counts: HashMap<String, usize> = HashMap::new();
+
+                    Ok(Dynamic::from_float(
+                        v.iter()
+                            .copied()
+                            .max_by_key(|&n| {
+                                let count = counts.entry(format!("{:?}", n)).or_insert(0);
+                                *count += 1;
+                                *count
+                            })
+                            .unwrap(),
+                    ))
+                },
+            )
+        })
+    }
+
+    /// Returns the mode along dimension `dim` (counting from 1) of a matrix or N-D array.
+    /// ```typescript
+    /// let data = [[1, 2, 2],
+    ///             [3, 3, 1]];
+    /// assert_eq(mode(data, 2), [[2], [3]]);
+    /// ```
+    #[rhai_fn(name = "mode", return_raw, pure)]
+    pub fn mode_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), mode)
+    }
+
+    /// Returns the elements of a list that are not NaN.
+    fn omit_nan(arr: &mut Array) -> Array {
+        arr.iter()
+            .filter(|el| !(el.is_float() && el.as_float().unwrap().is_nan()))
+            .cloned()
+            .collect()
+    }
+
+    /// Sums an array, ignoring NaN values. A matrix or N-D array is summed along its first
+    /// non-singleton dimension.
+    /// ```typescript
+    /// let data = [1.0, nan, 3.0];
+    /// assert_eq(nansum(data), 4.0);
+    /// ```
+    #[rhai_fn(name = "nansum", return_raw, pure)]
+    pub fn nansum(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| sum(&mut omit_nan(x)))
+    }
+
+    /// Sums a matrix or N-D array along dimension `dim` (counting from 1), ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1.0, nan],
+    ///             [3.0, 4.0]];
+    /// assert_eq(nansum(data, 2), [[1.0], [7.0]]);
+    /// ```
+    #[rhai_fn(name = "nansum", return_raw, pure)]
+    pub fn nansum_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nansum)
+    }
+
+    /// Returns the average of an array, ignoring NaN values.
+    /// ```typescript
+    /// let data = [1.0, nan, 3.0];
+    /// assert_eq(nanmean(data), 2.0);
+    /// ```
+    #[rhai_fn(name = "nanmean", return_raw, pure)]
+    pub fn nanmean(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| mean(&mut omit_nan(x)))
+    }
+
+    /// Returns the average along dimension `dim` (counting from 1) of a matrix or N-D array,
+    /// ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1.0, nan],
+    ///             [3.0, 4.0]];
+    /// assert_eq(nanmean(data, 1), [[2.0, 4.0]]);
+    /// ```
+    #[rhai_fn(name = "nanmean", return_raw, pure)]
+    pub fn nanmean_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nanmean)
+    }
+
+    /// Returns the variance of an array, ignoring NaN values.
+    /// ```typescript
+    /// let data = [1.0, nan, 2.0, 3.0];
+    /// assert_eq(nanvar(data), 1.0);
+    /// ```
+    #[rhai_fn(name = "nanvar", return_raw, pure)]
+    pub fn nanvar(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| variance(&mut omit_nan(x)))
+    }
+
+    /// Returns the variance along dimension `dim` (counting from 1) of a matrix or N-D array,
+    /// ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1.0, nan, 3.0],
+    ///             [1.0, 2.0, 3.0]];
+    /// assert_eq(nanvar(data, 2), [[2.0], [1.0]]);
+    /// ```
+    #[rhai_fn(name = "nanvar", return_raw, pure)]
+    pub fn nanvar_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nanvar)
+    }
+
+    /// Returns the standard deviation of an array, ignoring NaN values.
+    /// ```typescript
+    /// let data = [1.0, 2.0, nan, 3.0];
+    /// assert_eq(nanstd(data), 1.0);
+    /// ```
+    #[rhai_fn(name = "nanstd", return_raw, pure)]
+    pub fn nanstd(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| std(&mut omit_nan(x)))
+    }
+
+    /// Returns the standard deviation along dimension `dim` (counting from 1) of a matrix or N-D
+    /// array, ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1.0, 3.0],
+    ///             [nan, 5.0],
+    ///             [3.0, 7.0]];
+    /// assert_eq(nanstd(data, 1), [[sqrt(2.0), 2.0]]);
+    /// ```
+    #[rhai_fn(name = "nanstd", return_raw, pure)]
+    pub fn nanstd_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nanstd)
+    }
+
+    /// Returns the median of an array, ignoring NaN values.
+    /// ```typescript
+    /// let data = [5.0, nan, 1.0, 3.0];
+    /// assert_eq(nanmedian(data), 3.0);
+    /// ```
+    #[rhai_fn(name = "nanmedian", return_raw, pure)]
+    pub fn nanmedian(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| median(&mut omit_nan(x)))
+    }
+
+    /// Returns the median along dimension `dim` (counting from 1) of a matrix or N-D array,
+    /// ignoring NaN values.
+    /// ```typescript
+    /// let data = [[5.0, nan, 1.0],
+    ///             [2.0, 4.0, 9.0]];
+    /// assert_eq(nanmedian(data, 2), [[3.0], [4.0]]);
+    /// ```
+    #[rhai_fn(name = "nanmedian", return_raw, pure)]
+    pub fn nanmedian_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nanmedian)
+    }
+
+    /// Returns the highest value in an array, ignoring NaN values. The result is NaN if every value
+    /// is NaN.
+    /// ```typescript
+    /// let data = [1.0, nan, 3.0];
+    /// assert_eq(nanmax(data), 3.0);
+    /// ```
+    #[rhai_fn(name = "nanmax", return_raw, pure)]
+    pub fn nanmax(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| {
+            let mut y = omit_nan(x);
+            if y.is_empty() && !x.is_empty() {
+                Ok(Dynamic::from_float(FLOAT::NAN))
+            } else {
+                array_max(&mut y)
+            }
+        })
+    }
+
+    /// Returns the highest values along dimension `dim` (counting from 1) of a matrix or N-D array,
+    /// ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1.0, nan],
+    ///             [nan, 2.0]];
+    /// assert_eq(nanmax(data, 2), [[1.0], [2.0]]);
+    /// ```
+    #[rhai_fn(name = "nanmax", return_raw, pure)]
+    pub fn nanmax_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nanmax)
+    }
+
+    /// Returns the lowest value in an array, ignoring NaN values. The result is NaN if every value
+    /// is NaN.
+    /// ```typescript
+    /// let data = [nan, 2.0, 3.0];
+    /// assert_eq(nanmin(data), 2.0);
+    /// ```
+    #[rhai_fn(name = "nanmin", return_raw, pure)]
+    pub fn nanmin(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| {
+            let mut y = omit_nan(x);
+            if y.is_empty() && !x.is_empty() {
+                Ok(Dynamic::from_float(FLOAT::NAN))
+            } else {
+                array_min(&mut y)
+            }
+        })
+    }
+
+    /// Returns the lowest values along dimension `dim` (counting from 1) of a matrix or N-D array,
+    /// ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1.0, nan],
+    ///             [nan, 2.0]];
+    /// assert_eq(nanmin(data, 1), [[1.0, 2.0]]);
+    /// ```
+    #[rhai_fn(name = "nanmin", return_raw, pure)]
+    pub fn nanmin_along_dim(arr: &mut Array, dim: INT) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), nanmin)
+    }
+
+    /// Returns a given percentile value for an array, ignoring NaN values. The result is NaN if
+    /// every value is NaN.
+    /// ```typescript
+    /// let data = [1, 2, nan, 0, 3, 4];
+    /// assert_eq(nanprctile(data, 50), 2.0);
+    /// ```
+    #[rhai_fn(name = "nanprctile", return_raw, pure)]
+    pub fn nanprctile(arr: &mut Array, p: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_list_do_else_reduce_along_dim(arr, |x| {
+            let mut y = omit_nan(x);
+            if y.is_empty() && !x.is_empty() {
+                Ok(Dynamic::from_float(FLOAT::NAN))
+            } else {
+                prctile(&mut y, p.clone()).map(Dynamic::from_float)
+            }
+        })
+    }
+
+    /// Returns a given percentile value along dimension `dim` (counting from 1) of a matrix or N-D
+    /// array, ignoring NaN values.
+    /// ```typescript
+    /// let data = [[1, nan, 3],
+    ///             [4, 5, 6]];
+    /// assert_eq(nanprctile(data, 50, 2), [[2.0], [5.0]]);
+    /// ```
+    #[rhai_fn(name = "nanprctile", return_raw, pure)]
+    pub fn nanprctile_along_dim(
+        arr: &mut Array,
+        p: Dynamic,
+        dim: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if_numeric_array_reduce_along_dim(arr, Some(dim), |x| nanprctile(x, p.clone()))
+    }
+
+    /// Applies a test to every element of a number or numeric array, returning BOOL values with
+    /// the same shape.
+    fn test_elements(x: Dynamic, test: fn(FLOAT) -> bool) -> Result<Dynamic, Box<EvalAltResult>> {
+        crate::if_broadcastable_do_elementwise(x, Dynamic::from_int(0), |el, _| {
+            Ok(Dynamic::from_bool(test(if el.is_float() {
+                el.as_float().unwrap()
+            } else {
+                el.as_int().unwrap() as FLOAT
+            })))
+        })
+    }
+
+    /// Tests whether a number, or each element of a numeric array, is NaN.
+    /// ```typescript
+    /// assert_eq(isnan(nan), true);
+    /// assert_eq(isnan([1, 2.0, nan]), [false, false, true]);
+    /// ```
+    #[rhai_fn(name = "isnan", return_raw)]
+    pub fn isnan(x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        test_elements(x, FLOAT::is_nan)
+    }
+
+    /// Tests whether a number, or each element of a numeric array, is infinite.
+    /// ```typescript
+    /// assert_eq(isinf([[1, -inf], [inf, nan]]), [[false, true], [true, false]]);
+    /// ```
+    #[rhai_fn(name = "isinf", return_raw)]
+    pub fn isinf(x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        test_elements(x, FLOAT::is_infinite)
+    }
+
+    /// Tests whether a number, or each element of a numeric array, is finite (neither infinite nor
+    /// NaN).
+    /// ```typescript
+    /// assert_eq(isfinite([1, 2.5, inf, nan]), [true, true, false, false]);
+    /// ```
+    #[rhai_fn(name = "isfinite", return_raw)]
+    pub fn isfinite(x: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+        test_elements(x, FLOAT::is_finite)
+    }
+
+    /// Fills the NaN values of a list using the given method, replacing them with `value` for the
+    /// "constant" method.
+    fn fill_list(x: Vec<FLOAT>, method: ImmutableString, value: FLOAT) -> Array {
+        let known = (0..x.len())
+            .filter(|&i| !x[i].is_nan())
+            .collect::<Vec<usize>>();
+        let previous = |i: usize| known.iter().rev().find(|&&k| k < i).copied();
+        let next = |i: usize| known.iter().find(|&&k| k > i).copied();
+        let linear = |i: usize, a: usize, b: usize| {
+            x[a] + (x[b] - x[a]) * (i as FLOAT - a as FLOAT) / (b as FLOAT - a as FLOAT)
+        };
+
+        let mut filled = x.clone();
+        for (i, el) in filled.iter_mut().enumerate().filter(|(_, el)| el.is_nan()) {
+            *el = match method.as_str() {
+                "constant" => value,
+                "previous" => previous(i).map_or(FLOAT::NAN, |k| x[k]),
+                "next" => next(i).map_or(FLOAT::NAN, |k| x[k]),
+                "nearest" => match (previous(i), next(i)) {
+                    (Some(p), Some(n)) if n - i < i - p => x[n],
+                    (Some(p), _) => x[p],
+                    (None, Some(n)) => x[n],
+                    (None, None) => FLOAT::NAN,
+                },
+                "linear" => match (previous(i), next(i)) {
+                    _ if known.len() < 2 => FLOAT::NAN,
+                    (Some(p), Some(n)) => linear(i, p, n),
+                    (None, _) => linear(i, known[0], known[1]),
+                    (_, None) => linear(i, known[known.len() - 2], known[known.len() - 1]),
+                },
+                _ => unreachable!(),
+            };
+        }
+        filled.into_iter().map(Dynamic::from_float).collect()
+    }
+
+    /// Fills the NaN values in an array using the method "previous" (the last value before it),
+    /// "next" (the first value after it), "nearest" (the closest value, preferring the previous
+    /// one in a tie), or "linear" (interpolating between, or extrapolating from, the closest
+    /// values). Values that cannot be filled are left as NaN. Each column of a matrix (or each
+    /// slice along the first non-singleton dimension of an N-D array) is filled separately.
+    /// ```typescript
+    /// let data = [nan, 1.0, nan, nan, 4.0, nan];
+    /// assert_eq(fillmissing(data, "previous"), [nan, 1.0, 1.0, 1.0, 4.0, 4.0]);
+    /// assert_eq(fillmissing(data, "next"), [1.0, 1.0, 4.0, 4.0, 4.0, nan]);
+    /// assert_eq(fillmissing(data, "nearest"), [1.0, 1.0, 1.0, 4.0, 4.0, 4.0]);
+    /// assert_eq(fillmissing(data, "linear"), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// ```
+    /// ```typescript
+    /// let data = [[1.0, nan],
+    ///             [nan, 4.0],
+    ///             [3.0, 6.0]];
+    /// assert_eq(fillmissing(data, "linear"), [[1.0, 2.0], [2.0, 4.0], [3.0, 6.0]]);
+    /// ```
+    #[rhai_fn(name = "fillmissing", return_raw, pure)]
+    pub fn fillmissing(
+        arr: &mut Array,
+        method: ImmutableString,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if method == "constant" {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The 'constant' method requires a fill value".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        fillmissing_with_value(arr, method, Dynamic::from_float(FLOAT::NAN))
+    }
+
+    /// Fills the NaN values in an array with a constant value, using the method "constant".
+    /// ```typescript
+    /// let data = [1.0, nan, 3.0];
+    /// assert_eq(fillmissing(data, "constant", 0.0), [1.0, 0.0, 3.0]);
+    /// ```
+    #[rhai_fn(name = "fillmissing", return_raw, pure)]
+    pub fn fillmissing_with_value(
+        arr: &mut Array,
+        method: ImmutableString,
+        value: Dynamic,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if !["constant", "previous", "next", "nearest", "linear"].contains(&method.as_str()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown fill method '{method}', expected 'constant', 'previous', 'next', 'nearest' or 'linear'"),
+                Position::NONE,
+            )
+            .into());
+        }
+        let value = crate::if_int_convert_to_float_and_do(value, Ok)?;
+        crate::if_list_do_else_map_along_dim(arr, |x| {
+            if_list_do_int_or_do_float(
+                x,
+                |x| Ok(x.clone()),
+                |x| Ok(fill_list(array_to_vec_float(x), method.clone(), value)),
+            )
+        })
+    }
+
+    /// Removes the NaN values from a list, or the rows containing NaN from a matrix.
+    /// ```typescript
+    /// assert_eq(rmmissing([1.0, nan, 3.0]), [1.0, 3.0]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(rmmissing([1, nan, 3]), [1, 3]);
+    /// ```
+    /// ```typescript
+    /// let data = [[1.0, 2.0],
+    ///             [nan, 4.0],
+    ///             [5.0, 6.0]];
+    /// assert_eq(rmmissing(data), [[1.0, 2.0], [5.0, 6.0]]);
+    /// ```
+    #[rhai_fn(name = "rmmissing", return_raw, pure)]
+    pub fn rmmissing(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        if arr.iter().any(|el| el.is_array()) {
+            crate::if_matrix_convert_to_vec_array_and_do(arr, |mut matrix_as_vec| {
+                Ok(matrix_as_vec
+                    .iter_mut()
+                    .filter(|row| {
+                        !row.iter()
+                            .any(|el| el.is_float() && el.as_float().unwrap().is_nan())
+                    })
+                    .map(|row| Dynamic::from_array(row.clone()))
+                    .collect())
+            })
+        } else if crate::is_int_or_float_list(arr) {
+            Ok(omit_nan(arr))
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                "The elements of the input array must either be INT or FLOAT".to_string(),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Converts two lists of INT and/or FLOAT values to FLOAT vectors, checking that they are the
+    /// same, non-zero length.
+    fn paired_lists(
+        x: &mut Array,
+        mut y: Array,
+    ) -> Result<(Vec<FLOAT>, Vec<FLOAT>), Box<EvalAltResult>> {
+        let x = if_list_convert_to_vec_float_and_do(x, Ok)?;
+        let y = if_list_convert_to_vec_float_and_do(&mut y, Ok)?;
+        if x.len() != y.len() || x.is_empty() {
+            Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The input arrays must have the same, non-zero length ({} and {})",
+                    x.len(),
+                    y.len()
+                ),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok((x, y))
+        }
+    }
+
+    /// Sample covariance (normalized by N - 1, or by N for a single observation) of two vectors
+    /// of equal length.
+    fn covariance(x: Vec<FLOAT>, y: Vec<FLOAT>) -> FLOAT {
+        let n = x.len() as FLOAT;
+        let x_mean = x.iter().sum::<FLOAT>() / n;
+        let y_mean = y.iter().sum::<FLOAT>() / n;
+        let total = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| (a - x_mean) * (b - y_mean))
+            .sum::<FLOAT>();
+        total / (n - 1.0).max(1.0)
+    }
+
+    /// Pearson correlation coefficient of two vectors of equal length.
+    fn pearson(x: Vec<FLOAT>, y: Vec<FLOAT>) -> FLOAT {
+        covariance(x.clone(), y.clone())
+            / (covariance(x.clone(), x) * covariance(y.clone(), y)).sqrt()
+    }
+
+    /// Applies a pairwise statistic to every pair of columns of a matrix.
+    fn pairwise_columns(
+        matrix: &mut Array,
+        f: fn(Vec<FLOAT>, Vec<FLOAT>) -> FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let columns = crate::matrix_functions::transpose(matrix)?
+            .into_iter()
+            .map(|column| {
+                if_list_convert_to_vec_float_and_do(&mut column.into_array().unwrap(), Ok)
+            })
+            .collect::<Result<Vec<Vec<FLOAT>>, Box<EvalAltResult>>>()?;
+        Ok(columns
+            .iter()
+            .map(|a| {
+                Dynamic::from_array(
+                    columns
+                        .iter()
+                        .map(|b| Dynamic::from_float(f(a.clone(), b.clone())))
+                        .collect(),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the sample covariance of two arrays of equal length, normalized by N - 1.
+    /// ```typescript
+    /// let x = [1, 2, 3, 4];
+    /// let y = [2.0, 4.0, 6.0, 8.0];
+    /// assert_approx_eq(cov(x, y), 10.0/3.0);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { cov([[1, 2]], [1]); } catch { result = "not a list"; }
+    /// assert_eq(result, "not a list");
+    /// ```
+    #[rhai_fn(name = "cov", return_raw, pure)]
+    pub fn cov(x: &mut Array, y: Array) -> Result<FLOAT, Box<EvalAltResult>> {
+        paired_lists(x, y).map(|(x, y)| covariance(x, y))
+    }
+
+    /// Returns the covariance matrix of a matrix whose rows are observations and whose columns are
+    /// variables. For a list, this is the variance.
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [2, 4],
+    ///             [3, 3]];
+    /// assert_eq(cov(data), [[1.0, 0.5], [0.5, 1.0]]);
+    /// ```
+    /// ```typescript
+    /// assert_eq(cov([1, 2, 3, 4]), variance([1, 2, 3, 4]));
+    /// ```
+    #[rhai_fn(name = "cov", return_raw, pure)]
+    pub fn cov_matrix(matrix: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if matrix.iter().any(|el| el.is_array()) {
+            pairwise_columns(matrix, covariance).map(Dynamic::from_array)
+        } else {
+            paired_lists(matrix, matrix.clone()).map(|(x, y)| Dynamic::from_float(covariance(x, y)))
+        }
+    }
+
+    /// Returns the Pearson correlation coefficient of two arrays of equal length.
+    /// ```typescript
+    /// let x = [1, 2, 3, 4];
+    /// let y = [8.0, 6.0, 4.0, 2.0];
+    /// assert_approx_eq(corrcoef(x, y), -1.0);
+    /// ```
+    #[rhai_fn(name = "corrcoef", return_raw, pure)]
+    pub fn corrcoef(x: &mut Array, y: Array) -> Result<FLOAT, Box<EvalAltResult>> {
+        paired_lists(x, y).map(|(x, y)| pearson(x, y))
+    }
 
-            let mut x = crate::matrix_functions::linspace(
-                Dynamic::from_int(0),
-                Dynamic::from_int(100),
-                float_array.len() as INT,
-            )?;
-            crate::misc_functions::interp1(&mut x, sorted_array, p.clone())
-        })
+    /// Returns the matrix of Pearson correlation coefficients between the columns of a matrix
+    /// whose rows are observations and whose columns are variables.
+    /// ```typescript
+    /// let data = [[1, 2],
+    ///             [2, 4],
+    ///             [3, 3]];
+    /// assert_eq(corrcoef(data), [[1.0, 0.5], [0.5, 1.0]]);
+    /// ```
+    #[rhai_fn(name = "corrcoef", return_raw, pure)]
+    pub fn corrcoef_matrix(matrix: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if matrix.iter().any(|el| el.is_array()) {
+            pairwise_columns(matrix, pearson).map(Dynamic::from_array)
+        } else {
+            paired_lists(matrix, matrix.clone()).map(|(x, y)| Dynamic::from_float(pearson(x, y)))
+        }
     }
 
-    /// Returns the inter-quartile range for a 1-D array.
+    /// Builds a map holding a correlation coefficient under the key `name`, and its p-value under
+    /// the key `p`.
+    fn correlation_result(name: ImmutableString, value: FLOAT, p: FLOAT) -> Map {
+        let mut result = BTreeMap::new();
+        result.insert(name.as_str().into(), Dynamic::from_float(value));
+        result.insert("p".into(), Dynamic::from_float(p));
+        result
+    }
+
+    /// Returns Spearman's rank correlation coefficient of two arrays of equal length, along with a
+    /// two-sided p-value for the hypothesis of no correlation, computed from Student's t
+    /// distribution with N - 2 degrees of freedom. The result is a map with the keys `rho` and `p`.
+    /// Tied values receive their average rank. The p-value is NaN for fewer than three pairs.
     /// ```typescript
-    /// let data = [1, 1, 1, 1, 1, 1, 1, 5, 6, 9, 9, 9, 9, 9, 9, 9, 9];
-    /// let inter_quartile_range = iqr(data);
-    /// assert_eq(inter_quartile_range, 8.0);
+    /// let x = [1, 2, 3, 4, 5];
+    /// let y = [5.0, 6.0, 7.0, 8.0, 7.0];
+    /// let result = spearman(x, y);
+    /// assert_approx_eq(result.rho, 0.8207826816681233);
+    /// assert_approx_eq(result.p, 0.08858700531354381, 1e-8);
     /// ```
-    #[rhai_fn(name = "iqr", return_raw, pure)]
-    pub fn iqr(arr: &mut Array) -> Result<FLOAT, Box<EvalAltResult>> {
-        match (
-            prctile(arr, Dynamic::from_int(25)),
-            prctile(arr, Dynamic::from_int(75)),
-        ) {
-            (Ok(low), Ok(high)) => Ok(high - low),
-            (Ok(_), Err(high)) => Err(high),
-            (Err(low), Ok(_)) => Err(low),
-            (Err(low), Err(_)) => Err(low),
+    /// ```typescript
+    /// let result = spearman([1, 2], [3, 4]);
+    /// assert_eq(result.rho, 1.0);
+    /// assert(result.p.is_nan());
+    /// ```
+    #[rhai_fn(name = "spearman", return_raw, pure)]
+    pub fn spearman(x: &mut Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        let (x, y) = paired_lists(x, y)?;
+        if x.iter().chain(y.iter()).any(|v| v.is_nan()) {
+            return Ok(correlation_result("rho".into(), FLOAT::NAN, FLOAT::NAN));
         }
+        let n = x.len() as FLOAT;
+        let rho = pearson(crate::tied_ranks(&x), crate::tied_ranks(&y));
+        let p = if n < 3.0 {
+            FLOAT::NAN
+        } else if rho.abs() >= 1.0 {
+            0.0
+        } else {
+            let t = rho * ((n - 2.0) / (1.0 - rho * rho)).sqrt();
+            2.0 * crate::student_t_cdf(-t.abs(), n - 2.0)
+        };
+        Ok(correlation_result("rho".into(), rho, p))
     }
 
-    /// Returns the mode of a 1-D array.
+    /// Returns Kendall's rank correlation coefficient (tau-b, which accounts for ties) of two
+    /// arrays of equal length, along with a two-sided p-value for the hypothesis of no
+    /// correlation, computed with the normal approximation. The result is a map with the keys
+    /// `tau` and `p`.
     /// ```typescript
-    /// let data = [1, 2, 2, 2, 2, 3];
-    /// let m = mode(data);
-    /// assert_eq(m, 2);
+    /// let x = [1, 2, 3, 4, 5];
+    /// let y = [1.0, 3.0, 2.0, 5.0, 4.0];
+    /// let result = kendall(x, y);
+    /// assert_approx_eq(result.tau, 0.6);
+    /// assert_approx_eq(result.p, 0.14164469029513002, 1e-8);
     /// ```
+    #[rhai_fn(name = "kendall", return_raw, pure)]
+    pub fn kendall(x: &mut Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        let (x, y) = paired_lists(x, y)?;
+        if x.iter().chain(y.iter()).any(|v| v.is_nan()) {
+            return Ok(correlation_result("tau".into(), FLOAT::NAN, FLOAT::NAN));
+        }
+        let n = x.len();
+        let (mut score, mut x_ties, mut y_ties) = (0.0, 0.0, 0.0);
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let dx = x[j] - x[i];
+                let dy = y[j] - y[i];
+                score += (dx * dy).signum() * ((dx * dy != 0.0) as INT as FLOAT);
+                x_ties += (dx == 0.0) as INT as FLOAT;
+                y_ties += (dy == 0.0) as INT as FLOAT;
+            }
+        }
+        let pairs = (n * (n - 1) / 2) as FLOAT;
+        let tau = score / ((pairs - x_ties) * (pairs - y_ties)).sqrt();
+        let n = n as FLOAT;
+        let z = 3.0 * tau * (n * (n - 1.0)).sqrt() / (2.0 * (2.0 * n + 5.0)).sqrt();
+        let p = 2.0 * crate::normal_cdf(-z.abs());
+        Ok(correlation_result("tau".into(), tau, p))
+    }
+
+    /// Returns the cross-correlation of two arrays at every lag from -(N - 1) to N - 1, where N is
+    /// the length of the longer array (the shorter one is padded with zeros). The result is a map
+    /// with the keys `values` and `lags`, where the value at lag `m` is the sum of
+    /// `x[n + m] * y[n]` over all `n`.
     /// ```typescript
-    /// let data = [1.0, 2.0, 2.0, 2.0, 2.0, 3.0];
-    /// let m = mode(data);
-    /// assert_eq(m, 2.0);
+    /// let result = xcorr([1, 2, 3], [1, 1, 1]);
+    /// assert_eq(result.values, [1.0, 3.0, 6.0, 5.0, 3.0]);
+    /// assert_eq(result.lags, [-2, -1, 0, 1, 2]);
     /// ```
-    #[rhai_fn(name = "mode", return_raw, pure)]
-    pub fn mode(arr: &mut Array) -> Result<Dynamic, Box<EvalAltResult>> {
-        if_list_do_int_or_do_float(
-            arr,
-            |arr| {
-                let v = array_to_vec_int(arr);
+    #[rhai_fn(name = "xcorr", return_raw, pure)]
+    pub fn xcorr(x: &mut Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        let maxlag = x.len().max(y.len()).max(1) - 1;
+        xcorr_with_maxlag(x, y, maxlag as INT)
+    }
+
+    /// Returns the cross-correlation of two arrays at every lag from `-maxlag` to `maxlag`.
+    /// ```typescript
+    /// let result = xcorr([1, 2, 3], [1, 1, 1], 1);
+    /// assert_eq(result.values, [3.0, 6.0, 5.0]);
+    /// assert_eq(result.lags, [-1, 0, 1]);
+    /// ```
+    /// ```typescript
+    /// let result = "ok";
+    /// try { xcorr([1, 2, 3], [1, 1, 1], 9223372036854775807); } catch { result = "lag too large"; }
+    /// assert_eq(result, "lag too large");
+    /// ```
+    #[rhai_fn(name = "xcorr", return_raw, pure)]
+    pub fn xcorr_with_maxlag(
+        x: &mut Array,
+        mut y: Array,
+        maxlag: INT,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        if maxlag < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The maximum lag must not be negative".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let x = if_list_convert_to_vec_float_and_do(x, Ok)?;
+        let y = if_list_convert_to_vec_float_and_do(&mut y, Ok)?;
+        let n = x.len().max(y.len()) as INT;
+        if maxlag > (n - 1).max(0) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The maximum lag must not be larger than {}, one less than the array length",
+                    (n - 1).max(0)
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        let at = |v: &Vec<FLOAT>, i: INT| v.get(i as usize).copied().unwrap_or(0.0);
+        let lags = (-maxlag..=maxlag).collect::<Vec<INT>>();
+        let values = lags
+            .iter()
+            .map(|&m| {
+                Dynamic::from_float(
+                    (0.max(-m)..n.min(n - m))
+                        .map(|i| at(&x, i + m) * at(&y, i))
+                        .sum::<FLOAT>(),
+                )
+            })
+            .collect::<Array>();
 
-                let mut counts: HashMap<INT, usize> = HashMap::new();
+        let mut result = BTreeMap::new();
+        result.insert("values".into(), Dynamic::from_array(values));
+        result.insert(
+            "lags".into(),
+            Dynamic::from_array(lags.into_iter().map(Dynamic::from_int).collect()),
+        );
+        Ok(result)
+    }
 
-                Ok(Dynamic::from_int(
-                    v.iter()
-                        .copied()
-                        .max_by_key(|&n| {
-                            let count = counts.entry(n).or_insert(0);
-                            *count += 1;
-                            *count
-                        })
-                        .unwrap(),
-                ))
-            },
-            |arr| {
-                let v = array_to_vec_float(arr);
+    /// Returns the autocorrelation of an array with itself, at every lag from -(N - 1) to N - 1.
+    /// ```typescript
+    /// let result = xcorr([1, 2, 3]);
+    /// assert_eq(result.values, [3.0, 8.0, 14.0, 8.0, 3.0]);
+    /// ```
+    #[rhai_fn(name = "xcorr", return_raw, pure)]
+    pub fn xcorr_self(x: &mut Array) -> Result<Map, Box<EvalAltResult>> {
+        let y = x.clone();
+        xcorr(x, y)
+    }
 
-                let mut counts: HashMap<String, usize> = HashMap::new();
+    /// Returns the sample autocorrelation function of an array (with the mean removed, and
+    /// normalized so that the value at lag 0 is 1) for lags 0 through 20, or through N - 1 if the
+    /// array is shorter. The value at index `k` is the autocorrelation at lag `k`.
+    /// ```typescript
+    /// let acf = autocorr([1, 2, 3, 4]);
+    /// assert_eq(acf, [1.0, 0.25, -0.3, -0.45]);
+    /// ```
+    #[rhai_fn(name = "autocorr", return_raw, pure)]
+    pub fn autocorr(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        let numlags = arr.len().clamp(1, 21) - 1;
+        autocorr_with_numlags(arr, numlags as INT)
+    }
 
-                Ok(Dynamic::from_float(
-                    v.iter()
-                        .copied()
-                        .max_by_key(|&n| {
-                            let count = counts.entry(format!("{:?}", n)).or_insert(0);
-                            *count += 1;
-                            *count
-                        })
-                        .unwrap(),
-                ))
-            },
+    /// Returns the sample autocorrelation function of an array for lags 0 through `numlags`.
+    /// ```typescript
+    /// let acf = autocorr([1, 2, 3, 4], 1);
+    /// assert_eq(acf, [1.0, 0.25]);
+    /// ```
+    #[rhai_fn(name = "autocorr", return_raw, pure)]
+    pub fn autocorr_with_numlags(
+        arr: &mut Array,
+        numlags: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let x = if_list_convert_to_vec_float_and_do(arr, Ok)?;
+        if numlags < 0 || numlags as usize >= x.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "The number of lags must be between 0 and {}",
+                    x.len() as INT - 1
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        let mean = x.iter().sum::<FLOAT>() / x.len() as FLOAT;
+        let centered = x.iter().map(|v| v - mean).collect::<Vec<FLOAT>>();
+        let c0 = centered.iter().map(|v| v * v).sum::<FLOAT>();
+        Ok((0..=numlags as usize)
+            .map(|k| {
+                Dynamic::from_float(
+                    (0..centered.len() - k)
+                        .map(|t| centered[t] * centered[t + k])
+                        .sum::<FLOAT>()
+                        / c0,
+                )
+            })
+            .collect())
+    }
+
+    /// Parses a sort direction, which must be either "ascend" or "descend", returning whether it
+    /// is descending.
+    fn is_descending(direction: ImmutableString) -> Result<bool, Box<EvalAltResult>> {
+        match direction.as_str() {
+            "ascend" => Ok(false),
+            "descend" => Ok(true),
+            _ => Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown sort direction '{direction}', expected 'ascend' or 'descend'"),
+                Position::NONE,
+            )
+            .into()),
+        }
+    }
+
+    /// Returns the permutation that sorts a list of INT and FLOAT values. The sort is stable, and
+    /// NaN values are placed last.
+    fn sort_permutation(arr: Array, descending: bool) -> Result<Vec<usize>, Box<EvalAltResult>> {
+        if arr.iter().any(|el| !el.is_int() && !el.is_float()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The elements of the input array must either be INT or FLOAT".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let mut indices = (0..arr.len()).collect::<Vec<usize>>();
+        indices.sort_by(|&i, &j| crate::compare_numbers_nan_last(&arr[i], &arr[j], descending));
+        Ok(indices)
+    }
+
+    /// Sorts a 1-D array in the given direction, either "ascend" or "descend". The result is a map
+    /// with the sorted `values` and the `indices` of those values in the input. The sort is stable,
+    /// and NaN values are placed last.
+    /// ```typescript
+    /// let s = sort([3, 1, 2, 1], "ascend");
+    /// assert_eq(s.values, [1, 1, 2, 3]);
+    /// assert_eq(s.indices, [1, 3, 2, 0]);
+    /// ```
+    /// ```typescript
+    /// let nan = 0.0 / 0.0;
+    /// let s = sort([2.0, nan, 5.0, 1.0], "descend");
+    /// assert_eq(s.indices, [2, 0, 3, 1]);
+    /// ```
+    #[rhai_fn(name = "sort", return_raw, pure)]
+    pub fn sort_with_indices(
+        arr: &mut Array,
+        direction: ImmutableString,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let indices = sort_permutation(arr.clone(), is_descending(direction)?)?;
+
+        let mut result = BTreeMap::new();
+        let mut vid = smartstring::SmartString::new();
+        vid.push_str("values");
+        result.insert(
+            vid,
+            Dynamic::from_array(indices.iter().map(|&i| arr[i].clone()).collect()),
+        );
+        let mut iid = smartstring::SmartString::new();
+        iid.push_str("indices");
+        result.insert(
+            iid,
+            Dynamic::from_array(
+                indices
+                    .into_iter()
+                    .map(|i| Dynamic::from_int(i as INT))
+                    .collect(),
+            ),
+        );
+        Ok(result)
+    }
+
+    /// Returns the indices that sort a 1-D array in ascending order. The sort is stable, and NaN
+    /// values are placed last.
+    /// ```typescript
+    /// let idx = argsort([30, 10, 20]);
+    /// assert_eq(idx, [1, 2, 0]);
+    /// ```
+    #[rhai_fn(name = "argsort", return_raw, pure)]
+    pub fn argsort(arr: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        argsort_with_direction(arr, "ascend".into())
+    }
+
+    /// Returns the indices that sort a 1-D array in the given direction, either "ascend" or
+    /// "descend".
+    /// ```typescript
+    /// let idx = argsort([30, 10, 20], "descend");
+    /// assert_eq(idx, [0, 2, 1]);
+    /// ```
+    #[rhai_fn(name = "argsort", return_raw, pure)]
+    pub fn argsort_with_direction(
+        arr: &mut Array,
+        direction: ImmutableString,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        Ok(sort_permutation(arr.clone(), is_descending(direction)?)?
+            .into_iter()
+            .map(|i| Dynamic::from_int(i as INT))
+            .collect())
+    }
+
+    /// Tests whether a 1-D array is sorted in ascending order, with any NaN values last.
+    /// ```typescript
+    /// assert_eq(issorted([1, 2, 2, 5]), true);
+    /// assert_eq(issorted([1, 3, 2]), false);
+    /// ```
+    #[rhai_fn(name = "issorted", return_raw, pure)]
+    pub fn issorted(arr: &mut Array) -> Result<bool, Box<EvalAltResult>> {
+        issorted_with_direction(arr, "ascend".into())
+    }
+
+    /// Tests whether a 1-D array is sorted in the given direction, either "ascend" or "descend",
+    /// with any NaN values last.
+    /// ```typescript
+    /// assert_eq(issorted([5.0, 2, 1, 0.0 / 0.0], "descend"), true);
+    /// assert_eq(issorted([1, 2], "descend"), false);
+    /// ```
+    #[rhai_fn(name = "issorted", return_raw, pure)]
+    pub fn issorted_with_direction(
+        arr: &mut Array,
+        direction: ImmutableString,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        let descending = is_descending(direction)?;
+        sort_permutation(arr.clone(), descending)?;
+        Ok(arr.windows(2).all(|pair| {
+            crate::compare_numbers_nan_last(&pair[0], &pair[1], descending)
+                != std::cmp::Ordering::Greater
+        }))
+    }
+
+    /// Sorts the rows of a matrix in ascending order, comparing them by their first column, then
+    /// by their second column, and so on.
+    /// ```typescript
+    /// let m = sortrows([[3, 1],
+    ///                   [1, 2],
+    ///                   [3, 0]]);
+    /// assert_eq(m, [[1, 2],
+    ///               [3, 0],
+    ///               [3, 1]]);
+    /// ```
+    #[rhai_fn(name = "sortrows", return_raw, pure)]
+    pub fn sortrows(matrix: &mut Array) -> Result<Array, Box<EvalAltResult>> {
+        let n = crate::if_matrix_convert_to_vec_array_and_do(matrix, |m| Ok(m[0].len()))?;
+        sortrows_with_directions(
+            matrix,
+            Dynamic::from_array((0..n).map(|j| Dynamic::from_int(j as INT)).collect()),
+            Dynamic::from("ascend"),
         )
     }
 
-    /// Performs ordinary least squares regression and provides a statistical assessment.
+    /// Sorts the rows of a matrix in ascending order, comparing them by the values in the given
+    /// column or array of columns (starting at 0), in turn.
+    /// ```typescript
+    /// let m = sortrows([[3, 1],
+    ///                   [1, 2],
+    ///                   [2, 0]], 1);
+    /// assert_eq(m, [[2, 0],
+    ///               [3, 1],
+    ///               [1, 2]]);
+    /// ```
+    #[rhai_fn(name = "sortrows", return_raw, pure)]
+    pub fn sortrows_by_columns(
+        matrix: &mut Array,
+        columns: Dynamic,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        sortrows_with_directions(matrix, columns, Dynamic::from("ascend"))
+    }
+
+    /// Sorts the rows of a matrix, comparing them by the values in the given column or array of
+    /// columns (starting at 0), in turn. The direction is either "ascend" or "descend", or an array
+    /// with one direction per column. The sort is stable, and NaN values are placed last.
+    /// ```typescript
+    /// let data = [[1, 10],
+    ///             [2, 30],
+    ///             [1, 20]];
+    /// let m = sortrows(data, [0, 1], ["ascend", "descend"]);
+    /// assert_eq(m, [[1, 20],
+    ///               [1, 10],
+    ///               [2, 30]]);
+    /// ```
+    /// ```typescript
+    /// let m = sortrows([[1, 10], [2, 30], [1, 20]], 1, "descend");
+    /// assert_eq(m, [[2, 30], [1, 20], [1, 10]]);
+    /// ```
+    #[rhai_fn(name = "sortrows", return_raw, pure)]
+    pub fn sortrows_with_directions(
+        matrix: &mut Array,
+        columns: Dynamic,
+        directions: Dynamic,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        crate::if_matrix_convert_to_vec_array_and_do(matrix, |matrix_as_vec| {
+            if !crate::validation_functions::is_numeric_array(&mut matrix_as_vec.concat()) {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "The elements of the input matrix must either be INT or FLOAT".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+
+            let n = matrix_as_vec[0].len() as INT;
+            let columns = if columns.is_array() {
+                columns.clone().into_array().unwrap()
+            } else {
+                vec![columns.clone()]
+            };
+            let columns = columns
+                .iter()
+                .map(|c| match c.as_int() {
+                    Ok(c) if (0..n).contains(&c) => Ok(c as usize),
+                    _ => Err(EvalAltResult::ErrorArithmetic(
+                        format!("The columns must be INT values from 0 to {}", n - 1),
+                        Position::NONE,
+                    )
+                    .into()),
+                })
+                .collect::<Result<Vec<usize>, Box<EvalAltResult>>>()?;
+
+            let descending = if directions.is_array() {
+                directions
+                    .clone()
+                    .into_array()
+                    .unwrap()
+                    .into_iter()
+                    .map(|d| {
+                        d.into_immutable_string()
+                            .map_err(|_| {
+                                EvalAltResult::ErrorArithmetic(
+                                    "The sort directions must be strings".to_string(),
+                                    Position::NONE,
+                                )
+                                .into()
+                            })
+                            .and_then(is_descending)
+                    })
+                    .collect::<Result<Vec<bool>, Box<EvalAltResult>>>()?
+            } else {
+                let d = directions.clone().into_immutable_string().map_err(|_| {
+                    EvalAltResult::ErrorArithmetic(
+                        "The sort direction must be a string".to_string(),
+                        Position::NONE,
+                    )
+                })?;
+                vec![is_descending(d)?; columns.len()]
+            };
+            if descending.len() != columns.len() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    "There must be one sort direction for each column".to_string(),
+                    Position::NONE,
+                )
+                .into());
+            }
+
+            let mut indices = (0..matrix_as_vec.len()).collect::<Vec<usize>>();
+            indices.sort_by(|&i, &j| {
+                columns
+                    .iter()
+                    .zip(&descending)
+                    .map(|(&c, &desc)| {
+                        crate::compare_numbers_nan_last(
+                            &matrix_as_vec[i][c],
+                            &matrix_as_vec[j][c],
+                            desc,
+                        )
+                    })
+                    .find(|o| o.is_ne())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            Ok(indices
+                .into_iter()
+                .map(|i| Dynamic::from_array(matrix_as_vec[i].clone()))
+                .collect())
+        })
+    }
+
+    /// Reads the significance level `alpha` (default 0.05) of the confidence intervals of a
+    /// regression from a map of options, which must have no other keys.
+    #[cfg(feature = "nalgebra")]
+    fn significance_level(options: Map) -> Result<FLOAT, Box<EvalAltResult>> {
+        if let Some(key) = options.keys().find(|k| k.as_str() != "alpha") {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Unknown option '{key}', expected alpha"),
+                Position::NONE,
+            )
+            .into());
+        }
+        let alpha = match options.get("alpha") {
+            Some(alpha) => crate::if_int_convert_to_float_and_do(alpha.clone(), Ok)?,
+            None => 0.05,
+        };
+        if !(alpha > 0.0 && alpha < 1.0) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "The significance level must be between 0 and 1".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        Ok(alpha)
+    }
+
+    /// Returns the pseudo-inverse and the rank of a design matrix. As in `pinv` and `rank`,
+    /// singular values at or below `max(rows, columns) * epsilon * largest singular value` are
+    /// treated as zero, so collinear columns make the design rank-deficient.
+    #[cfg(feature = "nalgebra")]
+    fn pseudo_inverse_and_rank(
+        design: nalgebralib::DMatrix<FLOAT>,
+    ) -> Result<(nalgebralib::DMatrix<FLOAT>, FLOAT), Box<EvalAltResult>> {
+        let error =
+            |message: &str| EvalAltResult::ErrorArithmetic(message.to_string(), Position::NONE);
+        if design.iter().any(|v| !v.is_finite()) {
+            return Err(error("The design matrix must contain only finite values").into());
+        }
+        let max_dim = design.nrows().max(design.ncols()) as FLOAT;
+        let svd = design
+            .try_svd(true, true, FLOAT::EPSILON, 0)
+            .ok_or_else(|| {
+                error("Computing the singular-value decomposition of the design matrix failed")
+            })?;
+        let s_max = svd.singular_values.iter().copied().fold(0.0, FLOAT::max);
+        let tol = max_dim * FLOAT::EPSILON * s_max;
+        let rank = svd.singular_values.iter().filter(|&&s| s > tol).count() as FLOAT;
+        let pinv = svd.pseudo_inverse(tol).map_err(error)?;
+        Ok((pinv, rank))
+    }
+
+    /// Adds the goodness-of-fit statistics of a least squares fit of `y`, with the given
+    /// residuals and design matrix rank, to a result map, and returns the residual degrees of
+    /// freedom. When `has_intercept` is true, R² and the F test compare the model to one with
+    /// only an intercept; otherwise, to one with no terms at all.
+    #[cfg(feature = "nalgebra")]
+    fn insert_fit_statistics(
+        result: &mut Map,
+        y: Vec<FLOAT>,
+        residuals: Vec<FLOAT>,
+        rank: FLOAT,
+        has_intercept: bool,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        let n = y.len() as FLOAT;
+        let ssr = residuals.iter().map(|r| r * r).sum::<FLOAT>();
+        let (df_model, df_resid, tss) = if has_intercept {
+            let mean = y.iter().sum::<FLOAT>() / n;
+            let tss = y.iter().map(|v| (v - mean).powi(2)).sum::<FLOAT>();
+            (rank - 1.0, n - rank, tss)
+        } else {
+            (rank, n - rank, y.iter().map(|v| v * v).sum::<FLOAT>())
+        };
+        if df_resid < 1.0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "There are not enough residual degrees of freedom to perform statistics on this model"
+                    .to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        let rsquared = 1.0 - ssr / tss;
+        let adj_rsquared = 1.0 - (df_model + df_resid) / df_resid * (1.0 - rsquared);
+        let f_statistic = ((tss - ssr) / df_model) / (ssr / df_resid);
+        let f_pvalue = crate::regularized_beta(
+            df_resid / 2.0,
+            df_model / 2.0,
+            df_resid / (df_resid + df_model * f_statistic),
+        );
+        let fitted = y
+            .iter()
+            .zip(residuals.iter())
+            .map(|(v, r)| Dynamic::from_float(v - r))
+            .collect::<Array>();
+
+        result.insert("rsquared".into(), Dynamic::from_float(rsquared));
+        result.insert("adj_rsquared".into(), Dynamic::from_float(adj_rsquared));
+        result.insert("f_statistic".into(), Dynamic::from_float(f_statistic));
+        result.insert("f_pvalue".into(), Dynamic::from_float(f_pvalue));
+        result.insert("fitted".into(), Dynamic::from_array(fitted));
+        result.insert(
+            "residuals".into(),
+            Dynamic::from_array(residuals.into_iter().map(Dynamic::from_float).collect()),
+        );
+        Ok(df_resid)
+    }
+
+    /// Returns the `[lower, upper]` confidence interval of each coefficient, given its estimate
+    /// and standard error, at the significance level `alpha`.
+    #[cfg(feature = "nalgebra")]
+    fn coefficient_intervals(
+        parameters: Vec<FLOAT>,
+        standard_errors: Vec<FLOAT>,
+        df_resid: FLOAT,
+        alpha: FLOAT,
+    ) -> Vec<Dynamic> {
+        let critical_value = crate::Distribution::StudentT { nu: df_resid }.inv(1.0 - alpha / 2.0);
+        parameters
+            .iter()
+            .zip(standard_errors)
+            .map(|(b, se)| {
+                Dynamic::from_array(vec![
+                    Dynamic::from_float(b - critical_value * se),
+                    Dynamic::from_float(b + critical_value * se),
+                ])
+            })
+            .collect()
+    }
+
+    /// Performs ordinary least squares regression and provides a statistical assessment. See
+    /// `regress(x, y, options)` for a description of the result.
     /// ```typescript
     /// let x = [[1.0, 0.0],
     ///          [1.0, 1.0],
@@ -542,67 +2344,471 @@ pub mod stats {
     ///          [0.8],
     ///          [2.1]];
     /// let b = regress(x, y);
-    /// assert_eq(b,  #{"parameters": [-2.220446049250313e-16, 1.0000000000000002],
-    ///                 "pvalues": [1.0, 0.10918255350924745],
-    ///                 "standard_errors": [0.11180339887498947, 0.17320508075688767]});
+    /// assert_eq(b.parameters, [-2.220446049250313e-16, 1.0000000000000002]);
+    /// assert_eq(b.pvalues, [1.0, 0.10918255350924745]);
+    /// assert_eq(b.standard_errors, [0.11180339887498947, 0.17320508075688767]);
+    /// ```
+    /// ```typescript
+    /// let x = [[1.0, 0.0],
+    ///          [1.0, 1.0],
+    ///          [1.0, 2.0]];
+    /// let y = [0.1, 0.8, 2.1];
+    /// let b = regress(x, y);
+    /// assert_approx_eq(b.rsquared, 0.970873786407767);
+    /// assert_approx_eq(b.adj_rsquared, 0.941747572815534);
+    /// assert_approx_eq(b.f_statistic, 33.33333333333333, 1e-8);
+    /// assert_approx_eq(b.f_pvalue, 0.10918255350924745, 1e-8);
+    /// assert_approx_eq(b.fitted, [0.0, 1.0, 2.0]);
+    /// assert_approx_eq(b.residuals, [0.1, -0.2, 0.1]);
+    /// assert_approx_eq(flatten(b.covariance), [0.0125, -0.015, -0.015, 0.03]);
     /// ```
     #[cfg(feature = "nalgebra")]
     #[rhai_fn(name = "regress", return_raw, pure)]
     pub fn regress(x: &mut Array, y: Array) -> Result<Map, Box<EvalAltResult>> {
+        regress_with_options(x, y, Map::new())
+    }
+
+    /// Performs ordinary least squares regression with options: the significance level `alpha`
+    /// (default 0.05) of the confidence intervals. The model always includes an intercept. The
+    /// result is a map with the keys
+    /// - `parameters`, the estimated coefficient of each column of `x`,
+    /// - `standard_errors` and `pvalues`, the standard error of each coefficient and the p-value
+    ///   of its t statistic,
+    /// - `confidence_intervals`, a `[lower, upper]` pair for each coefficient,
+    /// - `covariance`, the covariance matrix of the coefficients,
+    /// - `rsquared` and `adj_rsquared`, the coefficient of determination and its adjusted value,
+    /// - `f_statistic` and `f_pvalue`, the F statistic of the regression and its p-value,
+    /// - `fitted` and `residuals`, the fitted values and residuals of each observation.
+    /// ```typescript
+    /// let x = [[1.0, 0.0],
+    ///          [1.0, 1.0],
+    ///          [1.0, 2.0]];
+    /// let y = [0.1, 0.8, 2.1];
+    /// let b = regress(x, y, #{alpha: 0.1});
+    /// assert_approx_eq(b.confidence_intervals[1], [-0.09357384097821075, 2.093573840978211], 1e-8);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "regress", return_raw, pure)]
+    pub fn regress_with_options(
+        x: &mut Array,
+        y: Array,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
         use linregress::{FormulaRegressionBuilder, RegressionDataBuilder};
-        let x_transposed = crate::matrix_functions::transpose(x)?;
-        let mut data: Vec<(String, Vec<f64>)> = vec![];
-        let mut vars = vec![];
-        for (iter, column) in x_transposed.iter().enumerate() {
-            let var_name = format!("x_{iter}");
-            vars.push(var_name.clone());
-            data.push((
-                var_name,
-                array_to_vec_float(&mut column.clone().into_array().unwrap()),
-            ));
-        }
-        data.push((
-            "y".to_string(),
-            array_to_vec_float(&mut crate::matrix_functions::flatten(&mut y.clone())),
-        ));
-
-        let regress_data = RegressionDataBuilder::new().build_from(data).unwrap();
-
-        let model = FormulaRegressionBuilder::new()
-            .data(&regress_data)
-            .data_columns("y", vars)
-            .fit()
-            .map_err(|e| EvalAltResult::ErrorArithmetic(e.to_string(), Position::NONE))?;
-
-        let parameters = Dynamic::from_array(
-            model
+        let alpha = significance_level(options)?;
+        let y = if_list_convert_to_vec_float_and_do(
+            &mut crate::matrix_functions::flatten(&mut y.clone()),
+            Ok,
+        )?;
+
+        crate::if_matrix_convert_to_dmatrix_and_do(x, |xm| {
+            if xm.nrows() != y.len() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!(
+                        "The number of rows of x ({}) must match the number of observations of y ({})",
+                        xm.nrows(),
+                        y.len()
+                    ),
+                    Position::NONE,
+                )
+                .into());
+            }
+            let mut data: Vec<(String, Vec<FLOAT>)> = vec![];
+            let mut vars = vec![];
+            for (iter, column) in xm.column_iter().enumerate() {
+                let var_name = format!("x_{iter}");
+                vars.push(var_name.clone());
+                data.push((var_name, column.iter().copied().collect()));
+            }
+            data.push(("y".to_string(), y.clone()));
+
+            let regress_data = RegressionDataBuilder::new()
+                .build_from(data)
+                .map_err(|e| EvalAltResult::ErrorArithmetic(e.to_string(), Position::NONE))?;
+            let model = FormulaRegressionBuilder::new()
+                .data(&regress_data)
+                .data_columns("y", vars)
+                .fit()
+                .map_err(|e| EvalAltResult::ErrorArithmetic(e.to_string(), Position::NONE))?;
+            let parameters = model
                 .iter_parameter_pairs()
-                .map(|x| Dynamic::from_float(x.1))
-                .collect::<Array>(),
+                .map(|x| x.1)
+                .collect::<Vec<_>>();
+            let pvalues = model.iter_p_value_pairs().map(|x| x.1).collect::<Vec<_>>();
+            let standard_errors = model.iter_se_pairs().map(|x| x.1).collect::<Vec<_>>();
+
+            // The model fitted by linregress has an intercept ahead of the columns of x, which is
+            // left out of the coefficients that are reported.
+            let (pinv, rank) = pseudo_inverse_and_rank(xm.clone().insert_column(0, 1.0))?;
+            let covariance = (&pinv * pinv.transpose() * model.scale())
+                .remove_row(0)
+                .remove_column(0);
+            let mut result = BTreeMap::new();
+            let df_resid = insert_fit_statistics(
+                &mut result,
+                y.clone(),
+                model.residuals().to_vec(),
+                rank,
+                true,
+            )?;
+            let to_array = |values: Vec<FLOAT>| {
+                Dynamic::from_array(values.into_iter().map(Dynamic::from_float).collect())
+            };
+            result.insert(
+                "confidence_intervals".into(),
+                Dynamic::from_array(coefficient_intervals(
+                    parameters.clone(),
+                    standard_errors.clone(),
+                    df_resid,
+                    alpha,
+                )),
+            );
+            result.insert(
+                "covariance".into(),
+                Dynamic::from_array(crate::omatrix_to_vec_dynamic(covariance)),
+            );
+            result.insert("parameters".into(), to_array(parameters));
+            result.insert("pvalues".into(), to_array(pvalues));
+            result.insert("standard_errors".into(), to_array(standard_errors));
+            Ok(result)
+        })
+    }
+
+    /// The largest number of factors in a single `a*b*...` term of a model formula, which expands
+    /// into `2^n - 1` terms.
+    #[cfg(feature = "nalgebra")]
+    const MAX_FORMULA_FACTORS: usize = 10;
+
+    /// Splits the right-hand side of a model formula into its terms, each of which is a list of
+    /// the variables that it multiplies together, and whether the model has an intercept. Terms
+    /// are separated by `+`, `a:b` is the interaction of `a` and `b`, `a*b` is shorthand for
+    /// `a + b + a:b`, and `- 1` or `+ 0` removes the intercept.
+    #[cfg(feature = "nalgebra")]
+    fn parse_formula_terms(
+        rhs: ImmutableString,
+    ) -> Result<(Vec<Vec<String>>, bool), Box<EvalAltResult>> {
+        let error = |msg: String| -> Box<EvalAltResult> {
+            EvalAltResult::ErrorArithmetic(msg, Position::NONE).into()
+        };
+        let mut terms: Vec<Vec<String>> = vec![];
+        let mut has_intercept = true;
+        for (idx, token) in rhs.replace('-', "+-").split('+').enumerate() {
+            let token = match token.trim().strip_prefix('-') {
+                Some(removed) => format!("-{}", removed.trim()),
+                None => token.trim().to_string(),
+            };
+            let token = token.as_str();
+            match token {
+                "" if idx == 0 => continue,
+                "1" => has_intercept = true,
+                "0" | "-1" => has_intercept = false,
+                "" => return Err(error(format!("The formula has an empty term in '{rhs}'"))),
+                _ if token.starts_with('-') => {
+                    return Err(error(format!(
+                        "Removing the term '{}' is not supported, only '- 1'",
+                        token[1..].trim()
+                    )))
+                }
+                _ => {
+                    // Expand a*b*... into every interaction of its factors
+                    let factors = token
+                        .split('*')
+                        .map(|f| f.trim().to_string())
+                        .collect::<Vec<String>>();
+                    if factors.len() > MAX_FORMULA_FACTORS {
+                        return Err(error(format!(
+                            "The term '{token}' has more than {MAX_FORMULA_FACTORS} factors"
+                        )));
+                    }
+                    let mut expanded: Vec<Vec<String>> = vec![];
+                    for mask in 1..(1_usize << factors.len()) {
+                        let mut term = vec![];
+                        for (i, factor) in factors.iter().enumerate() {
+                            if mask & (1 << i) != 0 {
+                                term.extend(factor.split(':').map(|v| v.trim().to_string()));
+                            }
+                        }
+                        expanded.push(term);
+                    }
+                    expanded.sort_by_key(|term| term.len());
+                    for term in expanded {
+                        if term.iter().any(|v| v.is_empty()) {
+                            return Err(error(format!("The formula has an empty term in '{rhs}'")));
+                        }
+                        if !terms.contains(&term) {
+                            terms.push(term);
+                        }
+                    }
+                }
+            }
+        }
+        Ok((terms, has_intercept))
+    }
+
+    /// Encodes a column of data as named regressors: a column of INT and/or FLOAT values is used
+    /// as it is, and a column of strings is treated as categorical, with one indicator column
+    /// for each level except the first in sorted order, which is the reference level.
+    #[cfg(feature = "nalgebra")]
+    fn encode_variable(
+        column: Array,
+        name: String,
+    ) -> Result<Vec<(String, Vec<FLOAT>)>, Box<EvalAltResult>> {
+        if column.iter().all(|el| el.is_string()) {
+            let values = column
+                .into_iter()
+                .map(|el| el.into_immutable_string().unwrap())
+                .collect::<Vec<ImmutableString>>();
+            let mut levels = values.clone();
+            levels.sort();
+            levels.dedup();
+            Ok(levels
+                .iter()
+                .skip(1)
+                .map(|level| {
+                    (
+                        format!("{name}_{level}"),
+                        values
+                            .iter()
+                            .map(|v| if v == level { 1.0 } else { 0.0 })
+                            .collect(),
+                    )
+                })
+                .collect())
+        } else if crate::is_int_or_float_list(&column) {
+            Ok(vec![(
+                name,
+                if_list_convert_to_vec_float_and_do(&mut column.clone(), Ok)?,
+            )])
+        } else {
+            Err(EvalAltResult::ErrorArithmetic(
+                format!("The column '{name}' must contain only numbers or only strings"),
+                Position::NONE,
+            )
+            .into())
+        }
+    }
+
+    /// Fits a linear model to a map of named data columns, as described by a formula. See
+    /// `fitlm(data, formula, options)` for a description of the formula and the result.
+    /// ```typescript
+    /// let data = #{
+    ///     x: [0, 1, 2, 3, 4, 5],
+    ///     y: [1.1, 2.9, 5.2, 7.1, 8.8, 11.0]
+    /// };
+    /// let model = fitlm(data, "y ~ x");
+    /// assert_eq(model.terms, ["(Intercept)", "x"]);
+    /// assert_approx_eq(model.parameters["(Intercept)"], 1.0809523809523809, 1e-10);
+    /// assert_approx_eq(model.parameters.x, 1.9742857142857142, 1e-10);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "fitlm", return_raw)]
+    pub fn fitlm(data: Map, formula: ImmutableString) -> Result<Map, Box<EvalAltResult>> {
+        fitlm_with_options(data, formula, Map::new())
+    }
+
+    /// Fits a linear model to a map of named data columns, as described by a formula, with
+    /// options: the significance level `alpha` (default 0.05) of the confidence intervals.
+    ///
+    /// The formula has the form `"y ~ terms"`, where `y` is the response column and the terms are
+    /// separated by `+`. A term is a column name, an interaction `a:b`, or `a*b`, which is
+    /// shorthand for `a + b + a:b`. The model includes an intercept unless the formula contains
+    /// `- 1` or `+ 0`. Columns of strings are categorical: each level other than the first in
+    /// sorted order gets an indicator column named `column_level`. If some terms are collinear,
+    /// the minimum-norm coefficients are returned and the degrees of freedom use the rank of the
+    /// design matrix.
+    ///
+    /// The result is a map with the keys
+    /// - `terms`, the names of the coefficients in order, starting with `(Intercept)`,
+    /// - `parameters`, `standard_errors`, `pvalues` and `confidence_intervals`, maps from each
+    ///   term name to its estimate, standard error, p-value and `[lower, upper]` interval,
+    /// - `covariance`, the covariance matrix of the coefficients, in the order of `terms`,
+    /// - `rsquared`, `adj_rsquared`, `f_statistic`, `f_pvalue`, `fitted` and `residuals`, as
+    ///   for `regress`.
+    /// ```typescript
+    /// let data = #{
+    ///     dose: [1, 2, 3, 1, 2, 3, 1, 2, 3],
+    ///     drug: ["a", "a", "a", "b", "b", "b", "c", "c", "c"],
+    ///     response: [2.0, 3.9, 6.1, 3.1, 5.0, 6.8, 1.2, 1.9, 3.1]
+    /// };
+    /// let model = fitlm(data, "response ~ dose + drug");
+    /// assert_eq(model.terms, ["(Intercept)", "dose", "drug_b", "drug_c"]);
+    /// assert_approx_eq(model.parameters.drug_b, 0.9666666666666667, 1e-10);
+    /// assert_approx_eq(model.parameters.drug_c, -1.9333333333333333, 1e-10);
+    /// ```
+    /// ```typescript
+    /// let data = #{
+    ///     x1: [1, 2, 3, 4, 1, 2, 3, 4],
+    ///     x2: [0, 0, 0, 0, 1, 1, 1, 1],
+    ///     y:  [3.0, 5.1, 6.9, 9.0, 4.1, 8.0, 11.9, 16.1]
+    /// };
+    /// let model = fitlm(data, "y ~ x1*x2", #{alpha: 0.01});
+    /// assert_eq(model.terms, ["(Intercept)", "x1", "x2", "x1:x2"]);
+    /// assert_approx_eq(model.parameters["x1:x2"], 2.01, 1e-10);
+    /// assert_eq(model.rsquared > 0.99, true);
+    /// ```
+    /// ```typescript
+    /// let data = #{x: [1, 2, 3, 4], y: [2.1, 3.9, 6.2, 7.8]};
+    /// let model = fitlm(data, "y ~ x - 1");
+    /// assert_eq(model.terms, ["x"]);
+    /// assert_approx_eq(model.parameters.x, 1.99, 1e-10);
+    /// ```
+    /// ```typescript
+    /// let data = #{x: [1, 2.5, 3, 4], y: [2.0, 5.0, 6.0, 8.0]};
+    /// let model = fitlm(data, "y ~ x");
+    /// assert_approx_eq(model.parameters.x, 2.0, 1e-10);
+    /// ```
+    /// ```typescript
+    /// let data = #{a: [1, 2, 3], y: [1, 2, 3]};
+    /// let result = "ok";
+    /// try { fitlm(data, "y ~ a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a"); } catch { result = "too many factors"; }
+    /// assert_eq(result, "too many factors");
+    /// ```
+    /// ```typescript
+    /// let data = #{
+    ///     x1: [1, 2, 3, 4, 5],
+    ///     x2: [2, 4, 6, 8, 10],
+    ///     y:  [2.1, 3.9, 6.2, 7.8, 10.1]
+    /// };
+    /// let model = fitlm(data, "y ~ x1 + x2");
+    /// assert_approx_eq(model.parameters.x1, 0.398, 1e-10);
+    /// assert_approx_eq(model.parameters.x2, 0.796, 1e-10);
+    /// assert_approx_eq(model.adj_rsquared, 0.9964071052013028, 1e-10);
+    /// ```
+    #[cfg(feature = "nalgebra")]
+    #[rhai_fn(name = "fitlm", return_raw)]
+    pub fn fitlm_with_options(
+        data: Map,
+        formula: ImmutableString,
+        options: Map,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        let alpha = significance_level(options)?;
+        let error = |msg: String| -> Box<EvalAltResult> {
+            EvalAltResult::ErrorArithmetic(msg, Position::NONE).into()
+        };
+        let (lhs, rhs) = formula.split_once('~').ok_or_else(|| {
+            error(format!(
+                "The formula '{formula}' must have the form 'y ~ terms'"
+            ))
+        })?;
+        let column = |name: &str| -> Result<Array, Box<EvalAltResult>> {
+            data.get(name)
+                .ok_or_else(|| error(format!("There is no column named '{name}' in the data")))?
+                .clone()
+                .into_array()
+                .map_err(|_| error(format!("The column '{name}' must be an array")))
+        };
+
+        let response = lhs.trim();
+        let y = if_list_convert_to_vec_float_and_do(&mut column(response)?, Ok)
+            .map_err(|_| error(format!("The response '{response}' must be numeric")))?;
+        let n = y.len();
+        let (terms, has_intercept) = parse_formula_terms(rhs.into())?;
+
+        // Build the columns of the design matrix, term by term
+        let mut encoded: HashMap<String, Vec<(String, Vec<FLOAT>)>> = HashMap::new();
+        let mut regressors: Vec<(String, Vec<FLOAT>)> = vec![];
+        if has_intercept {
+            regressors.push(("(Intercept)".to_string(), vec![1.0; n]));
+        }
+        for term in terms {
+            let mut columns = vec![(String::new(), vec![1.0; n])];
+            for variable in term {
+                if !encoded.contains_key(&variable) {
+                    let variable_columns = encode_variable(column(&variable)?, variable.clone())?;
+                    if variable_columns.iter().any(|(_, v)| v.len() != n) {
+                        return Err(error(format!(
+                            "The column '{variable}' must have the same length as '{response}'"
+                        )));
+                    }
+                    encoded.insert(variable.clone(), variable_columns);
+                }
+                columns = columns
+                    .iter()
+                    .flat_map(|(name, values)| {
+                        encoded[&variable].iter().map(move |(other, other_values)| {
+                            (
+                                if name.is_empty() {
+                                    other.clone()
+                                } else {
+                                    format!("{name}:{other}")
+                                },
+                                values
+                                    .iter()
+                                    .zip(other_values)
+                                    .map(|(a, b)| a * b)
+                                    .collect::<Vec<FLOAT>>(),
+                            )
+                        })
+                    })
+                    .collect();
+            }
+            regressors.extend(columns);
+        }
+        if regressors.is_empty() {
+            return Err(error(format!("The formula '{formula}' has no terms")));
+        }
+
+        let design = nalgebralib::DMatrix::from_fn(n, regressors.len(), |i, j| regressors[j].1[i]);
+        let (pinv, rank) = pseudo_inverse_and_rank(design.clone())?;
+        let y_vector = nalgebralib::DVector::from_vec(y.clone());
+        let parameters = &pinv * &y_vector;
+        let residuals = (y_vector - &design * &parameters).data.as_vec().clone();
+
+        let mut result = BTreeMap::new();
+        let df_resid =
+            insert_fit_statistics(&mut result, y, residuals.clone(), rank, has_intercept)?;
+        let scale = residuals.iter().map(|r| r * r).sum::<FLOAT>() / df_resid;
+        let covariance = &pinv * pinv.transpose() * scale;
+        let parameters = parameters.data.as_vec().clone();
+        let standard_errors = covariance
+            .diagonal()
+            .map(|v| v.sqrt())
+            .data
+            .as_vec()
+            .clone();
+        let t_distribution = crate::Distribution::StudentT { nu: df_resid };
+        let pvalues = parameters
+            .iter()
+            .zip(standard_errors.iter())
+            .map(|(b, se)| 2.0 * t_distribution.cdf(-(b / se).abs()))
+            .collect::<Vec<FLOAT>>();
+        let intervals =
+            coefficient_intervals(parameters.clone(), standard_errors.clone(), df_resid, alpha);
+        let by_term = |values: Vec<Dynamic>| {
+            Dynamic::from_map(
+                regressors
+                    .iter()
+                    .zip(values)
+                    .map(|((name, _), v)| (name.into(), v))
+                    .collect::<Map>(),
+            )
+        };
+        let to_dynamic = |values: Vec<FLOAT>| {
+            values
+                .into_iter()
+                .map(Dynamic::from_float)
+                .collect::<Vec<Dynamic>>()
+        };
+        result.insert(
+            "terms".into(),
+            Dynamic::from_array(
+                regressors
+                    .iter()
+                    .map(|(name, _)| Dynamic::from(name.clone()))
+                    .collect(),
+            ),
         );
-        let pvalues = Dynamic::from_array(
-            model
-                .iter_p_value_pairs()
-                .map(|x| Dynamic::from_float(x.1))
-                .collect::<Array>(),
+        result.insert("parameters".into(), by_term(to_dynamic(parameters)));
+        result.insert(
+            "standard_errors".into(),
+            by_term(to_dynamic(standard_errors)),
         );
-        let standard_errors = Dynamic::from_array(
-            model
-                .iter_se_pairs()
-                .map(|x| Dynamic::from_float(x.1))
-                .collect::<Array>(),
+        result.insert("pvalues".into(), by_term(to_dynamic(pvalues)));
+        result.insert("confidence_intervals".into(), by_term(intervals));
+        result.insert(
+            "covariance".into(),
+            Dynamic::from_array(crate::omatrix_to_vec_dynamic(covariance)),
         );
-
-        let mut result = BTreeMap::new();
-        let mut params = smartstring::SmartString::new();
-        params.push_str("parameters");
-        result.insert(params, parameters);
-        let mut pv = smartstring::SmartString::new();
-        pv.push_str("pvalues");
-        result.insert(pv, pvalues);
-        let mut se = smartstring::SmartString::new();
-        se.push_str("standard_errors");
-        result.insert(se, standard_errors);
         Ok(result)
     }
 }